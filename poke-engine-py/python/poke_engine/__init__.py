@@ -1,4 +1,4 @@
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 from enum import StrEnum
 
 from .poke_engine import *
@@ -43,11 +43,38 @@ class MctsSideResult:
     :type total_score: float
     :param visits: The number of times the move was chosen
     :type visits: int
+    :param nash_probability: The probability of playing the move in the approximate Nash equilibrium, if one was solved for
+    :type nash_probability: float | None
     """
 
     move_choice: str
     total_score: float
     visits: int
+    nash_probability: float | None = None
+
+
+@dataclass
+class PrincipalVariationPly:
+    """
+    One ply of the line a Monte Carlo Tree Search expects to be played
+
+    :param side_one_move: Side one's most visited option
+    :type side_one_move: tuple[str, str]
+    :param side_two_move: Side two's most visited option
+    :type side_two_move: tuple[str, str]
+    :param instructions: The most likely outcome of the two options
+    :type instructions: StateInstructions
+    :param visits: The number of visits to the node that outcome leads to
+    :type visits: int
+    :param average_score: Side one's average score at that node
+    :type average_score: float
+    """
+
+    side_one_move: tuple[str, str]
+    side_two_move: tuple[str, str]
+    instructions: StateInstructions
+    visits: int
+    average_score: float
 
 
 @dataclass
@@ -61,11 +88,20 @@ class MctsResult:
     :type side_two: list[MctsSideResult]
     :param total_visits: Total number of monte carlo iterations
     :type total_visits: int
+    :param principal_variation: The line the search expects, empty unless MctsConfig.principal_variation_plies is set
+    :type principal_variation: list[PrincipalVariationPly]
+    :param max_leaf_depth: The most turns below the root that an iteration ended its selection on
+    :type max_leaf_depth: int
+    :param average_leaf_depth: The average turns below the root that the iterations ended their selection on
+    :type average_leaf_depth: float
     """
 
     side_one: list[MctsSideResult]
     side_two: list[MctsSideResult]
     total_visits: int
+    principal_variation: list[PrincipalVariationPly] = field(default_factory=list)
+    max_leaf_depth: int = 0
+    average_leaf_depth: float = 0.0
 
     @classmethod
     def _from_rust(cls, rust_result):
@@ -75,6 +111,7 @@ class MctsResult:
                     move_choice=i.move_choice,
                     total_score=i.total_score,
                     visits=i.visits,
+                    nash_probability=i.nash_probability,
                 )
                 for i in rust_result.side_one
             ],
@@ -83,48 +120,319 @@ class MctsResult:
                     move_choice=i.move_choice,
                     total_score=i.total_score,
                     visits=i.visits,
+                    nash_probability=i.nash_probability,
                 )
                 for i in rust_result.side_two
             ],
             total_visits=rust_result.iteration_count,
+            principal_variation=[
+                PrincipalVariationPly(
+                    side_one_move=i.side_one_move,
+                    side_two_move=i.side_two_move,
+                    instructions=i.instructions,
+                    visits=i.visits,
+                    average_score=i.average_score,
+                )
+                for i in rust_result.principal_variation
+            ],
+            max_leaf_depth=rust_result.max_leaf_depth,
+            average_leaf_depth=rust_result.average_leaf_depth,
+        )
+
+
+@dataclass
+class TeamPreviewTeam:
+    """
+    One team a side can bring at team preview
+
+    :param team: The party indices of the two leads, then the two pokemon in the back
+    :type team: tuple[int, int, int, int]
+    :param matchup: The team's matchup estimate, which teams are pruned by
+    :type matchup: float
+    :param visits: The number of times the team was chosen
+    :type visits: int
+    :param probability: The team's Nash probability if one was solved, otherwise its share of the visits
+    :type probability: float
+    """
+
+    team: tuple[int, int, int, int]
+    matchup: float
+    visits: int
+    probability: float
+
+
+@dataclass
+class TeamPreviewSideResult:
+    """
+    One side's result of a team preview search
+
+    :param teams: Every team that was searched, most probable first
+    :type teams: list[TeamPreviewTeam]
+    :param brings: The probability of bringing each pokemon, by party index
+    :type brings: list[float]
+    :param leads: The probability of leading with each pokemon, by party index
+    :type leads: list[float]
+    """
+
+    teams: list[TeamPreviewTeam]
+    brings: list[float]
+    leads: list[float]
+
+    @classmethod
+    def _from_rust(cls, rust_result):
+        return cls(
+            teams=[
+                TeamPreviewTeam(
+                    team=tuple(i.team),
+                    matchup=i.matchup,
+                    visits=i.visits,
+                    probability=i.probability,
+                )
+                for i in rust_result.teams
+            ],
+            brings=list(rust_result.brings),
+            leads=list(rust_result.leads),
+        )
+
+
+@dataclass
+class TeamPreviewResult:
+    """
+    Result of a search over every team both sides can bring at team preview
+
+    :param side_one: Result for side one
+    :type side_one: TeamPreviewSideResult
+    :param side_two: Result for side two
+    :type side_two: TeamPreviewSideResult
+    :param search: The search over the teams that were kept, with one option per team
+    :type search: MctsResult
+    """
+
+    side_one: TeamPreviewSideResult
+    side_two: TeamPreviewSideResult
+    search: MctsResult
+
+    @classmethod
+    def _from_rust(cls, rust_result):
+        return cls(
+            side_one=TeamPreviewSideResult._from_rust(rust_result.side_one),
+            side_two=TeamPreviewSideResult._from_rust(rust_result.side_two),
+            search=MctsResult._from_rust(rust_result.search),
         )
 
 
 def monte_carlo_tree_search(
-    state: State, duration_ms: int = 1000, threads: int = 1
+    state: State,
+    duration_ms: int = 1000,
+    threads: int = 1,
+    config: MctsConfig | None = None,
 ) -> MctsResult:
     """
     Perform monte-carlo-tree-search on the given state and for the given duration
 
     :param state: the state to search through
     :type state: State
-    :param duration_ms: time in milliseconds to run the search
+    :param duration_ms: time in milliseconds to run the search, 0 to only stop on the config's budgets
     :type duration_ms: int
     :param threads: number of threads to use for the search
     :type threads: int
+    :param config: search parameters, defaults to MctsConfig()
+    :type config: MctsConfig | None
     :return: the result of the search
     :rtype: MctsResult
     """
-    return MctsResult._from_rust(mcts(state, duration_ms, threads))
+    return MctsResult._from_rust(mcts(state, duration_ms, threads, config))
+
+
+def monte_carlo_tree_search_root_parallel(
+    state: State,
+    duration_ms: int = 1000,
+    trees: int = 4,
+    config: MctsConfig | None = None,
+    configs: list[MctsConfig] | None = None,
+) -> MctsResult:
+    """
+    Perform monte-carlo-tree-search with one independent tree per thread for the given
+    duration, merging the root statistics of every tree at the end
+
+    :param state: the state to search through
+    :type state: State
+    :param duration_ms: time in milliseconds to run the search, 0 to only stop on the config's budgets
+    :type duration_ms: int
+    :param trees: number of trees to search, each seeded differently
+    :type trees: int
+    :param config: search parameters for every tree, defaults to MctsConfig()
+    :type config: MctsConfig | None
+    :param configs: search parameters for each tree, used instead of trees and config
+    :type configs: list[MctsConfig] | None
+    :return: the result of the search
+    :rtype: MctsResult
+    """
+    return MctsResult._from_rust(
+        mcts_root_parallel(state, duration_ms, trees, config, configs)
+    )
 
 
 def monte_carlo_tree_search_team_preview(
-    state: State, filters: TeamPreviewFilters, duration_ms: int = 1000, threads: int = 1
+    state: State,
+    filters: TeamPreviewFilters,
+    duration_ms: int = 1000,
+    threads: int = 1,
+    config: MctsConfig | None = None,
 ) -> MctsResult:
     """
     Perform monte-carlo-tree-search on the given state and for the given duration
 
     :param state: the state to search through
     :type state: State
-    :param duration_ms: time in milliseconds to run the search
+    :param duration_ms: time in milliseconds to run the search, 0 to only stop on the config's budgets
     :type duration_ms: int
     :param filters: an instance of TeamPreviewFilters
     :type filters: TeamPreviewFilters
     :param threads: number of threads to use for the search
     :type threads: int
+    :param config: search parameters, defaults to MctsConfig()
+    :type config: MctsConfig | None
+    :return: the result of the search
+    :rtype: MctsResult
+    """
+    return MctsResult._from_rust(
+        mcts_team_preview(state, duration_ms, filters, threads, config)
+    )
+
+
+def monte_carlo_tree_search_team_preview_all(
+    state: State,
+    duration_ms: int = 1000,
+    keep: int = 12,
+    threads: int = 1,
+    config: MctsConfig | None = None,
+) -> TeamPreviewResult:
+    """
+    Perform monte-carlo-tree-search over every team both sides can bring at team preview,
+    searching the keep teams per side with the best matchups
+
+    :param state: the state to search through, in team preview
+    :type state: State
+    :param duration_ms: time in milliseconds to run the search, 0 to only stop on the config's budgets
+    :type duration_ms: int
+    :param keep: number of teams per side to search, 0 for every team
+    :type keep: int
+    :param threads: number of threads to use for the search
+    :type threads: int
+    :param config: search parameters, defaults to MctsConfig()
+    :type config: MctsConfig | None
+    :return: the distributions over teams, brings and leads for both sides
+    :rtype: TeamPreviewResult
+    """
+    return TeamPreviewResult._from_rust(
+        mcts_team_preview_all(state, duration_ms, keep, threads, config)
+    )
+
+
+def monte_carlo_tree_search_information_set(
+    state: State,
+    opponent_sets: list[list[OpponentSet]],
+    duration_ms: int = 1000,
+    config: MctsConfig | None = None,
+) -> MctsResult:
+    """
+    Perform monte-carlo-tree-search on the given state for the given duration,
+    sampling side two's sets from `opponent_sets` every iteration
+
+    :param state: the state to search through
+    :type state: State
+    :param opponent_sets: candidate sets for each of side two's pokemon, by party index
+    :type opponent_sets: list[list[OpponentSet]]
+    :param duration_ms: time in milliseconds to run the search, 0 to only stop on the config's budgets
+    :type duration_ms: int
+    :param config: search parameters, defaults to MctsConfig()
+    :type config: MctsConfig | None
     :return: the result of the search
     :rtype: MctsResult
     """
     return MctsResult._from_rust(
-        mcts_team_preview(state, duration_ms, filters, threads)
+        mcts_information_set(state, opponent_sets, duration_ms, config)
+    )
+
+
+class BackgroundMctsSearch:
+    """
+    A monte-carlo-tree-search running on background threads,
+    started with monte_carlo_tree_search_in_background
+    """
+
+    def __init__(self, rust_search):
+        self._search = rust_search
+
+    def snapshot(self) -> MctsResult:
+        """
+        The statistics of the root's options so far
+
+        :rtype: MctsResult
+        """
+        return MctsResult._from_rust(self._search.snapshot())
+
+    def is_finished(self) -> bool:
+        """
+        Whether the search has stopped, on its own or through stop()
+
+        :rtype: bool
+        """
+        return self._search.is_finished()
+
+    def stop(self) -> MctsResult:
+        """
+        Stop the search and return its result
+
+        :rtype: MctsResult
+        """
+        return MctsResult._from_rust(self._search.stop())
+
+    def wait(self) -> MctsResult:
+        """
+        Wait for the search to stop on its own and return its result
+
+        :rtype: MctsResult
+        """
+        return MctsResult._from_rust(self._search.wait())
+
+
+def monte_carlo_tree_search_in_background(
+    state: State,
+    duration_ms: int = 1000,
+    threads: int = 1,
+    config: MctsConfig | None = None,
+    progress_callback=None,
+    progress_interval: int = 100_000,
+) -> BackgroundMctsSearch:
+    """
+    Start monte-carlo-tree-search on the given state on background threads and return straight away.
+    The GIL is released while the search runs
+
+    :param state: the state to search through
+    :type state: State
+    :param duration_ms: time in milliseconds to run the search, 0 to search until stopped or the config's budgets are reached
+    :type duration_ms: int
+    :param threads: number of threads to use for the search
+    :type threads: int
+    :param config: search parameters, defaults to MctsConfig()
+    :type config: MctsConfig | None
+    :param progress_callback: called with an MctsResult of the root's statistics every progress_interval iterations, from a search thread
+    :type progress_callback: Callable[[MctsResult], None] | None
+    :param progress_interval: the number of iterations between calls to progress_callback
+    :type progress_interval: int
+    :return: a handle to the running search
+    :rtype: BackgroundMctsSearch
+    """
+    callback = None
+    if progress_callback is not None:
+
+        def callback(rust_result):
+            progress_callback(MctsResult._from_rust(rust_result))
+
+    return BackgroundMctsSearch(
+        mcts_in_background(
+            state, duration_ms, threads, config, callback, progress_interval
+        )
     )
@@ -1,4 +1,4 @@
-from typing import List, Tuple, Set, Optional
+from typing import Callable, Dict, List, Tuple, Set, Optional
 
 from poke_engine import Weather, Terrain, PokemonIndex
 
@@ -20,6 +20,8 @@ class VolatileStatusDurations:
     :type taunt: int
     :param yawn: Yawn turns remaining
     :type yawn: int
+    :param dynamax: Dynamax turns remaining
+    :type dynamax: int
     """
 
     confusion: int
@@ -29,6 +31,7 @@ class VolatileStatusDurations:
     slowstart: int
     taunt: int
     yawn: int
+    dynamax: int
 
     def __init__(
         self,
@@ -39,6 +42,7 @@ class VolatileStatusDurations:
         slowstart: int = 0,
         taunt: int = 0,
         yawn: int = 0,
+        dynamax: int = 0,
     ) -> None: ...
 
 class SideConditions:
@@ -177,6 +181,8 @@ class Pokemon:
     :type nature: str
     :param evs: Effort values
     :type evs: tuple[int, int, int, int, int, int]
+    :param ivs: Individual values
+    :type ivs: tuple[int, int, int, int, int, int]
     :param attack: Attack stat
     :type attack: int
     :param defense: Defense stat
@@ -220,6 +226,7 @@ class Pokemon:
     item: str
     nature: str
     evs: Tuple[int, int, int, int, int, int]
+    ivs: Tuple[int, int, int, int, int, int]
     attack: int
     defense: int
     special_attack: int
@@ -249,6 +256,7 @@ class Pokemon:
         item: str = "none",
         nature: str = "serious",
         evs: Tuple[int, int, int, int, int, int] = (11, 11, 11, 11, 11, 11),
+        ivs: Tuple[int, int, int, int, int, int] = (31, 31, 31, 31, 31, 31),
         attack: int = 100,
         defense: int = 100,
         special_attack: int = 100,
@@ -371,12 +379,21 @@ class Side:
     :type slot_a: SideSlot
     :param slot_b: Slot B for active Pokemon
     :type slot_b: SideSlot
+    :param dynamax_used: Whether this side has already used its Dynamax
+    :type dynamax_used: bool
+    :param z_move_used: Whether this side has already used its Z-Move
+    :type z_move_used: bool
+    :param mega_used: Whether a Pokemon on this side has already mega-evolved
+    :type mega_used: bool
     """
 
     pokemon: List[Pokemon]
     side_conditions: SideConditions
     slot_a: SideSlot
     slot_b: SideSlot
+    dynamax_used: bool
+    z_move_used: bool
+    mega_used: bool
 
     def __init__(
         self,
@@ -384,6 +401,9 @@ class Side:
         slot_a: SideSlot = None,
         slot_b: SideSlot = None,
         side_conditions: Optional[SideConditions] = None,
+        dynamax_used: bool = False,
+        z_move_used: bool = False,
+        mega_used: bool = False,
     ) -> None: ...
 
 class StateInstructions:
@@ -453,6 +473,16 @@ class MctsSideResult:
     move_choice: Tuple[str, str]
     total_score: float
     visits: int
+    nash_probability: Optional[float]
+
+class PrincipalVariationPly:
+    """One ply of the line MCTS expects to be played."""
+
+    side_one_move: Tuple[str, str]
+    side_two_move: Tuple[str, str]
+    instructions: StateInstructions
+    visits: int
+    average_score: float
 
 class MctsResult:
     """Result from MCTS algorithm."""
@@ -460,6 +490,120 @@ class MctsResult:
     side_one: List[MctsSideResult]
     side_two: List[MctsSideResult]
     iteration_count: int
+    principal_variation: List[PrincipalVariationPly]
+    max_leaf_depth: int
+    average_leaf_depth: float
+
+class MctsConfig:
+    """
+    Tuning parameters for Monte Carlo Tree Search. The defaults are the engine's built-in values.
+
+    :param min_visits_before_selection: Round-robin visits per option before UCB1 selection starts (single-threaded search only)
+    :type min_visits_before_selection: int
+    :param exploration_constant: The exploration constant used in UCB1
+    :type exploration_constant: float
+    :param max_depth: Number of full turns below the root to expand. Forced switches and team preview do not count as turns
+    :type max_depth: int
+    :param sigmoid_slope: Slope of the sigmoid mapping an evaluation difference to a score
+    :type sigmoid_slope: float
+    :param virtual_loss_visits: Visits temporarily added to a move while a thread searches through it
+    :type virtual_loss_visits: int
+    :param max_iterations: The search stops after this many iterations. None searches until the time or another budget runs out
+    :type max_iterations: Optional[int]
+    :param max_nodes: The search stops once the tree has allocated this many nodes
+    :type max_nodes: Optional[int]
+    :param max_memory: The tree stops growing once its nodes take up about this many bytes, the search carries on with the tree it has
+    :type max_memory: Optional[int]
+    :param early_stop: Stop once the most visited option of each side can no longer be overtaken
+    :type early_stop: bool
+    :param seed: Seed for the search's random sampling. Only reproducible when stopped by an iteration or node budget and searched with a single thread
+    :type seed: Optional[int]
+    :param nash_iterations: Iterations of regret matching used to solve the root for a mixed strategy. 0 skips the solve
+    :type nash_iterations: int
+    :param selection_policy: How each side picks its option during selection. One of "ucb1", "exp3", "regretmatching", "factorizeducb1" or "puct", any other name raises a ValueError
+    :type selection_policy: str
+    :param policy_exploration: Share of "exp3" and "regretmatching" selection spread uniformly over all options, and share of a uniform prior mixed into "puct" priors
+    :type policy_exploration: float
+    :param puct_constant: Weight of an option's prior against its average score in "puct" selection
+    :type puct_constant: float
+    :param prior_table: Usage data for the priors of "puct" selection and progressive widening, e.g. {"incineroar": {"fakeout": 0.8, "knockoff": 0.6}}. A move's usage is split evenly between the choices that use it, one per target and per gimmick such as terastallizing, and choices that are not listed, such as switching, get no prior. None gives every option the same prior. Unknown pokemon or moves raise a ValueError
+    :type prior_table: Optional[Dict[str, Dict[str, float]]]
+    :param progressive_widening: Open a node's options gradually as it is visited, most promising first ("ucb1", "factorizeducb1" and "puct" only)
+    :type progressive_widening: bool
+    :param widening_constant: A node visited n times opens ceil(widening_constant * (n + 1) ^ widening_exponent) options per side
+    :type widening_constant: float
+    :param widening_exponent: See widening_constant
+    :type widening_exponent: float
+    :param rollout_policy: How a leaf is scored. One of "static", "random" or "greedy"; the non-static policies play out rollout_turns turns from the leaf first. Any other name, including the "callback" policy that is only available from Rust, raises a ValueError
+    :type rollout_policy: str
+    :param rollout_turns: The number of turns a "random" or "greedy" rollout plays out
+    :type rollout_turns: int
+    :param rollout_mix: The weight of a rollout's score, the rest of a leaf's score being its static evaluation
+    :type rollout_mix: float
+    :param transposition_table: Share visits, option statistics and children between nodes that reach the same state at the same depth, keyed by the state's hash
+    :type transposition_table: bool
+    :param principal_variation_plies: Return the line of each side's most visited option and the most likely outcome for this many plies from the root, 0 for none
+    :type principal_variation_plies: int
+    :param endgame_pokemon: Solve the state exactly instead of searching it once both sides have at most this many pokemon left, 0 to never do so
+    :type endgame_pokemon: int
+    :param endgame_depth: The number of turns the endgame solver searches, if it has the time to
+    :type endgame_depth: int
+    """
+
+    min_visits_before_selection: int
+    exploration_constant: float
+    max_depth: int
+    sigmoid_slope: float
+    virtual_loss_visits: int
+    max_iterations: Optional[int]
+    max_nodes: Optional[int]
+    max_memory: Optional[int]
+    early_stop: bool
+    seed: Optional[int]
+    nash_iterations: int
+    selection_policy: str
+    policy_exploration: float
+    puct_constant: float
+    prior_table: Optional[Dict[str, Dict[str, float]]]
+    progressive_widening: bool
+    widening_constant: float
+    widening_exponent: float
+    rollout_policy: str
+    rollout_turns: int
+    rollout_mix: float
+    transposition_table: bool
+    principal_variation_plies: int
+    endgame_pokemon: int
+    endgame_depth: int
+
+    def __init__(
+        self,
+        min_visits_before_selection: int = 50,
+        exploration_constant: float = 2.0,
+        max_depth: int = 4,
+        sigmoid_slope: float = 0.0062,
+        virtual_loss_visits: int = 3,
+        max_iterations: Optional[int] = None,
+        max_nodes: Optional[int] = None,
+        max_memory: Optional[int] = None,
+        early_stop: bool = False,
+        seed: Optional[int] = None,
+        nash_iterations: int = 0,
+        selection_policy: str = "UCB1",
+        policy_exploration: float = 0.1,
+        puct_constant: float = 1.5,
+        prior_table: Optional[Dict[str, Dict[str, float]]] = None,
+        progressive_widening: bool = False,
+        widening_constant: float = 2.0,
+        widening_exponent: float = 0.5,
+        rollout_policy: str = "STATIC",
+        rollout_turns: int = 3,
+        rollout_mix: float = 0.5,
+        transposition_table: bool = False,
+        principal_variation_plies: int = 0,
+        endgame_pokemon: int = 0,
+        endgame_depth: int = 2,
+    ) -> None: ...
 
 class TeamPreviewFilters:
     """Team Preview Options for both Sides."""
@@ -479,25 +623,186 @@ def mcts_team_preview(
     duration_ms: int,
     team_preview_filter: TeamPreviewFilters,
     threads: int = 1,
+    config: Optional[MctsConfig] = None,
 ) -> MctsResult:
     """
     Perform Monte Carlo Tree Search on the given state.
 
     :param py_state: The game state to analyze
-    :param duration_ms: Duration in milliseconds to run MCTS
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
     :param team_preview_filter: an instance of TeamPreviewFilters
     :param threads: number of threads to use for the search
+    :param config: search parameters, defaults to MctsConfig()
+    :return: MCTS results for both sides
+    """
+    ...
+
+class TeamPreviewTeam:
+    """One team a side can bring at team preview."""
+
+    team: Tuple[int, int, int, int]
+    matchup: float
+    visits: int
+    probability: float
+
+class TeamPreviewSideResult:
+    """One side's distribution over teams, brings and leads at team preview."""
+
+    teams: List[TeamPreviewTeam]
+    brings: List[float]
+    leads: List[float]
+
+class TeamPreviewResult:
+    """Result from searching every team both sides can bring at team preview."""
+
+    side_one: TeamPreviewSideResult
+    side_two: TeamPreviewSideResult
+    search: MctsResult
+
+def mcts_team_preview_all(
+    py_state: State,
+    duration_ms: int,
+    keep: int = 12,
+    threads: int = 1,
+    config: Optional[MctsConfig] = None,
+) -> TeamPreviewResult:
+    """
+    Perform Monte Carlo Tree Search over every team both sides can bring at team preview.
+
+    :param py_state: The game state to analyze, in team preview
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
+    :param keep: Number of teams per side searched after ranking them by matchup, 0 for every team
+    :param threads: number of threads to use for the search
+    :param config: search parameters, defaults to MctsConfig()
+    :return: Distributions over teams, brings and leads for both sides
+    """
+    ...
+
+class OpponentSet:
+    """One set a pokemon on side two might be running.
+
+    :param weight: likelihood of this set relative to the pokemon's other sets
+    :type weight: float
+    :param item: item
+    :type item: str
+    :param ability: ability
+    :type ability: str
+    :param nature: nature
+    :type nature: str
+    :param evs: evs as stat points
+    :type evs: Tuple[int, int, int, int, int, int]
+    :param tera_type: tera type
+    :type tera_type: str
+    :param moves: up to four move ids
+    :type moves: list[str]
+    """
+
+    weight: float
+    item: str
+    ability: str
+    nature: str
+    evs: Tuple[int, int, int, int, int, int]
+    tera_type: str
+    moves: list[str]
+
+    def __init__(
+        self,
+        weight: float = 1.0,
+        item: str = "none",
+        ability: str = "none",
+        nature: str = "serious",
+        evs: Tuple[int, int, int, int, int, int] = (11, 11, 11, 11, 11, 11),
+        tera_type: str = "normal",
+        moves: list[str] = [],
+    ) -> None: ...
+
+def mcts_information_set(
+    py_state: State,
+    opponent_sets: list[list[OpponentSet]],
+    duration_ms: int,
+    config: Optional[MctsConfig] = None,
+) -> MctsResult:
+    """
+    Perform Monte Carlo Tree Search on the given state, sampling side two's sets every iteration.
+
+    :param py_state: The game state to analyze
+    :param opponent_sets: candidate sets for each of side two's pokemon, by party index. An empty list keeps the pokemon as it is in the state
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
+    :param config: Search parameters, defaults to MctsConfig()
     :return: MCTS results for both sides
     """
     ...
 
-def mcts(py_state: State, duration_ms: int, threads: int) -> MctsResult:
+class BackgroundSearch:
+    """A search running on background threads, started with mcts_in_background."""
+
+    def snapshot(self) -> MctsResult:
+        """The statistics of the root's options so far."""
+        ...
+    def is_finished(self) -> bool:
+        """Whether the search has stopped, on its own or through stop()."""
+        ...
+    def stop(self) -> MctsResult:
+        """Stop the search and return its result. Releases the GIL while it waits."""
+        ...
+    def wait(self) -> MctsResult:
+        """Wait for the search to stop on its own and return its result. Releases the GIL while it waits."""
+        ...
+
+def mcts_in_background(
+    py_state: State,
+    duration_ms: int,
+    threads: int,
+    config: Optional[MctsConfig] = None,
+    progress_callback: Optional[Callable[[MctsResult], None]] = None,
+    progress_interval: int = 100_000,
+) -> BackgroundSearch:
+    """
+    Start Monte Carlo Tree Search on the given state on background threads and return straight away.
+
+    :param py_state: The game state to analyze
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
+    :param threads: Number of threads to use for MCTS
+    :param config: Search parameters, defaults to MctsConfig()
+    :param progress_callback: Called with the root's statistics every progress_interval iterations, from a search thread
+    :param progress_interval: The number of iterations between calls to progress_callback
+    :return: A handle to the running search
+    """
+    ...
+
+def mcts(
+    py_state: State,
+    duration_ms: int,
+    threads: int,
+    config: Optional[MctsConfig] = None,
+) -> MctsResult:
     """
     Perform Monte Carlo Tree Search on the given state.
 
     :param py_state: The game state to analyze
-    :param duration_ms: Duration in milliseconds to run MCTS
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
     :param threads: Number of threads to use for MCTS
+    :param config: Search parameters, defaults to MctsConfig()
+    :return: MCTS results for both sides
+    """
+    ...
+
+def mcts_root_parallel(
+    py_state: State,
+    duration_ms: int,
+    trees: int,
+    config: Optional[MctsConfig] = None,
+    configs: Optional[List[MctsConfig]] = None,
+) -> MctsResult:
+    """
+    Perform Monte Carlo Tree Search with independent trees on separate threads, merging
+    the root statistics of every tree at the end.
+
+    :param py_state: The game state to analyze
+    :param duration_ms: Duration in milliseconds to run MCTS, 0 for no time limit
+    :param trees: Number of trees to search, each with its own seed derived from config's
+    :param config: Search parameters for every tree, defaults to MctsConfig()
+    :param configs: Search parameters for each tree, searched as given instead of trees copies of config
     :return: MCTS results for both sides
     """
     ...
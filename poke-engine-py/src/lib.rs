@@ -1,7 +1,7 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyTuple, PyType};
 use pyo3::{pyfunction, pymethods, pymodule, wrap_pyfunction, Bound, PyResult};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use poke_engine::choices::{Choices, MoveCategory, MOVES};
 use poke_engine::engine::abilities::Abilities;
@@ -13,8 +13,16 @@ use poke_engine::engine::state::{
     MoveChoice, PokemonVolatileStatus, Terrain, VolatileStatusBitset, Weather,
 };
 use poke_engine::instruction::{Instruction, StateInstructions};
-use poke_engine::mcts::{perform_mcts, MctsResult, MctsSideResult};
-use poke_engine::mcts_threaded::perform_mcts_shared_tree;
+use poke_engine::mcts::{
+    perform_mcts, search_duration, usage_prior_callback, MctsConfig, MctsResult, MctsSideResult,
+    PrincipalVariationPly, RolloutPolicy, SelectionPolicy,
+};
+use poke_engine::mcts_information_set::{perform_mcts_information_set, OpponentSet};
+use poke_engine::mcts_root_parallel::{perform_mcts_root_parallel, root_parallel_configs};
+use poke_engine::mcts_team_preview::{perform_mcts_team_preview, TeamPreviewSideResult};
+use poke_engine::mcts_threaded::{
+    perform_mcts_shared_tree, BackgroundSearch, ProgressCallback, SharedMctsTree,
+};
 use poke_engine::pokemon::PokemonName;
 use poke_engine::state::{
     LastUsedMove, Move, Pokemon, PokemonIndex, PokemonMoves, PokemonNature, PokemonStatus,
@@ -22,7 +30,6 @@ use poke_engine::state::{
     StateTerrain, StateTrickRoom, StateWeather, VolatileStatusDurations,
 };
 use std::str::FromStr;
-use std::time::Duration;
 
 fn movechoice_to_string(
     side: &Side,
@@ -86,8 +93,11 @@ impl Into<State> for PyState {
             team_preview: self.team_preview,
             use_last_used_move: false,
             use_damage_dealt: false,
+            hash: 0,
+            track_hash: false,
         };
         state.set_conditional_mechanics();
+        state.rehash();
         state
     }
 }
@@ -159,6 +169,9 @@ pub struct PySide {
     pub side_conditions: PySideConditions,
     pub slot_a: PySideSlot,
     pub slot_b: PySideSlot,
+    pub dynamax_used: bool,
+    pub z_move_used: bool,
+    pub mega_used: bool,
 }
 
 impl From<Side> for PySide {
@@ -169,6 +182,9 @@ impl From<Side> for PySide {
             side_conditions: PySideConditions::from(other.side_conditions),
             slot_a: PySideSlot::from(other.slot_a),
             slot_b: PySideSlot::from(other.slot_b),
+            dynamax_used: other.dynamax_used,
+            z_move_used: other.z_move_used,
+            mega_used: other.mega_used,
         }
     }
 }
@@ -189,6 +205,9 @@ impl Into<Side> for PySide {
             side_conditions: self.side_conditions.into(),
             slot_a: self.slot_a.into(),
             slot_b: self.slot_b.into(),
+            dynamax_used: self.dynamax_used,
+            z_move_used: self.z_move_used,
+            mega_used: self.mega_used,
         }
     }
 }
@@ -201,12 +220,18 @@ impl PySide {
         slot_a=PySideSlot::from(SideSlot::default()),
         slot_b=PySideSlot::from(SideSlot::default()),
         side_conditions=PySideConditions::from(SideConditions::default()),
+        dynamax_used=false,
+        z_move_used=false,
+        mega_used=false,
     ))]
     fn new(
         mut pokemon: Vec<PyPokemon>,
         slot_a: PySideSlot,
         slot_b: PySideSlot,
         side_conditions: PySideConditions,
+        dynamax_used: bool,
+        z_move_used: bool,
+        mega_used: bool,
     ) -> Self {
         while pokemon.len() < 6 {
             pokemon.push(PyPokemon::create_fainted());
@@ -223,6 +248,9 @@ impl PySide {
             side_conditions,
             slot_a,
             slot_b,
+            dynamax_used,
+            z_move_used,
+            mega_used,
         }
     }
 }
@@ -337,7 +365,7 @@ impl PySideSlot {
         active_index="0".to_string(),
         baton_passing=false,
         shed_tailing=false,
-        volatile_status_durations=PyVolatileStatusDurations::new(0, 0, 0, 0, 0, 0, 0),
+        volatile_status_durations=PyVolatileStatusDurations::new(0, 0, 0, 0, 0, 0, 0, 0),
         wish=(0, 0),
         future_sight=(0, "0".to_string()),
         force_switch=false,
@@ -417,6 +445,7 @@ pub struct PyVolatileStatusDurations {
     pub slowstart: i8,
     pub taunt: i8,
     pub yawn: i8,
+    pub dynamax: i8,
 }
 
 impl From<VolatileStatusDurations> for PyVolatileStatusDurations {
@@ -429,6 +458,7 @@ impl From<VolatileStatusDurations> for PyVolatileStatusDurations {
             slowstart: other.slowstart,
             taunt: other.taunt,
             yawn: other.yawn,
+            dynamax: other.dynamax,
         }
     }
 }
@@ -443,6 +473,7 @@ impl Into<VolatileStatusDurations> for PyVolatileStatusDurations {
             slowstart: self.slowstart,
             taunt: self.taunt,
             yawn: self.yawn,
+            dynamax: self.dynamax,
         }
     }
 }
@@ -458,6 +489,7 @@ impl PyVolatileStatusDurations {
         slowstart=0,
         taunt=0,
         yawn=0,
+        dynamax=0,
     ))]
     fn new(
         confusion: i8,
@@ -467,6 +499,7 @@ impl PyVolatileStatusDurations {
         slowstart: i8,
         taunt: i8,
         yawn: i8,
+        dynamax: i8,
     ) -> PyVolatileStatusDurations {
         PyVolatileStatusDurations {
             confusion,
@@ -476,6 +509,7 @@ impl PyVolatileStatusDurations {
             slowstart,
             taunt,
             yawn,
+            dynamax,
         }
     }
 }
@@ -639,6 +673,7 @@ pub struct PyPokemon {
     pub item: String,
     pub nature: String,
     pub evs: (u8, u8, u8, u8, u8, u8),
+    pub ivs: (u8, u8, u8, u8, u8, u8),
     pub attack: i16,
     pub defense: i16,
     pub special_attack: i16,
@@ -680,6 +715,14 @@ impl From<Pokemon> for PyPokemon {
                 other.evs.4,
                 other.evs.5,
             ),
+            ivs: (
+                other.ivs.0,
+                other.ivs.1,
+                other.ivs.2,
+                other.ivs.3,
+                other.ivs.4,
+                other.ivs.5,
+            ),
             attack: other.attack,
             defense: other.defense,
             special_attack: other.special_attack,
@@ -733,6 +776,9 @@ impl Into<Pokemon> for PyPokemon {
             evs: (
                 self.evs.0, self.evs.1, self.evs.2, self.evs.3, self.evs.4, self.evs.5,
             ),
+            ivs: (
+                self.ivs.0, self.ivs.1, self.ivs.2, self.ivs.3, self.ivs.4, self.ivs.5,
+            ),
             attack: self.attack,
             defense: self.defense,
             special_attack: self.special_attack,
@@ -776,6 +822,7 @@ impl PyPokemon {
         item="none".to_string(),
         nature="serious".to_string(),
         evs=(11, 11, 11, 11, 11, 11),
+        ivs=(31, 31, 31, 31, 31, 31),
         attack=100,
         defense=100,
         special_attack=100,
@@ -804,6 +851,7 @@ impl PyPokemon {
         item: String,
         nature: String,
         evs: (u8, u8, u8, u8, u8, u8),
+        ivs: (u8, u8, u8, u8, u8, u8),
         attack: i16,
         defense: i16,
         special_attack: i16,
@@ -835,6 +883,7 @@ impl PyPokemon {
             item,
             nature,
             evs,
+            ivs,
             attack,
             defense,
             special_attack,
@@ -921,7 +970,8 @@ impl PyMove {
 struct PyMctsSideResult {
     pub move_choice: (String, String),
     pub total_score: f32,
-    pub visits: u32,
+    pub visits: u64,
+    pub nash_probability: Option<f32>,
 }
 
 impl PyMctsSideResult {
@@ -933,16 +983,30 @@ impl PyMctsSideResult {
             ),
             total_score: result.total_score,
             visits: result.visits,
+            nash_probability: result.nash_probability,
         }
     }
 }
 
+#[derive(Clone)]
+#[pyclass(name = "PrincipalVariationPly", module = "poke_engine", get_all)]
+struct PyPrincipalVariationPly {
+    pub side_one_move: (String, String),
+    pub side_two_move: (String, String),
+    pub instructions: PyStateInstructions,
+    pub visits: u64,
+    pub average_score: f32,
+}
+
 #[derive(Clone)]
 #[pyclass(name = "MctsResult", module = "poke_engine", get_all)]
 struct PyMctsResult {
     side_one: Vec<PyMctsSideResult>,
     side_two: Vec<PyMctsSideResult>,
-    iteration_count: u32,
+    iteration_count: u64,
+    principal_variation: Vec<PyPrincipalVariationPly>,
+    max_leaf_depth: u8,
+    average_leaf_depth: f32,
 }
 
 impl PyMctsResult {
@@ -959,7 +1023,293 @@ impl PyMctsResult {
                 .map(|r| PyMctsSideResult::from_mcts_side_result(r.clone(), &state.sides[1]))
                 .collect(),
             iteration_count: result.iteration_count,
+            principal_variation: PyMctsResult::principal_variation(
+                result.principal_variation,
+                state,
+            ),
+            max_leaf_depth: result.max_leaf_depth,
+            average_leaf_depth: result.average_leaf_depth,
+        }
+    }
+
+    // each ply's moves are named in the state that ply is played from
+    fn principal_variation(
+        principal_variation: Vec<PrincipalVariationPly>,
+        state: &State,
+    ) -> Vec<PyPrincipalVariationPly> {
+        let mut state = state.clone();
+        principal_variation
+            .into_iter()
+            .map(|ply| {
+                let py_ply = PyPrincipalVariationPly {
+                    side_one_move: (
+                        movechoice_to_string(
+                            &state.sides[0],
+                            &ply.s1_move.0,
+                            &SlotReference::SlotA,
+                        ),
+                        movechoice_to_string(
+                            &state.sides[0],
+                            &ply.s1_move.1,
+                            &SlotReference::SlotB,
+                        ),
+                    ),
+                    side_two_move: (
+                        movechoice_to_string(
+                            &state.sides[1],
+                            &ply.s2_move.0,
+                            &SlotReference::SlotA,
+                        ),
+                        movechoice_to_string(
+                            &state.sides[1],
+                            &ply.s2_move.1,
+                            &SlotReference::SlotB,
+                        ),
+                    ),
+                    instructions: ply.instructions.clone().into(),
+                    visits: ply.visits,
+                    average_score: ply.average_score,
+                };
+                state.apply_instructions(&ply.instructions.instruction_list);
+                py_ply
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "MctsConfig", module = "poke_engine", get_all, set_all)]
+struct PyMctsConfig {
+    min_visits_before_selection: usize,
+    exploration_constant: f32,
+    max_depth: u8,
+    sigmoid_slope: f32,
+    virtual_loss_visits: u32,
+    max_iterations: Option<u64>,
+    max_nodes: Option<usize>,
+    max_memory: Option<usize>,
+    early_stop: bool,
+    seed: Option<u64>,
+    nash_iterations: u32,
+    selection_policy: String,
+    policy_exploration: f32,
+    puct_constant: f32,
+    prior_table: Option<HashMap<String, HashMap<String, f32>>>,
+    progressive_widening: bool,
+    widening_constant: f32,
+    widening_exponent: f32,
+    rollout_policy: String,
+    rollout_turns: u8,
+    rollout_mix: f32,
+    transposition_table: bool,
+    principal_variation_plies: u8,
+    endgame_pokemon: u8,
+    endgame_depth: u8,
+}
+
+impl From<MctsConfig> for PyMctsConfig {
+    fn from(other: MctsConfig) -> Self {
+        PyMctsConfig {
+            min_visits_before_selection: other.min_visits_before_selection,
+            exploration_constant: other.exploration_constant,
+            max_depth: other.max_depth,
+            sigmoid_slope: other.sigmoid_slope,
+            virtual_loss_visits: other.virtual_loss_visits,
+            max_iterations: other.max_iterations,
+            max_nodes: other.max_nodes,
+            max_memory: other.max_memory,
+            early_stop: other.early_stop,
+            seed: other.seed,
+            nash_iterations: other.nash_iterations,
+            selection_policy: other.selection_policy.to_string(),
+            policy_exploration: other.policy_exploration,
+            puct_constant: other.puct_constant,
+            // a prior callback can not be turned back into a table
+            prior_table: None,
+            progressive_widening: other.progressive_widening,
+            widening_constant: other.widening_constant,
+            widening_exponent: other.widening_exponent,
+            rollout_policy: other.rollout_policy.to_string(),
+            rollout_turns: other.rollout_turns,
+            rollout_mix: other.rollout_mix,
+            transposition_table: other.transposition_table,
+            principal_variation_plies: other.principal_variation_plies,
+            endgame_pokemon: other.endgame_pokemon,
+            endgame_depth: other.endgame_depth,
+        }
+    }
+}
+
+// a rollout callback can only be set from Rust, so the CALLBACK policy would have nothing to call
+fn parse_rollout_policy(name: &str) -> PyResult<RolloutPolicy> {
+    match RolloutPolicy::parse(name) {
+        Ok(RolloutPolicy::CALLBACK) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "the callback rollout policy needs a rollout callback, which can only be set from Rust",
+        )),
+        policy => policy.map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>),
+    }
+}
+
+// `MctsConfig.prior_table`, how often each pokemon uses each of its moves, by their names
+fn usage_table(
+    table: &HashMap<String, HashMap<String, f32>>,
+) -> PyResult<HashMap<PokemonName, HashMap<Choices, f32>>> {
+    let mut usage = HashMap::new();
+    for (pokemon, moves) in table {
+        let pokemon_name = PokemonName::from_str(pokemon).unwrap();
+        if pokemon_name == PokemonName::NONE {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid pokemon in prior_table: {}",
+                pokemon
+            )));
+        }
+        let mut move_usage = HashMap::new();
+        for (move_name, probability) in moves {
+            let choice = Choices::from_str(move_name).unwrap();
+            if choice == Choices::NONE {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid move for {} in prior_table: {}",
+                    pokemon, move_name
+                )));
+            }
+            move_usage.insert(choice, *probability);
+        }
+        usage.insert(pokemon_name, move_usage);
+    }
+    Ok(usage)
+}
+
+// the policies and prior table are checked by `PyMctsConfig::new`, but can be reassigned to
+// anything afterwards
+impl TryFrom<PyMctsConfig> for MctsConfig {
+    type Error = PyErr;
+
+    fn try_from(other: PyMctsConfig) -> PyResult<MctsConfig> {
+        Ok(MctsConfig {
+            min_visits_before_selection: other.min_visits_before_selection,
+            exploration_constant: other.exploration_constant,
+            max_depth: other.max_depth,
+            sigmoid_slope: other.sigmoid_slope,
+            virtual_loss_visits: other.virtual_loss_visits,
+            max_iterations: other.max_iterations,
+            max_nodes: other.max_nodes,
+            max_memory: other.max_memory,
+            early_stop: other.early_stop,
+            seed: other.seed,
+            nash_iterations: other.nash_iterations,
+            selection_policy: SelectionPolicy::parse(&other.selection_policy)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            policy_exploration: other.policy_exploration,
+            puct_constant: other.puct_constant,
+            prior_callback: match &other.prior_table {
+                Some(table) => Some(usage_prior_callback(usage_table(table)?)),
+                None => None,
+            },
+            progressive_widening: other.progressive_widening,
+            widening_constant: other.widening_constant,
+            widening_exponent: other.widening_exponent,
+            rollout_policy: parse_rollout_policy(&other.rollout_policy)?,
+            rollout_turns: other.rollout_turns,
+            rollout_mix: other.rollout_mix,
+            rollout_callback: None,
+            transposition_table: other.transposition_table,
+            principal_variation_plies: other.principal_variation_plies,
+            endgame_pokemon: other.endgame_pokemon,
+            endgame_depth: other.endgame_depth,
+        })
+    }
+}
+
+#[pymethods]
+impl PyMctsConfig {
+    #[new]
+    #[pyo3(signature = (
+        min_visits_before_selection=MctsConfig::default().min_visits_before_selection,
+        exploration_constant=MctsConfig::default().exploration_constant,
+        max_depth=MctsConfig::default().max_depth,
+        sigmoid_slope=MctsConfig::default().sigmoid_slope,
+        virtual_loss_visits=MctsConfig::default().virtual_loss_visits,
+        max_iterations=None,
+        max_nodes=None,
+        max_memory=None,
+        early_stop=false,
+        seed=None,
+        nash_iterations=MctsConfig::default().nash_iterations,
+        selection_policy=MctsConfig::default().selection_policy.to_string(),
+        policy_exploration=MctsConfig::default().policy_exploration,
+        puct_constant=MctsConfig::default().puct_constant,
+        prior_table=None,
+        progressive_widening=false,
+        widening_constant=MctsConfig::default().widening_constant,
+        widening_exponent=MctsConfig::default().widening_exponent,
+        rollout_policy=MctsConfig::default().rollout_policy.to_string(),
+        rollout_turns=MctsConfig::default().rollout_turns,
+        rollout_mix=MctsConfig::default().rollout_mix,
+        transposition_table=false,
+        principal_variation_plies=MctsConfig::default().principal_variation_plies,
+        endgame_pokemon=MctsConfig::default().endgame_pokemon,
+        endgame_depth=MctsConfig::default().endgame_depth,
+    ))]
+    fn new(
+        min_visits_before_selection: usize,
+        exploration_constant: f32,
+        max_depth: u8,
+        sigmoid_slope: f32,
+        virtual_loss_visits: u32,
+        max_iterations: Option<u64>,
+        max_nodes: Option<usize>,
+        max_memory: Option<usize>,
+        early_stop: bool,
+        seed: Option<u64>,
+        nash_iterations: u32,
+        selection_policy: String,
+        policy_exploration: f32,
+        puct_constant: f32,
+        prior_table: Option<HashMap<String, HashMap<String, f32>>>,
+        progressive_widening: bool,
+        widening_constant: f32,
+        widening_exponent: f32,
+        rollout_policy: String,
+        rollout_turns: u8,
+        rollout_mix: f32,
+        transposition_table: bool,
+        principal_variation_plies: u8,
+        endgame_pokemon: u8,
+        endgame_depth: u8,
+    ) -> PyResult<Self> {
+        SelectionPolicy::parse(&selection_policy)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        parse_rollout_policy(&rollout_policy)?;
+        if let Some(table) = &prior_table {
+            usage_table(table)?;
         }
+        Ok(PyMctsConfig {
+            min_visits_before_selection,
+            exploration_constant,
+            max_depth,
+            sigmoid_slope,
+            virtual_loss_visits,
+            max_iterations,
+            max_nodes,
+            max_memory,
+            early_stop,
+            seed,
+            nash_iterations,
+            selection_policy,
+            policy_exploration,
+            puct_constant,
+            prior_table,
+            progressive_widening,
+            widening_constant,
+            widening_exponent,
+            rollout_policy,
+            rollout_turns,
+            rollout_mix,
+            transposition_table,
+            principal_variation_plies,
+            endgame_pokemon,
+            endgame_depth,
+        })
     }
 }
 
@@ -1013,12 +1363,110 @@ impl PyTeamPreviewFilters {
     }
 }
 
+#[derive(Clone)]
+#[pyclass(name = "OpponentSet", module = "poke_engine", get_all, set_all)]
+struct PyOpponentSet {
+    weight: f32,
+    item: String,
+    ability: String,
+    nature: String,
+    evs: (u8, u8, u8, u8, u8, u8),
+    tera_type: String,
+    moves: Vec<String>,
+}
+
+impl From<PyOpponentSet> for OpponentSet {
+    fn from(other: PyOpponentSet) -> Self {
+        let mut moves = [Choices::NONE; 4];
+        for (index, move_id) in other.moves.iter().take(4).enumerate() {
+            moves[index] = Choices::from_str(move_id).unwrap();
+        }
+        OpponentSet {
+            weight: other.weight,
+            item: Items::from_str(&other.item).unwrap(),
+            ability: Abilities::from_str(&other.ability).unwrap(),
+            nature: PokemonNature::from_str(&other.nature).unwrap(),
+            evs: other.evs,
+            tera_type: PokemonType::from_str(&other.tera_type).unwrap(),
+            moves,
+        }
+    }
+}
+
+#[pymethods]
+impl PyOpponentSet {
+    #[new]
+    #[pyo3(signature = (
+        weight=1.0,
+        item="none".to_string(),
+        ability="none".to_string(),
+        nature="serious".to_string(),
+        evs=(11, 11, 11, 11, 11, 11),
+        tera_type="normal".to_string(),
+        moves=Vec::new(),
+    ))]
+    fn new(
+        weight: f32,
+        item: String,
+        ability: String,
+        nature: String,
+        evs: (u8, u8, u8, u8, u8, u8),
+        tera_type: String,
+        moves: Vec<String>,
+    ) -> Self {
+        PyOpponentSet {
+            weight,
+            item,
+            ability,
+            nature,
+            evs,
+            tera_type,
+            moves,
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (py_state, opponent_sets, duration_ms, config=None))]
+fn mcts_information_set(
+    py_state: PyState,
+    opponent_sets: Vec<Vec<PyOpponentSet>>,
+    duration_ms: u64,
+    config: Option<PyMctsConfig>,
+) -> PyResult<PyMctsResult> {
+    if opponent_sets.len() > 6 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "opponent_sets can have at most one list of sets per pokemon",
+        ));
+    }
+    let mut sets: [Vec<OpponentSet>; 6] = Default::default();
+    for (index, pkmn_sets) in opponent_sets.into_iter().enumerate() {
+        sets[index] = pkmn_sets.into_iter().map(Into::into).collect();
+    }
+
+    let mut state: State = py_state.into();
+    let (s1_options, _) = state.root_get_all_options();
+    let config: MctsConfig =
+        config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
+    let mcts_result = perform_mcts_information_set(
+        &mut state,
+        s1_options,
+        &sets,
+        search_duration(duration_ms),
+        &config,
+    );
+    let py_mcts_result = PyMctsResult::from_mcts_result(mcts_result, &state);
+    Ok(py_mcts_result)
+}
+
 #[pyfunction]
+#[pyo3(signature = (py_state, duration_ms, team_preview_filter, threads, config=None))]
 fn mcts_team_preview(
     py_state: PyState,
     duration_ms: u64,
     team_preview_filter: PyTeamPreviewFilters,
     threads: usize,
+    config: Option<PyMctsConfig>,
 ) -> PyResult<PyMctsResult> {
     let mut state: State = py_state.into();
     if !state.team_preview {
@@ -1060,35 +1508,239 @@ fn mcts_team_preview(
     let s1_options = State::generate_team_preview_options(side_one_options_pokemon_index);
     let s2_options = State::generate_team_preview_options(side_two_options_pokemon_index);
 
-    let duration = Duration::from_millis(duration_ms);
+    let duration = search_duration(duration_ms);
+    let config: MctsConfig =
+        config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
     let mcts_result = if threads > 1 {
-        perform_mcts_shared_tree(&mut state, s1_options, s2_options, duration, threads)
+        perform_mcts_shared_tree(
+            &mut state, s1_options, s2_options, duration, threads, &config,
+        )
     } else {
-        perform_mcts(&mut state, s1_options, s2_options, duration)
+        perform_mcts(&mut state, s1_options, s2_options, duration, &config)
     };
 
     let py_mcts_result = PyMctsResult::from_mcts_result(mcts_result, &state);
     Ok(py_mcts_result)
 }
 
+#[derive(Clone)]
+#[pyclass(name = "TeamPreviewTeam", module = "poke_engine", get_all)]
+struct PyTeamPreviewTeam {
+    pub team: [u8; 4],
+    pub matchup: f32,
+    pub visits: u64,
+    pub probability: f32,
+}
+
+#[derive(Clone)]
+#[pyclass(name = "TeamPreviewSideResult", module = "poke_engine", get_all)]
+struct PyTeamPreviewSideResult {
+    pub teams: Vec<PyTeamPreviewTeam>,
+    pub brings: [f32; 6],
+    pub leads: [f32; 6],
+}
+
+impl From<TeamPreviewSideResult> for PyTeamPreviewSideResult {
+    fn from(other: TeamPreviewSideResult) -> Self {
+        PyTeamPreviewSideResult {
+            teams: other
+                .teams
+                .into_iter()
+                .map(|x| PyTeamPreviewTeam {
+                    team: x.team.map(|index| index as u8),
+                    matchup: x.matchup,
+                    visits: x.visits,
+                    probability: x.probability,
+                })
+                .collect(),
+            brings: other.brings,
+            leads: other.leads,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "TeamPreviewResult", module = "poke_engine", get_all)]
+struct PyTeamPreviewResult {
+    pub side_one: PyTeamPreviewSideResult,
+    pub side_two: PyTeamPreviewSideResult,
+    pub search: PyMctsResult,
+}
+
+#[pyfunction]
+#[pyo3(signature = (py_state, duration_ms, keep=12, threads=1, config=None))]
+fn mcts_team_preview_all(
+    py_state: PyState,
+    duration_ms: u64,
+    keep: usize,
+    threads: usize,
+    config: Option<PyMctsConfig>,
+) -> PyResult<PyTeamPreviewResult> {
+    let mut state: State = py_state.into();
+    if !state.team_preview {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "State is not in team preview phase",
+        ));
+    }
+
+    let duration = search_duration(duration_ms);
+    let config: MctsConfig =
+        config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
+    let result = perform_mcts_team_preview(&mut state, duration, keep, threads, &config);
+    Ok(PyTeamPreviewResult {
+        side_one: result.s1.into(),
+        side_two: result.s2.into(),
+        search: PyMctsResult::from_mcts_result(result.search, &state),
+    })
+}
+
 #[pyfunction]
-fn mcts(py_state: PyState, mut duration_ms: u64, threads: usize) -> PyResult<PyMctsResult> {
+#[pyo3(signature = (py_state, duration_ms, threads, config=None))]
+fn mcts(
+    py_state: PyState,
+    mut duration_ms: u64,
+    threads: usize,
+    config: Option<PyMctsConfig>,
+) -> PyResult<PyMctsResult> {
     let mut state: State = py_state.into();
     let (s1_options, s2_options) = state.root_get_all_options();
     if s1_options.len() <= 1 {
         duration_ms = 100; // if there's only one option, no need to run MCTS for long
     }
 
-    let duration = Duration::from_millis(duration_ms);
+    let duration = search_duration(duration_ms);
+    let config: MctsConfig =
+        config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
     let mcts_result = if threads > 1 {
-        perform_mcts_shared_tree(&mut state, s1_options, s2_options, duration, threads)
+        perform_mcts_shared_tree(
+            &mut state, s1_options, s2_options, duration, threads, &config,
+        )
     } else {
-        perform_mcts(&mut state, s1_options, s2_options, duration)
+        perform_mcts(&mut state, s1_options, s2_options, duration, &config)
     };
     let py_mcts_result = PyMctsResult::from_mcts_result(mcts_result, &state);
     Ok(py_mcts_result)
 }
 
+#[pyfunction]
+#[pyo3(signature = (py_state, duration_ms, trees, config=None, configs=None))]
+fn mcts_root_parallel(
+    py_state: PyState,
+    mut duration_ms: u64,
+    trees: usize,
+    config: Option<PyMctsConfig>,
+    configs: Option<Vec<PyMctsConfig>>,
+) -> PyResult<PyMctsResult> {
+    let mut state: State = py_state.into();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    if s1_options.len() <= 1 {
+        duration_ms = 100; // if there's only one option, no need to run MCTS for long
+    }
+
+    let duration = search_duration(duration_ms);
+    let configs: Vec<MctsConfig> = match configs {
+        Some(configs) if !configs.is_empty() => configs
+            .into_iter()
+            .map(MctsConfig::try_from)
+            .collect::<PyResult<_>>()?,
+        _ => {
+            let config: MctsConfig =
+                config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
+            root_parallel_configs(&config, trees.max(1))
+        }
+    };
+    let mcts_result =
+        perform_mcts_root_parallel(&mut state, s1_options, s2_options, duration, &configs);
+    let py_mcts_result = PyMctsResult::from_mcts_result(mcts_result, &state);
+    Ok(py_mcts_result)
+}
+
+#[pyclass(name = "BackgroundSearch", module = "poke_engine")]
+struct PyBackgroundSearch {
+    // `None` once the search has been stopped or waited on, when `result` is set instead
+    search: Option<BackgroundSearch>,
+    result: Option<PyMctsResult>,
+    state: State,
+}
+
+impl PyBackgroundSearch {
+    fn finish(&mut self, py: Python, stop: bool) -> PyMctsResult {
+        if let Some(search) = self.search.take() {
+            let (_, result) = py.allow_threads(|| if stop { search.stop() } else { search.wait() });
+            self.result = Some(PyMctsResult::from_mcts_result(result, &self.state));
+        }
+        self.result.clone().expect("a finished search has a result")
+    }
+}
+
+#[pymethods]
+impl PyBackgroundSearch {
+    fn snapshot(&self) -> PyMctsResult {
+        match &self.search {
+            Some(search) => PyMctsResult::from_mcts_result(search.snapshot(), &self.state),
+            None => self.result.clone().expect("a finished search has a result"),
+        }
+    }
+    fn is_finished(&self) -> bool {
+        self.search
+            .as_ref()
+            .is_none_or(|search| search.is_finished())
+    }
+    fn stop(&mut self, py: Python) -> PyMctsResult {
+        self.finish(py, true)
+    }
+    fn wait(&mut self, py: Python) -> PyMctsResult {
+        self.finish(py, false)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    py_state,
+    duration_ms,
+    threads,
+    config=None,
+    progress_callback=None,
+    progress_interval=100_000,
+))]
+fn mcts_in_background(
+    py_state: PyState,
+    duration_ms: u64,
+    threads: usize,
+    config: Option<PyMctsConfig>,
+    progress_callback: Option<PyObject>,
+    progress_interval: u32,
+) -> PyResult<PyBackgroundSearch> {
+    let state: State = py_state.into();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config: MctsConfig =
+        config.map_or_else(|| Ok(MctsConfig::default()), MctsConfig::try_from)?;
+    let progress = progress_callback.map(|callback| {
+        let callback_state = state.clone();
+        let callback: ProgressCallback = Box::new(move |result: &MctsResult| {
+            Python::with_gil(|py| {
+                let py_result = PyMctsResult::from_mcts_result(result.clone(), &callback_state);
+                if let Err(error) = callback.call1(py, (py_result,)) {
+                    error.print(py);
+                }
+            })
+        });
+        (progress_interval, callback)
+    });
+    let search = SharedMctsTree::new(s1_options, s2_options).search_in_background(
+        state.clone(),
+        search_duration(duration_ms),
+        threads.max(1),
+        &config,
+        progress,
+    );
+    Ok(PyBackgroundSearch {
+        search: Some(search),
+        result: None,
+        state,
+    })
+}
+
 #[derive(Clone)]
 #[pyclass(name = "StateInstructions", module = "poke_engine", get_all)]
 struct PyStateInstructions {
@@ -1292,6 +1944,10 @@ fn py_poke_engine(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(generate_instructions, m)?)?;
     m.add_function(wrap_pyfunction!(mcts, m)?)?;
     m.add_function(wrap_pyfunction!(mcts_team_preview, m)?)?;
+    m.add_function(wrap_pyfunction!(mcts_team_preview_all, m)?)?;
+    m.add_function(wrap_pyfunction!(mcts_information_set, m)?)?;
+    m.add_function(wrap_pyfunction!(mcts_in_background, m)?)?;
+    m.add_function(wrap_pyfunction!(mcts_root_parallel, m)?)?;
     m.add_class::<PyState>()?;
     m.add_class::<PySide>()?;
     m.add_class::<PySideSlot>()?;
@@ -1302,6 +1958,13 @@ fn py_poke_engine(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyStateInstructions>()?;
     m.add_class::<PyMctsResult>()?;
     m.add_class::<PyMctsSideResult>()?;
+    m.add_class::<PyPrincipalVariationPly>()?;
     m.add_class::<PyTeamPreviewFilters>()?;
+    m.add_class::<PyTeamPreviewTeam>()?;
+    m.add_class::<PyTeamPreviewSideResult>()?;
+    m.add_class::<PyTeamPreviewResult>()?;
+    m.add_class::<PyMctsConfig>()?;
+    m.add_class::<PyOpponentSet>()?;
+    m.add_class::<PyBackgroundSearch>()?;
     Ok(())
 }
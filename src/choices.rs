@@ -100,6 +100,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::ACIDDOWNPOUR,
+            Choice {
+                move_id: Choices::ACIDDOWNPOUR,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::POISON,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::ACIDSPRAY,
             Choice {
@@ -257,6 +270,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::ALLOUTPUMMELING,
+            Choice {
+                move_id: Choices::ALLOUTPUMMELING,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FIGHTING,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::ALLURINGVOICE,
             Choice {
@@ -1099,6 +1125,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::BLACKHOLEECLIPSE,
+            Choice {
+                move_id: Choices::BLACKHOLEECLIPSE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::DARK,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::BLASTBURN,
             Choice {
@@ -1231,6 +1270,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::BLOOMDOOM,
+            Choice {
+                move_id: Choices::BLOOMDOOM,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GRASS,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::BLUEFLARE,
             Choice {
@@ -1485,6 +1537,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::BREAKNECKBLITZ,
+            Choice {
+                move_id: Choices::BREAKNECKBLITZ,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::NORMAL,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::BRICKBREAK,
             Choice {
@@ -2354,6 +2419,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::CONTINENTALCRUSH,
+            Choice {
+                move_id: Choices::CONTINENTALCRUSH,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ROCK,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::CONVERSION,
             Choice {
@@ -2404,6 +2482,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::CORKSCREWCRASH,
+            Choice {
+                move_id: Choices::CORKSCREWCRASH,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::STEEL,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::CORROSIVEGAS,
             Choice {
@@ -2914,6 +3005,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::DEVASTATINGDRAKE,
+            Choice {
+                move_id: Choices::DEVASTATINGDRAKE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::DRAGON,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::DIAMONDSTORM,
             Choice {
@@ -5348,6 +5452,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::GIGAVOLTHAVOC,
+            Choice {
+                move_id: Choices::GIGAVOLTHAVOC,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ELECTRIC,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::GLACIALLANCE,
             Choice {
@@ -6759,6 +6876,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::HYDROVORTEX,
+            Choice {
+                move_id: Choices::HYDROVORTEX,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::WATER,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::HYPERBEAM,
             Choice {
@@ -7180,6 +7310,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::INFERNOOVERDRIVE,
+            Choice {
+                move_id: Choices::INFERNOOVERDRIVE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FIRE,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::INFESTATION,
             Choice {
@@ -8271,6 +8414,257 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::MAXAIRSTREAM,
+            Choice {
+                move_id: Choices::MAXAIRSTREAM,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FLYING,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXDARKNESS,
+            Choice {
+                move_id: Choices::MAXDARKNESS,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::DARK,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXFLARE,
+            Choice {
+                move_id: Choices::MAXFLARE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FIRE,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXFLUTTERBY,
+            Choice {
+                move_id: Choices::MAXFLUTTERBY,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::BUG,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXGEYSER,
+            Choice {
+                move_id: Choices::MAXGEYSER,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::WATER,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXGUARD,
+            Choice {
+                move_id: Choices::MAXGUARD,
+                priority: 4,
+                target: MoveTarget::User,
+                move_type: PokemonType::NORMAL,
+                flags: Flags {
+                    ..Default::default()
+                },
+                volatile_status: Some(VolatileStatus {
+                    target: MoveTarget::User,
+                    volatile_status: PokemonVolatileStatus::MAXGUARD,
+                }),
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXHAILSTORM,
+            Choice {
+                move_id: Choices::MAXHAILSTORM,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ICE,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXKNUCKLE,
+            Choice {
+                move_id: Choices::MAXKNUCKLE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FIGHTING,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXLIGHTNING,
+            Choice {
+                move_id: Choices::MAXLIGHTNING,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ELECTRIC,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXMINDSTORM,
+            Choice {
+                move_id: Choices::MAXMINDSTORM,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::PSYCHIC,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXOOZE,
+            Choice {
+                move_id: Choices::MAXOOZE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::POISON,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXOVERGROWTH,
+            Choice {
+                move_id: Choices::MAXOVERGROWTH,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GRASS,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXPHANTASM,
+            Choice {
+                move_id: Choices::MAXPHANTASM,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GHOST,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXQUAKE,
+            Choice {
+                move_id: Choices::MAXQUAKE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GROUND,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXROCKFALL,
+            Choice {
+                move_id: Choices::MAXROCKFALL,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ROCK,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXSTARFALL,
+            Choice {
+                move_id: Choices::MAXSTARFALL,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FAIRY,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXSTEELSPIKE,
+            Choice {
+                move_id: Choices::MAXSTEELSPIKE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::STEEL,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXSTRIKE,
+            Choice {
+                move_id: Choices::MAXSTRIKE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::NORMAL,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        moves.insert(
+            Choices::MAXWYRMWIND,
+            Choice {
+                move_id: Choices::MAXWYRMWIND,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::DRAGON,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::MEANLOOK,
             Choice {
@@ -9171,6 +9565,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::NEVERENDINGNIGHTMARE,
+            Choice {
+                move_id: Choices::NEVERENDINGNIGHTMARE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GHOST,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::NIGHTDAZE,
             Choice {
@@ -11568,6 +11975,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::SAVAGESPINOUT,
+            Choice {
+                move_id: Choices::SAVAGESPINOUT,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::BUG,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::SCALD,
             Choice {
@@ -11990,6 +12410,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::SHATTEREDPSYCHE,
+            Choice {
+                move_id: Choices::SHATTEREDPSYCHE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::PSYCHIC,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::SHEDTAIL,
             Choice {
@@ -13613,6 +14046,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::SUBZEROSLAMMER,
+            Choice {
+                move_id: Choices::SUBZEROSLAMMER,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::ICE,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::SUCKERPUNCH,
             Choice {
@@ -13733,6 +14179,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::SUPERSONICSKYSTRIKE,
+            Choice {
+                move_id: Choices::SUPERSONICSKYSTRIKE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FLYING,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::SURF,
             Choice {
@@ -14186,6 +14645,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::TECTONICRAGE,
+            Choice {
+                move_id: Choices::TECTONICRAGE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::GROUND,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::TEETERDANCE,
             Choice {
@@ -14999,6 +15471,19 @@ lazy_static! {
                 ..Default::default()
             },
         );
+        moves.insert(
+            Choices::TWINKLETACKLE,
+            Choice {
+                move_id: Choices::TWINKLETACKLE,
+                category: MoveCategory::Physical,
+                move_type: PokemonType::FAIRY,
+                flags: Flags {
+                    protect: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
         moves.insert(
             Choices::TWISTER,
             Choice {
@@ -15831,6 +16316,13 @@ lazy_static! {
                 ..Default::default()
             },
         );
+
+        #[cfg(feature = "gen5")]
+        apply_gen5_move_changes(&mut moves);
+
+        #[cfg(feature = "gen8")]
+        apply_gen8_move_changes(&mut moves);
+
         moves
     };
 }
@@ -16038,6 +16530,7 @@ define_enum_with_from_str! {
         ACCELEROCK,
         ACID,
         ACIDARMOR,
+        ACIDDOWNPOUR,
         ACIDSPRAY,
         ACROBATICS,
         ACUPRESSURE,
@@ -16047,6 +16540,7 @@ define_enum_with_from_str! {
         AGILITY,
         AIRCUTTER,
         AIRSLASH,
+        ALLOUTPUMMELING,
         ALLURINGVOICE,
         ALLYSWITCH,
         AMNESIA,
@@ -16094,6 +16588,7 @@ define_enum_with_from_str! {
         BITE,
         BITTERBLADE,
         BITTERMALICE,
+        BLACKHOLEECLIPSE,
         BLASTBURN,
         BLAZEKICK,
         BLAZINGTORQUE,
@@ -16101,6 +16596,7 @@ define_enum_with_from_str! {
         BLIZZARD,
         BLOCK,
         BLOODMOON,
+        BLOOMDOOM,
         BLUEFLARE,
         BODYPRESS,
         BODYSLAM,
@@ -16115,6 +16611,7 @@ define_enum_with_from_str! {
         BRANCHPOKE,
         BRAVEBIRD,
         BREAKINGSWIPE,
+        BREAKNECKBLITZ,
         BRICKBREAK,
         BRINE,
         BRUTALSWING,
@@ -16159,10 +16656,12 @@ define_enum_with_from_str! {
         CONFUSERAY,
         CONFUSION,
         CONSTRICT,
+        CONTINENTALCRUSH,
         CONVERSION,
         CONVERSION2,
         COPYCAT,
         COREENFORCER,
+        CORKSCREWCRASH,
         CORROSIVEGAS,
         COSMICPOWER,
         COTTONGUARD,
@@ -16189,6 +16688,7 @@ define_enum_with_from_str! {
         DEFOG,
         DESTINYBOND,
         DETECT,
+        DEVASTATINGDRAKE,
         DIAMONDSTORM,
         DIG,
         DIRECLAW,
@@ -16326,6 +16826,7 @@ define_enum_with_from_str! {
         GIGADRAIN,
         GIGAIMPACT,
         GIGATONHAMMER,
+        GIGAVOLTHAVOC,
         GLACIALLANCE,
         GLACIATE,
         GLAIVERUSH,
@@ -16415,6 +16916,7 @@ define_enum_with_from_str! {
         HYDROCANNON,
         HYDROPUMP,
         HYDROSTEAM,
+        HYDROVORTEX,
         HYPERBEAM,
         HYPERDRILL,
         HYPERFANG,
@@ -16437,6 +16939,7 @@ define_enum_with_from_str! {
         INCINERATE,
         INFERNALPARADE,
         INFERNO,
+        INFERNOOVERDRIVE,
         INFESTATION,
         INGRAIN,
         INSTRUCT,
@@ -16498,6 +17001,25 @@ define_enum_with_from_str! {
         MALIGNANTCHAIN,
         MATBLOCK,
         MATCHAGOTCHA,
+        MAXAIRSTREAM,
+        MAXDARKNESS,
+        MAXFLARE,
+        MAXFLUTTERBY,
+        MAXGEYSER,
+        MAXGUARD,
+        MAXHAILSTORM,
+        MAXKNUCKLE,
+        MAXLIGHTNING,
+        MAXMINDSTORM,
+        MAXOOZE,
+        MAXOVERGROWTH,
+        MAXPHANTASM,
+        MAXQUAKE,
+        MAXROCKFALL,
+        MAXSTARFALL,
+        MAXSTEELSPIKE,
+        MAXSTRIKE,
+        MAXWYRMWIND,
         MEANLOOK,
         MEDITATE,
         MEFIRST,
@@ -16546,6 +17068,7 @@ define_enum_with_from_str! {
         NATUREPOWER,
         NATURESMADNESS,
         NEEDLEARM,
+        NEVERENDINGNIGHTMARE,
         NIGHTDAZE,
         NIGHTMARE,
         NIGHTSHADE,
@@ -16683,6 +17206,7 @@ define_enum_with_from_str! {
         SANDSTORM,
         SANDTOMB,
         SAPPYSEED,
+        SAVAGESPINOUT,
         SCALD,
         SCALESHOT,
         SCARYFACE,
@@ -16704,6 +17228,7 @@ define_enum_with_from_str! {
         SHADOWSNEAK,
         SHADOWSTRIKE,
         SHARPEN,
+        SHATTEREDPSYCHE,
         SHEDTAIL,
         SHEERCOLD,
         SHELLSIDEARM,
@@ -16795,6 +17320,7 @@ define_enum_with_from_str! {
         STUNSPORE,
         SUBMISSION,
         SUBSTITUTE,
+        SUBZEROSLAMMER,
         SUCKERPUNCH,
         SUNNYDAY,
         SUNSTEELSTRIKE,
@@ -16802,6 +17328,7 @@ define_enum_with_from_str! {
         SUPERFANG,
         SUPERPOWER,
         SUPERSONIC,
+        SUPERSONICSKYSTRIKE,
         SURF,
         SURGINGSTRIKES,
         SWAGGER,
@@ -16827,6 +17354,7 @@ define_enum_with_from_str! {
         TEARFULLOOK,
         TEATIME,
         TECHNOBLAST,
+        TECTONICRAGE,
         TEETERDANCE,
         TELEKINESIS,
         TELEPORT,
@@ -16870,6 +17398,7 @@ define_enum_with_from_str! {
         TRUMPCARD,
         TWINBEAM,
         TWINEEDLE,
+        TWINKLETACKLE,
         TWISTER,
         UPPERHAND,
         UPROAR,
@@ -17031,6 +17560,8 @@ pub struct Choice {
 
     pub first_move: bool,
     pub sleep_talk_move: bool,
+    pub z_move: bool,
+    pub max_move: bool,
 }
 
 impl fmt::Debug for Choice {
@@ -17162,6 +17693,8 @@ impl Default for Choice {
             move_choice_target: MoveChoiceTarget::Normal,
             first_move: true,
             sleep_talk_move: false,
+            z_move: false,
+            max_move: false,
         }
     }
 }
@@ -17189,3 +17722,72 @@ pub fn undo_physical_special_split(moves: &mut HashMap<Choices, Choice>) {
         }
     }
 }
+
+#[cfg(feature = "gen5")]
+fn apply_gen5_move_changes(moves: &mut HashMap<Choices, Choice>) {
+    // base powers that were changed in gen6 and later
+    let base_power_changes = [
+        (Choices::AIRCUTTER, 55.0),
+        (Choices::ASSURANCE, 50.0),
+        (Choices::BLIZZARD, 120.0),
+        (Choices::CHATTER, 60.0),
+        (Choices::COVET, 40.0),
+        (Choices::CRABHAMMER, 90.0),
+        (Choices::DRACOMETEOR, 140.0),
+        (Choices::DRAGONPULSE, 90.0),
+        (Choices::ENERGYBALL, 80.0),
+        (Choices::FIREBLAST, 120.0),
+        (Choices::FLAMETHROWER, 95.0),
+        (Choices::FROSTBREATH, 40.0),
+        (Choices::FURYCUTTER, 20.0),
+        (Choices::HEATWAVE, 100.0),
+        (Choices::HURRICANE, 120.0),
+        (Choices::HYDROPUMP, 120.0),
+        (Choices::ICEBEAM, 95.0),
+        (Choices::INCINERATE, 30.0),
+        (Choices::KNOCKOFF, 20.0),
+        (Choices::LEAFSTORM, 140.0),
+        (Choices::LICK, 20.0),
+        (Choices::MAGMASTORM, 120.0),
+        (Choices::METEORMASH, 100.0),
+        (Choices::MUDDYWATER, 95.0),
+        (Choices::OVERHEAT, 140.0),
+        (Choices::PINMISSILE, 14.0),
+        (Choices::SMOG, 20.0),
+        (Choices::STORMTHROW, 40.0),
+        (Choices::STRUGGLEBUG, 30.0),
+        (Choices::SURF, 95.0),
+        (Choices::SYNCHRONOISE, 70.0),
+        (Choices::TACKLE, 50.0),
+        (Choices::THIEF, 40.0),
+        (Choices::THUNDER, 120.0),
+        (Choices::THUNDERBOLT, 95.0),
+    ];
+    for (move_id, base_power) in base_power_changes {
+        if let Some(choice) = moves.get_mut(&move_id) {
+            choice.base_power = base_power;
+        }
+    }
+
+    // the fairy type did not exist yet
+    for move_id in [Choices::CHARM, Choices::MOONLIGHT, Choices::SWEETKISS] {
+        if let Some(choice) = moves.get_mut(&move_id) {
+            choice.move_type = PokemonType::NORMAL;
+        }
+    }
+}
+
+#[cfg(feature = "gen8")]
+fn apply_gen8_move_changes(moves: &mut HashMap<Choices, Choice>) {
+    // base powers that were lowered in gen9
+    let base_power_changes = [
+        (Choices::GLACIALLANCE, 130.0),
+        (Choices::GRASSYGLIDE, 70.0),
+        (Choices::WICKEDBLOW, 80.0),
+    ];
+    for (move_id, base_power) in base_power_changes {
+        if let Some(choice) = moves.get_mut(&move_id) {
+            choice.base_power = base_power;
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use crate::engine::evaluate::evaluate;
+use crate::engine::generate_instructions::generate_instructions_from_move_pair;
+use crate::engine::state::{MoveChoice, MoveOptions};
+use crate::mcts::{
+    index_of_max, solve_matrix_game, static_score, MctsConfig, MctsResult, MctsSideResult,
+};
+use crate::state::State;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// regret matching iterations used to solve a node's matrix game when it has no saddle point
+const ENDGAME_SOLVE_ITERATIONS: u32 = 1_000;
+
+/// Whether `state` is small enough to be solved by `solve_endgame`: both sides have at most
+/// `MctsConfig::endgame_pokemon` pokemon left
+pub fn is_endgame(state: &State, config: &MctsConfig) -> bool {
+    config.endgame_pokemon > 0
+        && !state.team_preview
+        && state.sides.iter().all(|side| {
+            side.pokemon.pkmn.iter().filter(|x| x.hp > 0).count() <= config.endgame_pokemon as usize
+        })
+}
+
+// the rows and columns left after repeatedly removing options that are strictly dominated by
+// another single option. side one picks a row to maximize `payoffs`, side two a column to
+// minimize it
+fn undominated(payoffs: &[Vec<f32>]) -> (Vec<usize>, Vec<usize>) {
+    let mut rows: Vec<usize> = (0..payoffs.len()).collect();
+    let mut columns: Vec<usize> = (0..payoffs.first().map_or(0, |row| row.len())).collect();
+    loop {
+        let row_count = rows.len();
+        let column_count = columns.len();
+        let remaining_rows = rows.clone();
+        rows.retain(|&a| {
+            !remaining_rows
+                .iter()
+                .any(|&b| columns.iter().all(|&j| payoffs[b][j] > payoffs[a][j]))
+        });
+        let remaining_columns = columns.clone();
+        columns.retain(|&a| {
+            !remaining_columns
+                .iter()
+                .any(|&b| rows.iter().all(|&i| payoffs[i][b] < payoffs[i][a]))
+        });
+        if rows.len() == row_count && columns.len() == column_count {
+            return (rows, columns);
+        }
+    }
+}
+
+// each side's equilibrium strategy in the zero-sum game of `payoffs`
+fn solve_game(payoffs: &[Vec<f32>]) -> (Vec<f32>, Vec<f32>) {
+    let mut s1_strategy = vec![0.0; payoffs.len()];
+    let mut s2_strategy = vec![0.0; payoffs[0].len()];
+
+    // a saddle point is an equilibrium in pure strategies
+    let row_minimums: Vec<f32> = payoffs
+        .iter()
+        .map(|row| row.iter().copied().fold(f32::MAX, f32::min))
+        .collect();
+    let column_maximums: Vec<f32> = (0..s2_strategy.len())
+        .map(|j| payoffs.iter().map(|row| row[j]).fold(f32::MIN, f32::max))
+        .collect();
+    let maximin = index_of_max(row_minimums.iter().copied());
+    let minimax = index_of_max(column_maximums.iter().map(|x| -x));
+    if row_minimums[maximin] >= column_maximums[minimax] {
+        s1_strategy[maximin] = 1.0;
+        s2_strategy[minimax] = 1.0;
+        return (s1_strategy, s2_strategy);
+    }
+
+    let (rows, columns) = undominated(payoffs);
+    let reduced: Vec<Vec<f32>> = rows
+        .iter()
+        .map(|&i| columns.iter().map(|&j| payoffs[i][j]).collect())
+        .collect();
+    let (reduced_s1, reduced_s2) = solve_matrix_game(&reduced, ENDGAME_SOLVE_ITERATIONS);
+    for (&i, p) in rows.iter().zip(reduced_s1) {
+        s1_strategy[i] = p;
+    }
+    for (&j, q) in columns.iter().zip(reduced_s2) {
+        s2_strategy[j] = q;
+    }
+    (s1_strategy, s2_strategy)
+}
+
+// side one's best score when it picks an option first and side two answers it (the lower
+// bound), and side two's when it goes first (the upper bound). The value of the game lies
+// between the two and equals both when the game has a saddle point.
+//
+// Once an option is answered by something worse than the best bound found so far, the rest of
+// its answers are skipped, so `payoff` is only called for the pairs the bounds depend on
+fn pure_strategy_bounds(
+    rows: usize,
+    columns: usize,
+    mut payoff: impl FnMut(usize, usize) -> Option<f32>,
+) -> Option<(f32, f32)> {
+    let mut lower = f32::MIN;
+    for i in 0..rows {
+        let mut row_minimum = f32::MAX;
+        for j in 0..columns {
+            row_minimum = row_minimum.min(payoff(i, j)?);
+            if row_minimum <= lower {
+                break;
+            }
+        }
+        lower = lower.max(row_minimum);
+    }
+    let mut upper = f32::MAX;
+    for j in 0..columns {
+        let mut column_maximum = f32::MIN;
+        for i in 0..rows {
+            column_maximum = column_maximum.max(payoff(i, j)?);
+            if column_maximum >= upper {
+                break;
+            }
+        }
+        upper = upper.min(column_maximum);
+    }
+    Some((lower, upper))
+}
+
+fn game_value(payoffs: &[Vec<f32>], s1_strategy: &[f32], s2_strategy: &[f32]) -> f32 {
+    payoffs
+        .iter()
+        .zip(s1_strategy)
+        .map(|(row, p)| p * row.iter().zip(s2_strategy).map(|(x, q)| x * q).sum::<f32>())
+        .sum()
+}
+
+struct Solver<'a> {
+    root_eval: f32,
+    config: &'a MctsConfig,
+    move_options: MoveOptions,
+
+    // side one's value of a state searched some number of turns deep, keyed by the state's
+    // hash and that number of turns
+    values: HashMap<(u64, u8), f32>,
+    deadline: Option<Instant>,
+}
+
+impl Solver<'_> {
+    // side one's score for a pair of options, every outcome searched `depth` more turns.
+    // `None` if the deadline passed before every outcome was searched
+    fn payoff(
+        &mut self,
+        state: &mut State,
+        s1_move: &(MoveChoice, MoveChoice),
+        s2_move: &(MoveChoice, MoveChoice),
+        depth: u8,
+    ) -> Option<f32> {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return None;
+        }
+        let outcomes = generate_instructions_from_move_pair(
+            state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, true,
+        );
+        let total_percentage: f32 = outcomes.iter().map(|x| x.percentage).sum();
+        let mut payoff = 0.0;
+        for outcome in outcomes.iter() {
+            let child_depth = if outcome.end_of_turn_triggered {
+                depth - 1
+            } else {
+                depth
+            };
+            state.apply_instructions(&outcome.instruction_list);
+            let value = self.value(state, child_depth);
+            state.reverse_instructions(&outcome.instruction_list);
+            payoff += value? * outcome.percentage / total_percentage;
+        }
+        Some(payoff)
+    }
+
+    // side one's score for each pair of options, every outcome searched `depth` more turns.
+    // `None` if the deadline passed before every pair was searched
+    fn payoffs(
+        &mut self,
+        state: &mut State,
+        s1_options: &[(MoveChoice, MoveChoice)],
+        s2_options: &[(MoveChoice, MoveChoice)],
+        depth: u8,
+    ) -> Option<Vec<Vec<f32>>> {
+        let mut payoffs = vec![vec![0.0; s2_options.len()]; s1_options.len()];
+        for (s1_move, row) in s1_options.iter().zip(payoffs.iter_mut()) {
+            for (s2_move, payoff) in s2_options.iter().zip(row.iter_mut()) {
+                *payoff = self.payoff(state, s1_move, s2_move, depth)?;
+            }
+        }
+        Some(payoffs)
+    }
+
+    // side one's value of `state` searched `depth` more turns
+    fn value(&mut self, state: &mut State, depth: u8) -> Option<f32> {
+        if depth == 0 || state.battle_is_over() != 0.0 {
+            return Some(static_score(state, self.root_eval, self.config));
+        }
+        if let Some(value) = self.values.get(&(state.hash, depth)) {
+            return Some(*value);
+        }
+
+        state.get_all_options(&mut self.move_options);
+        let s1_options: Vec<(MoveChoice, MoveChoice)> = self
+            .move_options
+            .side_one_combined_options
+            .drain(..)
+            .collect();
+        let s2_options: Vec<(MoveChoice, MoveChoice)> = self
+            .move_options
+            .side_two_combined_options
+            .drain(..)
+            .collect();
+        if s1_options.is_empty() || s2_options.is_empty() {
+            return Some(static_score(state, self.root_eval, self.config));
+        }
+
+        // a saddle point settles the value without searching every pair of options
+        let mut searched = vec![vec![None; s2_options.len()]; s1_options.len()];
+        let (lower, upper) = pure_strategy_bounds(s1_options.len(), s2_options.len(), |i, j| {
+            if searched[i][j].is_none() {
+                searched[i][j] = Some(self.payoff(state, &s1_options[i], &s2_options[j], depth)?);
+            }
+            searched[i][j]
+        })?;
+        let value = if lower >= upper {
+            lower
+        } else {
+            let mut payoffs = vec![vec![0.0; s2_options.len()]; s1_options.len()];
+            for (i, row) in payoffs.iter_mut().enumerate() {
+                for (j, payoff) in row.iter_mut().enumerate() {
+                    *payoff = match searched[i][j] {
+                        Some(searched_payoff) => searched_payoff,
+                        None => self.payoff(state, &s1_options[i], &s2_options[j], depth)?,
+                    };
+                }
+            }
+            let (s1_strategy, s2_strategy) = solve_game(&payoffs);
+            game_value(&payoffs, &s1_strategy, &s2_strategy)
+        };
+        self.values.insert((state.hash, depth), value);
+        Some(value)
+    }
+}
+
+/// Solves `state` as a simultaneous-move game searched `MctsConfig::endgame_depth` turns deep,
+/// scoring the states it ends on like a search scores its leaves.
+///
+/// Every outcome of a pair of options is searched, weighted by its percentage, and each state
+/// is solved as a matrix game for both sides' equilibrium strategies. A state below the root
+/// whose game has a saddle point is settled by the pairs of options its pure strategy bounds
+/// depend on, without searching the rest. Options that are strictly dominated are left out of
+/// the game and states that are reached more than once are only solved once.
+///
+/// The search deepens one turn at a time and returns the deepest search that finished within
+/// `max_time`, or `None` if not even a search one turn deep did. Each option's
+/// `nash_probability` is the share of the equilibrium strategy it gets and its average score is
+/// its score against the other side's strategy. Its `visits` are synthetic: that share of
+/// `iteration_count`, the number of states solved including `state`. Both leaf depths are the
+/// number of turns the returned search was deep
+pub fn solve_endgame(
+    state: &mut State,
+    side_one_options: &[(MoveChoice, MoveChoice)],
+    side_two_options: &[(MoveChoice, MoveChoice)],
+    max_time: Duration,
+    config: &MctsConfig,
+) -> Option<MctsResult> {
+    let track_hash = std::mem::replace(&mut state.track_hash, true);
+    state.rehash();
+    let mut solver = Solver {
+        root_eval: evaluate(state),
+        config,
+        move_options: MoveOptions::new(),
+        values: HashMap::new(),
+        deadline: Instant::now().checked_add(max_time),
+    };
+
+    let mut payoffs = None;
+    let mut searched_depth = 0;
+    for depth in 1..=config.endgame_depth.max(1) {
+        match solver.payoffs(state, side_one_options, side_two_options, depth) {
+            Some(depth_payoffs) => payoffs = Some(depth_payoffs),
+            None => break,
+        }
+        searched_depth = depth;
+    }
+    state.track_hash = track_hash;
+    let payoffs = payoffs?;
+    let (s1_strategy, s2_strategy) = solve_game(&payoffs);
+    let iteration_count = solver.values.len() as u64 + 1;
+
+    let side_result = |move_choice: (MoveChoice, MoveChoice), probability: f32, score: f32| {
+        let visits = (probability * iteration_count as f32).round() as u64;
+        MctsSideResult {
+            move_choice,
+            total_score: score * visits as f32,
+            visits,
+            nash_probability: Some(probability),
+        }
+    };
+    Some(MctsResult {
+        s1: side_one_options
+            .iter()
+            .zip(payoffs.iter())
+            .zip(s1_strategy.iter())
+            .map(|((move_choice, row), p)| {
+                let score = row.iter().zip(&s2_strategy).map(|(x, q)| x * q).sum();
+                side_result(*move_choice, *p, score)
+            })
+            .collect(),
+        s2: side_two_options
+            .iter()
+            .enumerate()
+            .zip(s2_strategy.iter())
+            .map(|((j, move_choice), q)| {
+                let score = payoffs
+                    .iter()
+                    .zip(&s1_strategy)
+                    .map(|(row, p)| (1.0 - row[j]) * p)
+                    .sum();
+                side_result(*move_choice, *q, score)
+            })
+            .collect(),
+        iteration_count,
+        principal_variation: Vec::new(),
+        max_leaf_depth: searched_depth,
+        average_leaf_depth: searched_depth as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_game_finds_a_saddle_point() {
+        let payoffs = vec![vec![0.4, 0.6], vec![0.3, 0.2]];
+
+        let (s1_strategy, s2_strategy) = solve_game(&payoffs);
+
+        assert_eq!(vec![1.0, 0.0], s1_strategy);
+        assert_eq!(vec![1.0, 0.0], s2_strategy);
+    }
+
+    #[test]
+    fn test_solve_game_never_plays_a_dominated_option() {
+        // matching pennies with a third row that is worse than the first
+        let payoffs = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.9, -0.1]];
+
+        let (s1_strategy, s2_strategy) = solve_game(&payoffs);
+
+        assert_eq!(0.0, s1_strategy[2]);
+        assert!((s1_strategy[0] - 0.5).abs() < 0.05);
+        assert!((s2_strategy[0] - 0.5).abs() < 0.05);
+        assert!((game_value(&payoffs, &s1_strategy, &s2_strategy) - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_pure_strategy_bounds_skip_refuted_options() {
+        // a saddle point at (0, 0): once row 0 guarantees 0.5, rows 1 and 2 are refuted by
+        // their first answer, and columns 1 and 2 by row 0
+        let payoffs = [[0.5, 0.7, 0.6], [0.2, 0.9, 0.8], [0.1, 0.8, 0.9]];
+        let mut searched = Vec::new();
+
+        let bounds = pure_strategy_bounds(3, 3, |i, j| {
+            searched.push((i, j));
+            Some(payoffs[i][j])
+        });
+
+        assert_eq!(Some((0.5, 0.5)), bounds);
+        assert!(!searched.contains(&(1, 1)) && !searched.contains(&(2, 2)));
+    }
+
+    #[test]
+    fn test_pure_strategy_bounds_contain_a_mixed_value() {
+        // matching pennies has no saddle point, its value of 0.5 lies between the bounds
+        let payoffs = [[1.0, 0.0], [0.0, 1.0]];
+
+        let bounds = pure_strategy_bounds(2, 2, |i, j| Some(payoffs[i][j]));
+
+        assert_eq!(Some((0.0, 1.0)), bounds);
+    }
+
+    #[test]
+    fn test_undominated_removes_options_in_turn() {
+        // row 1 is dominated by row 0, after which column 0 is dominated by column 1
+        let payoffs = vec![vec![0.5, 0.4], vec![0.4, 0.3]];
+
+        assert_eq!((vec![0], vec![1]), undominated(&payoffs));
+    }
+}
@@ -21,6 +21,10 @@ use crate::state::{
 };
 use std::cmp;
 
+// weather set by an ability lasted indefinitely before gen6
+#[cfg(feature = "gen5")]
+pub const WEATHER_ABILITY_TURNS: i8 = -1;
+#[cfg(not(feature = "gen5"))]
 pub const WEATHER_ABILITY_TURNS: i8 = 5;
 
 define_enum_with_from_str! {
@@ -2147,6 +2151,8 @@ pub fn ability_modify_attack_being_used(
     }
 
     match attacking_pkmn.ability {
+        // dark types became immune to prankster-boosted status moves in gen7
+        #[cfg(not(feature = "gen5"))]
         Abilities::PRANKSTER => {
             if attacker_choice.category == MoveCategory::Status
                 && attacker_choice.target == MoveTarget::Target
@@ -660,6 +660,8 @@ pub fn modify_choice(
             }
         }
 
+        // knockoff's bonus damage was added in gen6
+        #[cfg(not(feature = "gen5"))]
         Choices::KNOCKOFF => {
             // Bonus damage still applies if substitute is hit
             let target = target_side.get_active_immutable(target_slot_ref);
@@ -782,6 +784,15 @@ pub fn modify_choice(
                 attacker_choice.base_power *= 1.3;
             }
         }
+        Choices::GRASSKNOT | Choices::LOWKICK | Choices::HEATCRASH | Choices::HEAVYSLAM
+            if target_side
+                .get_slot_immutable(target_slot_ref)
+                .volatile_statuses
+                .contains(&PokemonVolatileStatus::DYNAMAX) =>
+        {
+            // weight based moves fail against a dynamaxed target
+            attacker_choice.remove_all_effects();
+        }
         Choices::GRASSKNOT | Choices::LOWKICK => {
             let target_active = target_side.get_active_immutable(target_slot_ref);
             if target_active.weight_kg < 10.0 {
@@ -870,6 +881,105 @@ pub fn choice_after_damage_hit(
             .insert(PokemonVolatileStatus::TRUANT);
     }
     match choice.move_id {
+        Choices::MAXFLARE | Choices::MAXGEYSER | Choices::MAXHAILSTORM | Choices::MAXROCKFALL => {
+            let new_weather = match choice.move_id {
+                Choices::MAXFLARE => Weather::SUN,
+                Choices::MAXGEYSER => Weather::RAIN,
+                Choices::MAXHAILSTORM => Weather::HAIL,
+                _ => Weather::SAND,
+            };
+            if state.weather.weather_type != new_weather {
+                instructions
+                    .instruction_list
+                    .push(Instruction::ChangeWeather(ChangeWeather {
+                        new_weather,
+                        new_weather_turns_remaining: 5,
+                        previous_weather: state.weather.weather_type,
+                        previous_weather_turns_remaining: state.weather.turns_remaining,
+                    }));
+                state.weather.weather_type = new_weather;
+                state.weather.turns_remaining = 5;
+            }
+        }
+        Choices::MAXLIGHTNING
+        | Choices::MAXOVERGROWTH
+        | Choices::MAXMINDSTORM
+        | Choices::MAXSTARFALL => {
+            let new_terrain = match choice.move_id {
+                Choices::MAXLIGHTNING => Terrain::ELECTRICTERRAIN,
+                Choices::MAXOVERGROWTH => Terrain::GRASSYTERRAIN,
+                Choices::MAXMINDSTORM => Terrain::PSYCHICTERRAIN,
+                _ => Terrain::MISTYTERRAIN,
+            };
+            if state.terrain.terrain_type != new_terrain {
+                instructions
+                    .instruction_list
+                    .push(Instruction::ChangeTerrain(ChangeTerrain {
+                        new_terrain,
+                        new_terrain_turns_remaining: 5,
+                        previous_terrain: state.terrain.terrain_type,
+                        previous_terrain_turns_remaining: state.terrain.turns_remaining,
+                    }));
+                state.terrain.terrain_type = new_terrain;
+                state.terrain.turns_remaining = 5;
+            }
+        }
+        // boosts both pokemon on the user's side
+        Choices::MAXKNUCKLE
+        | Choices::MAXOOZE
+        | Choices::MAXQUAKE
+        | Choices::MAXAIRSTREAM
+        | Choices::MAXSTEELSPIKE => {
+            let stat = match choice.move_id {
+                Choices::MAXKNUCKLE => PokemonBoostableStat::Attack,
+                Choices::MAXOOZE => PokemonBoostableStat::SpecialAttack,
+                Choices::MAXQUAKE => PokemonBoostableStat::SpecialDefense,
+                Choices::MAXAIRSTREAM => PokemonBoostableStat::Speed,
+                _ => PokemonBoostableStat::Defense,
+            };
+            let (attacking_side, other_side) = state.get_both_sides(attacking_side_ref);
+            for slot_ref in [SlotReference::SlotA, SlotReference::SlotB] {
+                apply_boost_instructions(
+                    attacking_side,
+                    other_side,
+                    &stat,
+                    &1,
+                    attacking_side_ref,
+                    attacking_side_ref,
+                    attacking_slot_ref,
+                    slot_ref,
+                    instructions,
+                );
+            }
+        }
+        // lowers a stat of both pokemon on the target's side
+        Choices::MAXSTRIKE
+        | Choices::MAXFLUTTERBY
+        | Choices::MAXPHANTASM
+        | Choices::MAXWYRMWIND
+        | Choices::MAXDARKNESS => {
+            let stat = match choice.move_id {
+                Choices::MAXSTRIKE => PokemonBoostableStat::Speed,
+                Choices::MAXFLUTTERBY => PokemonBoostableStat::SpecialAttack,
+                Choices::MAXPHANTASM => PokemonBoostableStat::Defense,
+                Choices::MAXWYRMWIND => PokemonBoostableStat::Attack,
+                _ => PokemonBoostableStat::SpecialDefense,
+            };
+            let (target_side, attacking_side) = state.get_both_sides(target_side_ref);
+            for slot_ref in [SlotReference::SlotA, SlotReference::SlotB] {
+                apply_boost_instructions(
+                    target_side,
+                    attacking_side,
+                    &stat,
+                    &-1,
+                    attacking_side_ref,
+                    target_side_ref,
+                    attacking_slot_ref,
+                    slot_ref,
+                    instructions,
+                );
+            }
+        }
         Choices::DOUBLESHOCK => {
             let (attacker_active, attacker_index) =
                 attacking_side.get_active_with_index(attacking_slot_ref);
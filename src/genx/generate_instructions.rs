@@ -20,8 +20,9 @@ use crate::instruction::{
     ChangeVolatileStatusDurationInstruction, ChangeWeather, DecrementRestTurnsInstruction,
     HealInstruction, InsertStellarBoostedTypeInstruction, RemoveVolatileStatusInstruction,
     SetSleepTurnsInstruction, ToggleBatonPassingInstruction,
-    ToggleDamageDealtHitSubstituteInstruction, ToggleMegaEvolvedInstruction,
-    ToggleShedTailingInstruction, ToggleTrickRoomInstruction,
+    ToggleDamageDealtHitSubstituteInstruction, ToggleDynamaxUsedInstruction,
+    ToggleMegaEvolvedInstruction, ToggleMegaUsedInstruction, ToggleShedTailingInstruction,
+    ToggleTrickRoomInstruction, ToggleZMoveUsedInstruction,
 };
 use crate::instruction::{DamageSubstituteInstruction, ToggleTerastallizedInstruction};
 use crate::instruction::{FormeChangeInstruction, SetLastUsedMoveInstruction};
@@ -32,7 +33,8 @@ use super::items::{item_on_switch_in, Items};
 use super::state::{MoveChoice, PokemonVolatileStatus, Terrain, Weather};
 use crate::choices::{Choice, MoveCategory};
 use crate::instruction::{
-    ChangeStatusInstruction, DamageInstruction, Instruction, StateInstructions, SwitchInstruction,
+    ChangeStatInstruction, ChangeStatusInstruction, DamageInstruction, Instruction,
+    StateInstructions, SwitchInstruction,
 };
 use crate::state::{
     LastUsedMove, PokemonBoostableStat, PokemonIndex, PokemonMoveIndex, PokemonSideCondition,
@@ -46,20 +48,35 @@ use crate::pokemon::PokemonName;
 
 pub const PARALYSIS_CHANCE: f32 = 0.125;
 pub const THAW_CHANCE: f32 = 0.25;
+#[cfg(feature = "gen5")]
+pub const BASE_CRIT_CHANCE: f32 = 1.0 / 16.0;
+#[cfg(not(feature = "gen5"))]
 pub const BASE_CRIT_CHANCE: f32 = 1.0 / 24.0;
+
+// paralysis quartered speed and burn did 1/8 of max hp per turn before gen7
+#[cfg(feature = "gen5")]
+pub const PARALYSIS_SPEED_MULTIPLIER: f32 = 0.25;
+#[cfg(not(feature = "gen5"))]
+pub const PARALYSIS_SPEED_MULTIPLIER: f32 = 0.5;
+#[cfg(feature = "gen5")]
+pub const BURN_DAMAGE_FACTOR: f32 = 0.125;
+#[cfg(not(feature = "gen5"))]
+pub const BURN_DAMAGE_FACTOR: f32 = 0.0625;
 pub const MAX_SLEEP_TURNS: i8 = 3;
 pub const HIT_SELF_IN_CONFUSION_CHANCE: f32 = 1.0 / 3.0;
 pub const CONSECUTIVE_PROTECT_CHANCE: f32 = 1.0 / 3.0;
 pub const SIDE_CONDITION_DURATION: i8 = 5;
 pub const TAILWIND_DURATION: i8 = 4;
+pub const DYNAMAX_DURATION: i8 = 3;
 
-const PROTECT_VOLATILES: [PokemonVolatileStatus; 6] = [
+const PROTECT_VOLATILES: [PokemonVolatileStatus; 7] = [
     PokemonVolatileStatus::PROTECT,
     PokemonVolatileStatus::BANEFULBUNKER,
     PokemonVolatileStatus::BURNINGBULWARK,
     PokemonVolatileStatus::SPIKYSHIELD,
     PokemonVolatileStatus::SILKTRAP,
     PokemonVolatileStatus::ENDURE,
+    PokemonVolatileStatus::MAXGUARD,
 ];
 
 fn chance_to_wake_up(turns_asleep: i8) -> f32 {
@@ -204,6 +221,660 @@ fn generate_instructions_from_tera(
     }
 }
 
+fn generate_instructions_from_dynamax(
+    state: &mut State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    incoming_instructions: &mut StateInstructions,
+) {
+    // Like terastallization, these instructions are generated before any move is run
+    // and are reversed before the moves are generated
+    let side = state.get_side(side_ref);
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::ToggleDynamaxUsed(
+            ToggleDynamaxUsedInstruction { side_ref },
+        ));
+    side.dynamax_used = true;
+
+    let slot = side.get_slot(slot_ref);
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::ApplyVolatileStatus(
+            ApplyVolatileStatusInstruction {
+                side_ref,
+                slot_ref: *slot_ref,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+            },
+        ));
+    slot.volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::ChangeVolatileStatusDuration(
+            ChangeVolatileStatusDurationInstruction {
+                side_ref,
+                slot_ref: *slot_ref,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: DYNAMAX_DURATION - slot.volatile_status_durations.dynamax,
+            },
+        ));
+    slot.volatile_status_durations.dynamax = DYNAMAX_DURATION;
+
+    // hp and maxhp are both doubled
+    let (active_pkmn, pokemon_index) = side.get_active_with_index(slot_ref);
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::ChangeMaxHP(ChangeStatInstruction {
+            side_ref,
+            pokemon_index,
+            amount: active_pkmn.maxhp,
+        }));
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::Heal(HealInstruction {
+            side_ref,
+            pokemon_index,
+            heal_amount: active_pkmn.hp,
+        }));
+    active_pkmn.maxhp *= 2;
+    active_pkmn.hp *= 2;
+}
+
+fn max_move_for_type(move_type: &PokemonType) -> Choices {
+    match move_type {
+        PokemonType::FIRE => Choices::MAXFLARE,
+        PokemonType::WATER => Choices::MAXGEYSER,
+        PokemonType::ELECTRIC => Choices::MAXLIGHTNING,
+        PokemonType::GRASS => Choices::MAXOVERGROWTH,
+        PokemonType::ICE => Choices::MAXHAILSTORM,
+        PokemonType::FIGHTING => Choices::MAXKNUCKLE,
+        PokemonType::POISON => Choices::MAXOOZE,
+        PokemonType::GROUND => Choices::MAXQUAKE,
+        PokemonType::FLYING => Choices::MAXAIRSTREAM,
+        PokemonType::PSYCHIC => Choices::MAXMINDSTORM,
+        PokemonType::BUG => Choices::MAXFLUTTERBY,
+        PokemonType::ROCK => Choices::MAXROCKFALL,
+        PokemonType::GHOST => Choices::MAXPHANTASM,
+        PokemonType::DRAGON => Choices::MAXWYRMWIND,
+        PokemonType::DARK => Choices::MAXDARKNESS,
+        PokemonType::STEEL => Choices::MAXSTEELSPIKE,
+        PokemonType::FAIRY => Choices::MAXSTARFALL,
+        _ => Choices::MAXSTRIKE,
+    }
+}
+
+fn max_move_base_power(choice: &Choice) -> f32 {
+    let base_power = choice.base_power;
+    // moves with a variable base power (weight based, fixed damage, etc.)
+    if base_power == 0.0 {
+        return 100.0;
+    }
+    if choice.move_type == PokemonType::FIGHTING || choice.move_type == PokemonType::POISON {
+        if base_power >= 150.0 {
+            100.0
+        } else if base_power >= 110.0 {
+            95.0
+        } else if base_power >= 75.0 {
+            90.0
+        } else if base_power >= 65.0 {
+            85.0
+        } else if base_power >= 55.0 {
+            80.0
+        } else if base_power >= 45.0 {
+            75.0
+        } else {
+            70.0
+        }
+    } else if base_power >= 150.0 {
+        150.0
+    } else if base_power >= 110.0 {
+        140.0
+    } else if base_power >= 75.0 {
+        130.0
+    } else if base_power >= 65.0 {
+        120.0
+    } else if base_power >= 55.0 {
+        110.0
+    } else if base_power >= 45.0 {
+        100.0
+    } else {
+        90.0
+    }
+}
+
+fn modify_choice_for_dynamax(
+    state: &State,
+    side_reference: SideReference,
+    slot_reference: &SlotReference,
+    choice: &mut Choice,
+    pkmn_just_dynamaxed: bool,
+) {
+    if choice.category == MoveCategory::Switch || choice.move_id == Choices::NONE {
+        return;
+    }
+    if !pkmn_just_dynamaxed
+        && !state
+            .get_side_immutable(side_reference)
+            .get_slot_immutable(slot_reference)
+            .volatile_statuses
+            .contains(&PokemonVolatileStatus::DYNAMAX)
+    {
+        return;
+    }
+
+    let mut max_move = if choice.category == MoveCategory::Status {
+        MOVES.get(&Choices::MAXGUARD).unwrap().clone()
+    } else {
+        let mut max_move = MOVES
+            .get(&max_move_for_type(&choice.move_type))
+            .unwrap()
+            .clone();
+        max_move.base_power = max_move_base_power(choice);
+        max_move.category = choice.category;
+        max_move
+    };
+    max_move.move_index = choice.move_index;
+    max_move.max_move = true;
+    *choice = max_move;
+}
+
+fn z_move_for_type(move_type: &PokemonType) -> Choices {
+    match move_type {
+        PokemonType::FIRE => Choices::INFERNOOVERDRIVE,
+        PokemonType::WATER => Choices::HYDROVORTEX,
+        PokemonType::ELECTRIC => Choices::GIGAVOLTHAVOC,
+        PokemonType::GRASS => Choices::BLOOMDOOM,
+        PokemonType::ICE => Choices::SUBZEROSLAMMER,
+        PokemonType::FIGHTING => Choices::ALLOUTPUMMELING,
+        PokemonType::POISON => Choices::ACIDDOWNPOUR,
+        PokemonType::GROUND => Choices::TECTONICRAGE,
+        PokemonType::FLYING => Choices::SUPERSONICSKYSTRIKE,
+        PokemonType::PSYCHIC => Choices::SHATTEREDPSYCHE,
+        PokemonType::BUG => Choices::SAVAGESPINOUT,
+        PokemonType::ROCK => Choices::CONTINENTALCRUSH,
+        PokemonType::GHOST => Choices::NEVERENDINGNIGHTMARE,
+        PokemonType::DRAGON => Choices::DEVASTATINGDRAKE,
+        PokemonType::DARK => Choices::BLACKHOLEECLIPSE,
+        PokemonType::STEEL => Choices::CORKSCREWCRASH,
+        PokemonType::FAIRY => Choices::TWINKLETACKLE,
+        _ => Choices::BREAKNECKBLITZ,
+    }
+}
+
+fn z_move_base_power(choice: &Choice) -> f32 {
+    // a handful of moves don't follow the table below
+    match choice.move_id {
+        Choices::MEGADRAIN => return 120.0,
+        Choices::COREENFORCER => return 140.0,
+        Choices::WEATHERBALL | Choices::HEX => return 160.0,
+        Choices::FLYINGPRESS => return 170.0,
+        Choices::GEARGRIND => return 180.0,
+        Choices::VCREATE => return 220.0,
+        _ => {}
+    }
+    let base_power = choice.base_power;
+    // moves with a variable base power (weight based, fixed damage, etc.)
+    if base_power == 0.0 {
+        100.0
+    } else if base_power >= 140.0 {
+        200.0
+    } else if base_power >= 130.0 {
+        195.0
+    } else if base_power >= 120.0 {
+        190.0
+    } else if base_power >= 110.0 {
+        185.0
+    } else if base_power >= 100.0 {
+        180.0
+    } else if base_power >= 90.0 {
+        175.0
+    } else if base_power >= 80.0 {
+        160.0
+    } else if base_power >= 70.0 {
+        140.0
+    } else if base_power >= 60.0 {
+        120.0
+    } else {
+        100.0
+    }
+}
+
+fn modify_choice_for_z_move(choice: &mut Choice, pkmn_using_z_move: bool) {
+    if !pkmn_using_z_move
+        || choice.category == MoveCategory::Switch
+        || choice.move_id == Choices::NONE
+    {
+        return;
+    }
+
+    // status z-moves are the original move with an extra effect applied when it is used
+    if choice.category != MoveCategory::Status {
+        let mut z_move = MOVES
+            .get(&z_move_for_type(&choice.move_type))
+            .unwrap()
+            .clone();
+        z_move.base_power = z_move_base_power(choice);
+        z_move.category = choice.category;
+        z_move.move_index = choice.move_index;
+        *choice = z_move;
+    }
+    choice.z_move = true;
+}
+
+fn z_power_heal(
+    state: &mut State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    incoming_instructions: &mut StateInstructions,
+) {
+    let (active_pkmn, pokemon_index) = state.get_side(side_ref).get_active_with_index(slot_ref);
+    let heal_amount = active_pkmn.maxhp - active_pkmn.hp;
+    if heal_amount > 0 {
+        incoming_instructions
+            .instruction_list
+            .push(Instruction::Heal(HealInstruction {
+                side_ref,
+                pokemon_index,
+                heal_amount,
+            }));
+        active_pkmn.hp += heal_amount;
+    }
+}
+
+fn z_power_reset_lowered_stats(
+    state: &mut State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    incoming_instructions: &mut StateInstructions,
+) {
+    for stat in [
+        PokemonBoostableStat::Attack,
+        PokemonBoostableStat::Defense,
+        PokemonBoostableStat::SpecialAttack,
+        PokemonBoostableStat::SpecialDefense,
+        PokemonBoostableStat::Speed,
+        PokemonBoostableStat::Accuracy,
+        PokemonBoostableStat::Evasion,
+    ] {
+        let boost = state
+            .get_side_immutable(side_ref)
+            .get_boost_from_boost_enum(slot_ref, &stat);
+        if boost < 0 {
+            let instruction = Instruction::Boost(BoostInstruction {
+                side_ref,
+                slot_ref: *slot_ref,
+                stat,
+                amount: -boost,
+            });
+            state.apply_one_instruction(&instruction);
+            incoming_instructions.instruction_list.push(instruction);
+        }
+    }
+}
+
+fn z_power_volatile_status(
+    state: &mut State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    volatile_status: PokemonVolatileStatus,
+    incoming_instructions: &mut StateInstructions,
+) {
+    let slot = state.get_side(side_ref).get_slot(slot_ref);
+    if !slot.volatile_statuses.contains(&volatile_status) {
+        slot.volatile_statuses.insert(volatile_status);
+        incoming_instructions
+            .instruction_list
+            .push(Instruction::ApplyVolatileStatus(
+                ApplyVolatileStatusInstruction {
+                    side_ref,
+                    slot_ref: *slot_ref,
+                    volatile_status,
+                },
+            ));
+    }
+}
+
+// Using a z-move spends the side's z-power. Status z-moves also get an effect on the user
+// before the move runs. Healing the replacement (Memento, Parting Shot) is not implemented
+fn generate_instructions_from_z_power(
+    state: &mut State,
+    choice: &Choice,
+    attacking_side_ref: SideReference,
+    attacking_slot_ref: &SlotReference,
+    incoming_instructions: &mut StateInstructions,
+) {
+    incoming_instructions
+        .instruction_list
+        .push(Instruction::ToggleZMoveUsed(ToggleZMoveUsedInstruction {
+            side_ref: attacking_side_ref,
+        }));
+    state.get_side(attacking_side_ref).z_move_used = true;
+
+    if choice.category != MoveCategory::Status {
+        return;
+    }
+
+    // (attack, defense, special_attack, special_defense, speed, accuracy)
+    let boosts: (i8, i8, i8, i8, i8, i8) = match choice.move_id {
+        Choices::CURSE
+            if state
+                .get_side_immutable(attacking_side_ref)
+                .get_active_immutable(attacking_slot_ref)
+                .has_type(&PokemonType::GHOST) =>
+        {
+            return z_power_heal(
+                state,
+                attacking_side_ref,
+                attacking_slot_ref,
+                incoming_instructions,
+            );
+        }
+        Choices::BULKUP
+        | Choices::CURSE
+        | Choices::HOWL
+        | Choices::LASERFOCUS
+        | Choices::LEER
+        | Choices::MEDITATE
+        | Choices::ODORSLEUTH
+        | Choices::POWERTRICK
+        | Choices::ROTOTILLER
+        | Choices::SCREECH
+        | Choices::SHARPEN
+        | Choices::TAILWHIP
+        | Choices::TAUNT
+        | Choices::TOPSYTURVY
+        | Choices::WILLOWISP
+        | Choices::WORKUP => (1, 0, 0, 0, 0, 0),
+        Choices::MIRRORMOVE => (2, 0, 0, 0, 0, 0),
+        Choices::SPLASH => (3, 0, 0, 0, 0, 0),
+        Choices::AQUARING
+        | Choices::BABYDOLLEYES
+        | Choices::BANEFULBUNKER
+        | Choices::BLOCK
+        | Choices::CHARM
+        | Choices::DEFENDORDER
+        | Choices::FAIRYLOCK
+        | Choices::FEATHERDANCE
+        | Choices::FLOWERSHIELD
+        | Choices::GRASSYTERRAIN
+        | Choices::GROWL
+        | Choices::HARDEN
+        | Choices::MATBLOCK
+        | Choices::NOBLEROAR
+        | Choices::PAINSPLIT
+        | Choices::PLAYNICE
+        | Choices::POISONGAS
+        | Choices::POISONPOWDER
+        | Choices::QUICKGUARD
+        | Choices::REFLECT
+        | Choices::SPIDERWEB
+        | Choices::SPIKES
+        | Choices::SPIKYSHIELD
+        | Choices::STEALTHROCK
+        | Choices::STRENGTHSAP
+        | Choices::TEARFULLOOK
+        | Choices::TICKLE
+        | Choices::TORMENT
+        | Choices::TOXIC
+        | Choices::TOXICSPIKES
+        | Choices::VENOMDRENCH
+        | Choices::WIDEGUARD
+        | Choices::WITHDRAW => (0, 1, 0, 0, 0, 0),
+        Choices::CONFUSERAY
+        | Choices::ELECTRIFY
+        | Choices::EMBARGO
+        | Choices::FAKETEARS
+        | Choices::GEARUP
+        | Choices::GRAVITY
+        | Choices::GROWTH
+        | Choices::INSTRUCT
+        | Choices::IONDELUGE
+        | Choices::METALSOUND
+        | Choices::MINDREADER
+        | Choices::MIRACLEEYE
+        | Choices::NIGHTMARE
+        | Choices::PSYCHICTERRAIN
+        | Choices::REFLECTTYPE
+        | Choices::SIMPLEBEAM
+        | Choices::SOAK
+        | Choices::SWEETKISS
+        | Choices::TEETERDANCE
+        | Choices::TELEKINESIS => (0, 0, 1, 0, 0, 0),
+        Choices::HEALBLOCK | Choices::PSYCHOSHIFT => (0, 0, 2, 0, 0, 0),
+        Choices::CHARGE
+        | Choices::CONFIDE
+        | Choices::COSMICPOWER
+        | Choices::CRAFTYSHIELD
+        | Choices::EERIEIMPULSE
+        | Choices::ENTRAINMENT
+        | Choices::FLATTER
+        | Choices::GLARE
+        | Choices::INGRAIN
+        | Choices::LIGHTSCREEN
+        | Choices::MAGICROOM
+        | Choices::MAGNETICFLUX
+        | Choices::MEANLOOK
+        | Choices::MISTYTERRAIN
+        | Choices::MUDSPORT
+        | Choices::ROAR
+        | Choices::SPOTLIGHT
+        | Choices::STUNSPORE
+        | Choices::THUNDERWAVE
+        | Choices::WATERSPORT
+        | Choices::WHIRLWIND
+        | Choices::WISH
+        | Choices::WONDERROOM => (0, 0, 0, 1, 0, 0),
+        Choices::AROMATICMIST
+        | Choices::CAPTIVATE
+        | Choices::IMPRISON
+        | Choices::MAGICCOAT
+        | Choices::POWDER => (0, 0, 0, 2, 0, 0),
+        Choices::AFTERYOU
+        | Choices::AURORAVEIL
+        | Choices::ELECTRICTERRAIN
+        | Choices::ENCORE
+        | Choices::GASTROACID
+        | Choices::GRASSWHISTLE
+        | Choices::GUARDSPLIT
+        | Choices::GUARDSWAP
+        | Choices::HAIL
+        | Choices::HYPNOSIS
+        | Choices::LOCKON
+        | Choices::LOVELYKISS
+        | Choices::POWERSPLIT
+        | Choices::POWERSWAP
+        | Choices::QUASH
+        | Choices::RAINDANCE
+        | Choices::ROLEPLAY
+        | Choices::SAFEGUARD
+        | Choices::SANDSTORM
+        | Choices::SCARYFACE
+        | Choices::SING
+        | Choices::SKILLSWAP
+        | Choices::SLEEPPOWDER
+        | Choices::SPEEDSWAP
+        | Choices::STICKYWEB
+        | Choices::STRINGSHOT
+        | Choices::SUNNYDAY
+        | Choices::SUPERSONIC
+        | Choices::TOXICTHREAD
+        | Choices::WORRYSEED
+        | Choices::YAWN => (0, 0, 0, 0, 1, 0),
+        Choices::ALLYSWITCH
+        | Choices::BESTOW
+        | Choices::MEFIRST
+        | Choices::RECYCLE
+        | Choices::SNATCH
+        | Choices::SWITCHEROO
+        | Choices::TRICK => (0, 0, 0, 0, 2, 0),
+        Choices::COPYCAT
+        | Choices::DEFENSECURL
+        | Choices::DEFOG
+        | Choices::FOCUSENERGY
+        | Choices::MIMIC
+        | Choices::SWEETSCENT
+        | Choices::TRICKROOM => (0, 0, 0, 0, 0, 1),
+        Choices::CELEBRATE
+        | Choices::CONVERSION
+        | Choices::FORESTSCURSE
+        | Choices::GEOMANCY
+        | Choices::HAPPYHOUR
+        | Choices::HOLDHANDS
+        | Choices::PURIFY
+        | Choices::SKETCH
+        | Choices::TRICKORTREAT => (1, 1, 1, 1, 1, 0),
+        Choices::ACIDARMOR
+        | Choices::AGILITY
+        | Choices::AMNESIA
+        | Choices::ATTRACT
+        | Choices::AUTOTOMIZE
+        | Choices::BARRIER
+        | Choices::BATONPASS
+        | Choices::CALMMIND
+        | Choices::COIL
+        | Choices::COTTONGUARD
+        | Choices::COTTONSPORE
+        | Choices::DARKVOID
+        | Choices::DISABLE
+        | Choices::DOUBLETEAM
+        | Choices::DRAGONDANCE
+        | Choices::ENDURE
+        | Choices::FLORALHEALING
+        | Choices::FOLLOWME
+        | Choices::HEALORDER
+        | Choices::HEALPULSE
+        | Choices::HELPINGHAND
+        | Choices::IRONDEFENSE
+        | Choices::KINESIS
+        | Choices::KINGSSHIELD
+        | Choices::MAGNETRISE
+        | Choices::MILKDRINK
+        | Choices::MINIMIZE
+        | Choices::MOONLIGHT
+        | Choices::MORNINGSUN
+        | Choices::NASTYPLOT
+        | Choices::PERISHSONG
+        | Choices::PROTECT
+        | Choices::QUIVERDANCE
+        | Choices::RAGEPOWDER
+        | Choices::RECOVER
+        | Choices::REST
+        | Choices::ROCKPOLISH
+        | Choices::ROOST
+        | Choices::SHELLSMASH
+        | Choices::SHIFTGEAR
+        | Choices::SHOREUP
+        | Choices::SLACKOFF
+        | Choices::SMOKESCREEN
+        | Choices::SOFTBOILED
+        | Choices::SPORE
+        | Choices::STOCKPILE
+        | Choices::SUBSTITUTE
+        | Choices::SWAGGER
+        | Choices::SWALLOW
+        | Choices::SWORDSDANCE
+        | Choices::SYNTHESIS
+        | Choices::TAILGLOW => {
+            return z_power_reset_lowered_stats(
+                state,
+                attacking_side_ref,
+                attacking_slot_ref,
+                incoming_instructions,
+            );
+        }
+        Choices::AROMATHERAPY
+        | Choices::BELLYDRUM
+        | Choices::CONVERSION2
+        | Choices::HAZE
+        | Choices::HEALBELL
+        | Choices::MIST
+        | Choices::PSYCHUP
+        | Choices::REFRESH
+        | Choices::SPITE
+        | Choices::TELEPORT
+        | Choices::TRANSFORM => {
+            return z_power_heal(
+                state,
+                attacking_side_ref,
+                attacking_slot_ref,
+                incoming_instructions,
+            );
+        }
+        Choices::ACUPRESSURE
+        | Choices::FORESIGHT
+        | Choices::HEARTSWAP
+        | Choices::SLEEPTALK
+        | Choices::TAILWIND => {
+            return z_power_volatile_status(
+                state,
+                attacking_side_ref,
+                attacking_slot_ref,
+                PokemonVolatileStatus::FOCUSENERGY,
+                incoming_instructions,
+            );
+        }
+        Choices::DESTINYBOND | Choices::GRUDGE => {
+            return z_power_volatile_status(
+                state,
+                attacking_side_ref,
+                attacking_slot_ref,
+                PokemonVolatileStatus::FOLLOWME,
+                incoming_instructions,
+            );
+        }
+        _ => return,
+    };
+
+    let (attack, defense, special_attack, special_defense, speed, accuracy) = boosts;
+    for (stat, amount) in [
+        (PokemonBoostableStat::Attack, attack),
+        (PokemonBoostableStat::Defense, defense),
+        (PokemonBoostableStat::SpecialAttack, special_attack),
+        (PokemonBoostableStat::SpecialDefense, special_defense),
+        (PokemonBoostableStat::Speed, speed),
+        (PokemonBoostableStat::Accuracy, accuracy),
+    ] {
+        if amount != 0 {
+            let (attacking_side, other_side) = state.get_both_sides(attacking_side_ref);
+            apply_boost_instructions(
+                attacking_side,
+                other_side,
+                &stat,
+                &amount,
+                attacking_side_ref,
+                attacking_side_ref,
+                attacking_slot_ref,
+                *attacking_slot_ref,
+                incoming_instructions,
+            );
+        }
+    }
+}
+
+#[cfg(feature = "gen5")]
+fn reset_sleep_turns_on_switch_out(
+    state: &mut State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    incoming_instructions: &mut StateInstructions,
+) {
+    // in gen5 the sleep counter is reset when a sleeping pokemon switches out
+    // rest is unaffected because it always lasts exactly 2 turns
+    let (pkmn, pokemon_index) = state.get_side(side_ref).get_active_with_index(slot_ref);
+    if pkmn.status == PokemonStatus::SLEEP && pkmn.rest_turns == 0 && pkmn.sleep_turns > 0 {
+        incoming_instructions
+            .instruction_list
+            .push(Instruction::SetSleepTurns(SetSleepTurnsInstruction {
+                side_ref,
+                pokemon_index,
+                new_turns: 0,
+                previous_turns: pkmn.sleep_turns,
+            }));
+        pkmn.sleep_turns = 0;
+    }
+}
+
 fn generate_instructions_from_switch(
     state: &mut State,
     slot_ref: &SlotReference,
@@ -327,6 +998,9 @@ fn generate_instructions_from_switch(
 
     ability_on_switch_out(state, switching_side_ref, slot_ref, incoming_instructions);
 
+    #[cfg(feature = "gen5")]
+    reset_sleep_turns_on_switch_out(state, switching_side_ref, slot_ref, incoming_instructions);
+
     let switch_instruction = Instruction::Switch(SwitchInstruction {
         side_ref: switching_side_ref,
         slot_ref: *slot_ref,
@@ -1415,12 +2089,12 @@ fn get_instructions_from_pivot(
                 continue;
             }
 
-            let saved_move = if let MoveChoice::MoveMega(slot_ref, side_ref, pokemon_move_index) =
-                rtm.move_choice
-            {
-                MoveChoice::Move(slot_ref, side_ref, pokemon_move_index)
-            } else {
-                rtm.move_choice
+            let saved_move = match rtm.move_choice {
+                MoveChoice::MoveMega(slot_ref, side_ref, pokemon_move_index)
+                | MoveChoice::MoveDynamax(slot_ref, side_ref, pokemon_move_index) => {
+                    MoveChoice::Move(slot_ref, side_ref, pokemon_move_index)
+                }
+                _ => rtm.move_choice,
             };
 
             let slot = state.get_side(rtm.side_ref).get_slot(&rtm.slot_ref);
@@ -2084,7 +2758,7 @@ fn before_move(
     }
 
     // modify choice if defender has protect active
-    if (defending_slot
+    let defender_is_protected = defending_slot
         .volatile_statuses
         .contains(&PokemonVolatileStatus::PROTECT)
         || defending_slot
@@ -2098,9 +2772,28 @@ fn before_move(
             .contains(&PokemonVolatileStatus::BURNINGBULWARK)
         || defending_slot
             .volatile_statuses
-            .contains(&PokemonVolatileStatus::SILKTRAP))
+            .contains(&PokemonVolatileStatus::SILKTRAP)
+        || defending_slot
+            .volatile_statuses
+            .contains(&PokemonVolatileStatus::MAXGUARD);
+    if defender_is_protected
+        && choice.flags.protect
+        && choice.z_move
+        && choice.category != MoveCategory::Status
+    {
+        // damaging z-moves break through protection for a quarter of their damage
+        choice.base_power *= 0.25;
+    } else if defender_is_protected
         && choice.flags.protect
+        && choice.max_move
+        && choice.category != MoveCategory::Status
+        && !defending_slot
+            .volatile_statuses
+            .contains(&PokemonVolatileStatus::MAXGUARD)
     {
+        // so do damaging max moves, unless the target is behind max guard
+        choice.base_power *= 0.25;
+    } else if defender_is_protected && choice.flags.protect {
         choice.remove_effects_for_protect();
         if choice.crash.is_some() {
             choice.accuracy = 0.0;
@@ -3032,6 +3725,16 @@ fn run_move(
         return;
     }
 
+    if final_run_move && choice.z_move {
+        generate_instructions_from_z_power(
+            state,
+            choice,
+            attacking_side,
+            &attacking_slot,
+            &mut incoming_instructions,
+        );
+    }
+
     if move_has_no_effect(state, &choice, target_side, &target_slot) {
         state.reverse_instructions(&incoming_instructions.instruction_list);
         final_instructions.push((incoming_instructions, remaining_to_move));
@@ -3339,7 +4042,7 @@ pub fn get_effective_speed(
 
     if active_pkmn.status == PokemonStatus::PARALYZE && active_pkmn.ability != Abilities::QUICKFEET
     {
-        boosted_speed *= 0.50;
+        boosted_speed *= PARALYSIS_SPEED_MULTIPLIER;
     }
 
     boosted_speed as i16
@@ -3913,7 +4616,7 @@ fn add_end_of_turn_instructions(
 
             match active_pkmn.status {
                 PokemonStatus::BURN => {
-                    let mut damage_factor = 0.0625;
+                    let mut damage_factor = BURN_DAMAGE_FACTOR;
 
                     if active_pkmn.ability == Abilities::HEATPROOF {
                         damage_factor /= 2.0;
@@ -4429,6 +5132,7 @@ fn add_end_of_turn_instructions(
                 PokemonVolatileStatus::SPIKYSHIELD,
                 PokemonVolatileStatus::SILKTRAP,
                 PokemonVolatileStatus::ENDURE,
+                PokemonVolatileStatus::MAXGUARD,
             ];
 
             let slot = side.get_slot(slot_ref);
@@ -4475,6 +5179,41 @@ fn add_end_of_turn_instructions(
                 );
                 slot.volatile_status_durations.protect -= slot.volatile_status_durations.protect;
             }
+
+            if slot
+                .volatile_statuses
+                .contains(&PokemonVolatileStatus::DYNAMAX)
+            {
+                incoming_instructions.instruction_list.push(
+                    Instruction::ChangeVolatileStatusDuration(
+                        ChangeVolatileStatusDurationInstruction {
+                            side_ref,
+                            slot_ref: *slot_ref,
+                            amount: -1,
+                            volatile_status: PokemonVolatileStatus::DYNAMAX,
+                        },
+                    ),
+                );
+                slot.volatile_status_durations.dynamax -= 1;
+                if slot.volatile_status_durations.dynamax == 0 {
+                    incoming_instructions
+                        .instruction_list
+                        .push(Instruction::RemoveVolatileStatus(
+                            RemoveVolatileStatusInstruction {
+                                side_ref,
+                                slot_ref: *slot_ref,
+                                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                            },
+                        ));
+                    slot.volatile_statuses
+                        .remove(&PokemonVolatileStatus::DYNAMAX);
+                    side.revert_dynamax_hp(
+                        side_ref,
+                        slot_ref,
+                        &mut incoming_instructions.instruction_list,
+                    );
+                }
+            }
         }
     } // end volatile statuses
 }
@@ -4882,6 +5621,13 @@ fn mega_evolve(
         ));
     active_pkmn.mega_evolved = true;
 
+    instructions
+        .instruction_list
+        .push(Instruction::ToggleMegaUsed(ToggleMegaUsedInstruction {
+            side_ref,
+        }));
+    state.get_side(side_ref).mega_used = true;
+
     // ability on switch in
     ability_on_switch_in(state, side_ref, &slot_ref, instructions);
 }
@@ -4893,6 +5639,29 @@ pub fn generate_instructions_from_move_pair(
     side_two_a_move: &MoveChoice,
     side_two_b_move: &MoveChoice,
     branch_on_damage: bool,
+) -> Vec<StateInstructions> {
+    // generating changes some fields directly rather than through instructions, so the hash
+    // drifts even though every change is undone before returning
+    let hash = state.hash;
+    let instructions = generate_move_pair_instructions(
+        state,
+        side_one_a_move,
+        side_one_b_move,
+        side_two_a_move,
+        side_two_b_move,
+        branch_on_damage,
+    );
+    state.hash = hash;
+    instructions
+}
+
+fn generate_move_pair_instructions(
+    state: &mut State,
+    side_one_a_move: &MoveChoice,
+    side_one_b_move: &MoveChoice,
+    side_two_a_move: &MoveChoice,
+    side_two_b_move: &MoveChoice,
+    branch_on_damage: bool,
 ) -> Vec<StateInstructions> {
     if state.team_preview {
         let (s1a_lead, s1a_faint) = match side_one_a_move {
@@ -4948,6 +5717,8 @@ pub fn generate_instructions_from_move_pair(
     let mut s1_b_tera = false;
     let mut s1_b_replacing_fainted_pkmn = false;
     let mut s1_mega_slot = None;
+    let mut s1_dynamax_slot = None;
+    let mut s1_z_slot = None;
     match side_one_a_move {
         MoveChoice::Switch(switch_id) => {
             if state.sides[0].pokemon[state.sides[0].slot_a.active_index].hp == 0 {
@@ -4983,6 +5754,24 @@ pub fn generate_instructions_from_move_pair(
             side_one_a_target_slot = *target_slot;
             s1_mega_slot = Some(SlotReference::SlotA);
         }
+        MoveChoice::MoveDynamax(target_slot, target_side, move_index) => {
+            side_one_a_choice = state.sides[0].get_active(&SlotReference::SlotA).moves[move_index]
+                .choice
+                .clone();
+            side_one_a_choice.move_index = *move_index;
+            side_one_a_target_side = *target_side;
+            side_one_a_target_slot = *target_slot;
+            s1_dynamax_slot = Some(SlotReference::SlotA);
+        }
+        MoveChoice::MoveZ(target_slot, target_side, move_index) => {
+            side_one_a_choice = state.sides[0].get_active(&SlotReference::SlotA).moves[move_index]
+                .choice
+                .clone();
+            side_one_a_choice.move_index = *move_index;
+            side_one_a_target_side = *target_side;
+            side_one_a_target_slot = *target_slot;
+            s1_z_slot = Some(SlotReference::SlotA);
+        }
         MoveChoice::TeamPreview(_, _) => {
             panic!("MoveChoice::TeamPreview should not be used unless state.team_preview=true")
         }
@@ -5025,6 +5814,24 @@ pub fn generate_instructions_from_move_pair(
             side_one_b_target_slot = *target_slot;
             s1_mega_slot = Some(SlotReference::SlotB);
         }
+        MoveChoice::MoveDynamax(target_slot, target_side, move_index) => {
+            side_one_b_choice = state.sides[0].get_active(&SlotReference::SlotB).moves[move_index]
+                .choice
+                .clone();
+            side_one_b_choice.move_index = *move_index;
+            side_one_b_target_side = *target_side;
+            side_one_b_target_slot = *target_slot;
+            s1_dynamax_slot = Some(SlotReference::SlotB);
+        }
+        MoveChoice::MoveZ(target_slot, target_side, move_index) => {
+            side_one_b_choice = state.sides[0].get_active(&SlotReference::SlotB).moves[move_index]
+                .choice
+                .clone();
+            side_one_b_choice.move_index = *move_index;
+            side_one_b_target_side = *target_side;
+            side_one_b_target_slot = *target_slot;
+            s1_z_slot = Some(SlotReference::SlotB);
+        }
         MoveChoice::TeamPreview(_, _) => {
             panic!("MoveChoice::TeamPreview should not be used unless state.team_preview=true")
         }
@@ -5040,6 +5847,8 @@ pub fn generate_instructions_from_move_pair(
     let mut s2_b_tera = false;
     let mut s2_b_replacing_fainted_pkmn = false;
     let mut s2_mega_slot = None;
+    let mut s2_dynamax_slot = None;
+    let mut s2_z_slot = None;
     match side_two_a_move {
         MoveChoice::Switch(switch_id) => {
             if state.sides[1].pokemon[state.sides[1].slot_a.active_index].hp == 0 {
@@ -5075,6 +5884,24 @@ pub fn generate_instructions_from_move_pair(
             side_two_a_target_slot = *target_slot;
             s2_mega_slot = Some(SlotReference::SlotA);
         }
+        MoveChoice::MoveDynamax(target_slot, target_side, move_index) => {
+            side_two_a_choice = state.sides[1].get_active(&SlotReference::SlotA).moves[move_index]
+                .choice
+                .clone();
+            side_two_a_choice.move_index = *move_index;
+            side_two_a_target_side = *target_side;
+            side_two_a_target_slot = *target_slot;
+            s2_dynamax_slot = Some(SlotReference::SlotA);
+        }
+        MoveChoice::MoveZ(target_slot, target_side, move_index) => {
+            side_two_a_choice = state.sides[1].get_active(&SlotReference::SlotA).moves[move_index]
+                .choice
+                .clone();
+            side_two_a_choice.move_index = *move_index;
+            side_two_a_target_side = *target_side;
+            side_two_a_target_slot = *target_slot;
+            s2_z_slot = Some(SlotReference::SlotA);
+        }
         MoveChoice::TeamPreview(_, _) => {
             panic!("MoveChoice::TeamPreview should not be used unless state.team_preview=true")
         }
@@ -5117,6 +5944,24 @@ pub fn generate_instructions_from_move_pair(
             side_two_b_target_slot = *target_slot;
             s2_mega_slot = Some(SlotReference::SlotB);
         }
+        MoveChoice::MoveDynamax(target_slot, target_side, move_index) => {
+            side_two_b_choice = state.sides[1].get_active(&SlotReference::SlotB).moves[move_index]
+                .choice
+                .clone();
+            side_two_b_choice.move_index = *move_index;
+            side_two_b_target_side = *target_side;
+            side_two_b_target_slot = *target_slot;
+            s2_dynamax_slot = Some(SlotReference::SlotB);
+        }
+        MoveChoice::MoveZ(target_slot, target_side, move_index) => {
+            side_two_b_choice = state.sides[1].get_active(&SlotReference::SlotB).moves[move_index]
+                .choice
+                .clone();
+            side_two_b_choice.move_index = *move_index;
+            side_two_b_target_side = *target_side;
+            side_two_b_target_slot = *target_slot;
+            s2_z_slot = Some(SlotReference::SlotB);
+        }
         MoveChoice::TeamPreview(_, _) => {
             panic!("MoveChoice::TeamPreview should not be used unless state.team_preview=true")
         }
@@ -5224,6 +6069,22 @@ pub fn generate_instructions_from_move_pair(
         s2_mega_slot,
         &mut incoming_instructions,
     );
+    if let Some(slot_ref) = s1_dynamax_slot {
+        generate_instructions_from_dynamax(
+            state,
+            SideReference::SideOne,
+            &slot_ref,
+            &mut incoming_instructions,
+        );
+    }
+    if let Some(slot_ref) = s2_dynamax_slot {
+        generate_instructions_from_dynamax(
+            state,
+            SideReference::SideTwo,
+            &slot_ref,
+            &mut incoming_instructions,
+        );
+    }
     state.reverse_instructions(&incoming_instructions.instruction_list);
 
     modify_choice_before_move(
@@ -5255,6 +6116,52 @@ pub fn generate_instructions_from_move_pair(
         s2_b_tera,
     );
 
+    modify_choice_for_dynamax(
+        state,
+        SideReference::SideOne,
+        &SlotReference::SlotA,
+        &mut side_one_a_choice,
+        s1_dynamax_slot == Some(SlotReference::SlotA),
+    );
+    modify_choice_for_dynamax(
+        state,
+        SideReference::SideOne,
+        &SlotReference::SlotB,
+        &mut side_one_b_choice,
+        s1_dynamax_slot == Some(SlotReference::SlotB),
+    );
+    modify_choice_for_dynamax(
+        state,
+        SideReference::SideTwo,
+        &SlotReference::SlotA,
+        &mut side_two_a_choice,
+        s2_dynamax_slot == Some(SlotReference::SlotA),
+    );
+    modify_choice_for_dynamax(
+        state,
+        SideReference::SideTwo,
+        &SlotReference::SlotB,
+        &mut side_two_b_choice,
+        s2_dynamax_slot == Some(SlotReference::SlotB),
+    );
+
+    modify_choice_for_z_move(
+        &mut side_one_a_choice,
+        s1_z_slot == Some(SlotReference::SlotA),
+    );
+    modify_choice_for_z_move(
+        &mut side_one_b_choice,
+        s1_z_slot == Some(SlotReference::SlotB),
+    );
+    modify_choice_for_z_move(
+        &mut side_two_a_choice,
+        s2_z_slot == Some(SlotReference::SlotA),
+    );
+    modify_choice_for_z_move(
+        &mut side_two_b_choice,
+        s2_z_slot == Some(SlotReference::SlotB),
+    );
+
     let mut need_to_move = Vec::with_capacity(4);
     if !s1_a_switch {
         need_to_move.push(RemainingToMove {
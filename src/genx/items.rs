@@ -255,10 +255,54 @@ define_enum_with_from_str! {
         DRAMPANITE,
         CHIMECHITE,
         CHANDELURITE,
+
+        // z-crystals
+        NORMALIUMZ,
+        FIRIUMZ,
+        WATERIUMZ,
+        ELECTRIUMZ,
+        GRASSIUMZ,
+        ICIUMZ,
+        FIGHTINIUMZ,
+        POISONIUMZ,
+        GROUNDIUMZ,
+        FLYINIUMZ,
+        PSYCHIUMZ,
+        BUGINIUMZ,
+        ROCKIUMZ,
+        GHOSTIUMZ,
+        DRAGONIUMZ,
+        DARKINIUMZ,
+        STEELIUMZ,
+        FAIRIUMZ,
     },
     default = UNKNOWNITEM
 }
 
+pub fn z_crystal_type(item: &Items) -> Option<PokemonType> {
+    match item {
+        Items::NORMALIUMZ => Some(PokemonType::NORMAL),
+        Items::FIRIUMZ => Some(PokemonType::FIRE),
+        Items::WATERIUMZ => Some(PokemonType::WATER),
+        Items::ELECTRIUMZ => Some(PokemonType::ELECTRIC),
+        Items::GRASSIUMZ => Some(PokemonType::GRASS),
+        Items::ICIUMZ => Some(PokemonType::ICE),
+        Items::FIGHTINIUMZ => Some(PokemonType::FIGHTING),
+        Items::POISONIUMZ => Some(PokemonType::POISON),
+        Items::GROUNDIUMZ => Some(PokemonType::GROUND),
+        Items::FLYINIUMZ => Some(PokemonType::FLYING),
+        Items::PSYCHIUMZ => Some(PokemonType::PSYCHIC),
+        Items::BUGINIUMZ => Some(PokemonType::BUG),
+        Items::ROCKIUMZ => Some(PokemonType::ROCK),
+        Items::GHOSTIUMZ => Some(PokemonType::GHOST),
+        Items::DRAGONIUMZ => Some(PokemonType::DRAGON),
+        Items::DARKINIUMZ => Some(PokemonType::DARK),
+        Items::STEELIUMZ => Some(PokemonType::STEEL),
+        Items::FAIRIUMZ => Some(PokemonType::FAIRY),
+        _ => None,
+    }
+}
+
 pub fn get_choice_move_disable_instructions(
     pkmn: &Pokemon,
     pkmn_index: PokemonIndex,
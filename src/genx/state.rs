@@ -1,11 +1,11 @@
 use super::abilities::Abilities;
-use super::items::Items;
+use super::items::{z_crystal_type, Items};
 use crate::choices::{Choice, Choices, MoveCategory, MoveChoiceTarget, MoveTarget};
 use crate::define_enum_with_from_str;
 use crate::instruction::{
     ChangeSideConditionInstruction, ChangeStatInstruction, ChangeType,
-    ChangeVolatileStatusDurationInstruction, Instruction, RemoveVolatileStatusInstruction,
-    StateInstructions,
+    ChangeVolatileStatusDurationInstruction, HealInstruction, Instruction,
+    RemoveVolatileStatusInstruction, StateInstructions,
 };
 use crate::pokemon::PokemonName;
 use crate::state::{
@@ -100,6 +100,40 @@ impl MoveOptions {
                     continue;
                 }
 
+                // Check if both slots are trying to dynamax
+                if matches!(slot_a_choice, MoveChoice::MoveDynamax(_, _, _))
+                    && matches!(slot_b_choice, MoveChoice::MoveDynamax(_, _, _))
+                {
+                    // Skip this combination - both Pokémon cannot dynamax together
+                    continue;
+                }
+
+                // Check if both slots are trying to use a z-move
+                if matches!(slot_a_choice, MoveChoice::MoveZ(_, _, _))
+                    && matches!(slot_b_choice, MoveChoice::MoveZ(_, _, _))
+                {
+                    // Skip this combination - both Pokémon cannot use a z-move together
+                    continue;
+                }
+
+                // Check if one slot is trying to dynamax while the other uses another gimmick
+                let other_gimmick = |choice: &MoveChoice| {
+                    matches!(
+                        choice,
+                        MoveChoice::MoveTera(_, _, _)
+                            | MoveChoice::MoveMega(_, _, _)
+                            | MoveChoice::MoveZ(_, _, _)
+                    )
+                };
+                if (matches!(slot_a_choice, MoveChoice::MoveDynamax(_, _, _))
+                    && other_gimmick(slot_b_choice))
+                    || (matches!(slot_b_choice, MoveChoice::MoveDynamax(_, _, _))
+                        && other_gimmick(slot_a_choice))
+                {
+                    // Skip this combination - dynamax cannot be used alongside another gimmick
+                    continue;
+                }
+
                 combined_options.push((*slot_a_choice, *slot_b_choice));
             }
         }
@@ -125,6 +159,8 @@ pub enum MoveChoice {
     MoveTera(SlotReference, SideReference, PokemonMoveIndex),
     Move(SlotReference, SideReference, PokemonMoveIndex),
     MoveMega(SlotReference, SideReference, PokemonMoveIndex),
+    MoveDynamax(SlotReference, SideReference, PokemonMoveIndex),
+    MoveZ(SlotReference, SideReference, PokemonMoveIndex),
     Switch(PokemonIndex),
     TeamPreview(PokemonIndex, PokemonIndex), // represents a choice to lead and a choice to faint
     None,
@@ -152,6 +188,14 @@ impl MoveChoice {
             MoveChoice::TeamPreview(index1, index2) => 54 + (*index1 as u8) * 6 + (*index2 as u8),
             // 90
             MoveChoice::None => 90,
+            // 4 moves, 2 slots, 2 sides = 16 => 91-106
+            MoveChoice::MoveDynamax(slot, side, mv) => {
+                91 + (*slot as u8) * 8 + (*side as u8) * 4 + (*mv as u8)
+            }
+            // 4 moves, 2 slots, 2 sides = 16 => 107-122
+            MoveChoice::MoveZ(slot, side, mv) => {
+                107 + (*slot as u8) * 8 + (*side as u8) * 4 + (*mv as u8)
+            }
         }
     }
 
@@ -173,6 +217,22 @@ impl MoveChoice {
                     index.serialize()
                 )
             }
+            MoveChoice::MoveDynamax(target_slot, target_side, index) => {
+                format!(
+                    "{},{},{},dynamax",
+                    target_side.to_string(),
+                    target_slot.to_string(),
+                    index.serialize()
+                )
+            }
+            MoveChoice::MoveZ(target_slot, target_side, index) => {
+                format!(
+                    "{},{},{},z",
+                    target_side.to_string(),
+                    target_slot.to_string(),
+                    index.serialize()
+                )
+            }
             MoveChoice::Move(target_slot, target_side, index) => {
                 format!(
                     "{},{},{},false",
@@ -196,10 +256,16 @@ impl MoveChoice {
             let index: PokemonMoveIndex = PokemonMoveIndex::deserialize(parts[2]);
             let is_tera: bool = parts[3] == "tera";
             let is_mega: bool = parts[3] == "mega";
+            let is_dynamax: bool = parts[3] == "dynamax";
+            let is_z: bool = parts[3] == "z";
             if is_tera {
                 MoveChoice::MoveTera(target_slot, target_side, index)
             } else if is_mega {
                 MoveChoice::MoveMega(target_slot, target_side, index)
+            } else if is_dynamax {
+                MoveChoice::MoveDynamax(target_slot, target_side, index)
+            } else if is_z {
+                MoveChoice::MoveZ(target_slot, target_side, index)
             } else {
                 MoveChoice::Move(target_slot, target_side, index)
             }
@@ -247,6 +313,40 @@ impl MoveChoice {
                     format!("{},mega", mv.id).to_lowercase()
                 }
             }
+            MoveChoice::MoveDynamax(target_slot, target_side, index) => {
+                let mv = &side.get_active_immutable(attacking_slot_ref).moves[&index];
+                if mv.choice.move_choice_target == MoveChoiceTarget::Ally
+                    || (mv.choice.move_choice_target == MoveChoiceTarget::Normal
+                        && mv.choice.target == MoveTarget::Target)
+                {
+                    format!(
+                        "{},{},{},dynamax",
+                        mv.id,
+                        target_side.to_string(),
+                        target_slot.to_string()
+                    )
+                    .to_lowercase()
+                } else {
+                    format!("{},dynamax", mv.id).to_lowercase()
+                }
+            }
+            MoveChoice::MoveZ(target_slot, target_side, index) => {
+                let mv = &side.get_active_immutable(attacking_slot_ref).moves[&index];
+                if mv.choice.move_choice_target == MoveChoiceTarget::Ally
+                    || (mv.choice.move_choice_target == MoveChoiceTarget::Normal
+                        && mv.choice.target == MoveTarget::Target)
+                {
+                    format!(
+                        "{},{},{},z",
+                        mv.id,
+                        target_side.to_string(),
+                        target_slot.to_string()
+                    )
+                    .to_lowercase()
+                } else {
+                    format!("{},z", mv.id).to_lowercase()
+                }
+            }
             MoveChoice::Move(target_slot, target_side, index) => {
                 let mv = &side.get_active_immutable(attacking_slot_ref).moves[&index];
                 if mv.choice.move_choice_target == MoveChoiceTarget::Ally
@@ -312,6 +412,18 @@ impl MoveChoice {
         } else {
             false
         };
+        let is_dynamax = if s.ends_with(",dynamax") {
+            s = s.trim_end_matches(",dynamax").to_string();
+            true
+        } else {
+            false
+        };
+        let is_z = if s.ends_with(",z") {
+            s = s.trim_end_matches(",z").to_string();
+            true
+        } else {
+            false
+        };
         let parts: Vec<&str> = s.split(',').collect();
         let move_name_part;
         let side_ref_str;
@@ -368,6 +480,18 @@ impl MoveChoice {
                         target_side_ref,
                         move_iter.pokemon_move_index,
                     ))
+                } else if is_dynamax {
+                    Some(MoveChoice::MoveDynamax(
+                        target_slot_ref,
+                        target_side_ref,
+                        move_iter.pokemon_move_index,
+                    ))
+                } else if is_z {
+                    Some(MoveChoice::MoveZ(
+                        target_slot_ref,
+                        target_side_ref,
+                        move_iter.pokemon_move_index,
+                    ))
                 } else {
                     Some(MoveChoice::Move(
                         target_slot_ref,
@@ -404,6 +528,7 @@ define_enum_with_from_str! {
         DIG,
         DISABLE,
         DIVE,
+        DYNAMAX,
         ELECTRIFY,
         ELECTROSHOT,
         EMBARGO,
@@ -572,11 +697,108 @@ define_enum_with_from_str! {
     }
 }
 
+impl PokemonNature {
+    pub fn apply_to_stats(
+        &self,
+        stats: (i16, i16, i16, i16, i16, i16),
+    ) -> (i16, i16, i16, i16, i16, i16) {
+        let mut result = stats;
+        match self {
+            PokemonNature::LONELY => {
+                result.1 = result.1 * 11 / 10; // +Atk
+                result.2 = result.2 * 9 / 10; // -Def
+            }
+            PokemonNature::ADAMANT => {
+                result.1 = result.1 * 11 / 10; // +Atk
+                result.3 = result.3 * 9 / 10; // -SpA
+            }
+            PokemonNature::NAUGHTY => {
+                result.1 = result.1 * 11 / 10; // +Atk
+                result.4 = result.4 * 9 / 10; // -SpD
+            }
+            PokemonNature::BRAVE => {
+                result.1 = result.1 * 11 / 10; // +Atk
+                result.5 = result.5 * 9 / 10; // -Spe
+            }
+            PokemonNature::BOLD => {
+                result.2 = result.2 * 11 / 10; // +Def
+                result.1 = result.1 * 9 / 10; // -Atk
+            }
+            PokemonNature::IMPISH => {
+                result.2 = result.2 * 11 / 10; // +Def
+                result.3 = result.3 * 9 / 10; // -SpA
+            }
+            PokemonNature::LAX => {
+                result.2 = result.2 * 11 / 10; // +Def
+                result.4 = result.4 * 9 / 10; // -SpD
+            }
+            PokemonNature::RELAXED => {
+                result.2 = result.2 * 11 / 10; // +Def
+                result.5 = result.5 * 9 / 10; // -Spe
+            }
+            PokemonNature::MODEST => {
+                result.3 = result.3 * 11 / 10; // +SpA
+                result.1 = result.1 * 9 / 10; // -Atk
+            }
+            PokemonNature::MILD => {
+                result.3 = result.3 * 11 / 10; // +SpA
+                result.2 = result.2 * 9 / 10; // -Def
+            }
+            PokemonNature::RASH => {
+                result.3 = result.3 * 11 / 10; // +SpA
+                result.4 = result.4 * 9 / 10; // -SpD
+            }
+            PokemonNature::QUIET => {
+                result.3 = result.3 * 11 / 10; // +SpA
+                result.5 = result.5 * 9 / 10; // -Spe
+            }
+            PokemonNature::CALM => {
+                result.4 = result.4 * 11 / 10; // +SpD
+                result.1 = result.1 * 9 / 10; // -Atk
+            }
+            PokemonNature::GENTLE => {
+                result.4 = result.4 * 11 / 10; // +SpD
+                result.2 = result.2 * 9 / 10; // -Def
+            }
+            PokemonNature::CAREFUL => {
+                result.4 = result.4 * 11 / 10; // +SpD
+                result.3 = result.3 * 9 / 10; // -SpA
+            }
+            PokemonNature::SASSY => {
+                result.4 = result.4 * 11 / 10; // +SpD
+                result.5 = result.5 * 9 / 10; // -Spe
+            }
+            PokemonNature::TIMID => {
+                result.5 = result.5 * 11 / 10; // +Spe
+                result.1 = result.1 * 9 / 10; // -Atk
+            }
+            PokemonNature::HASTY => {
+                result.5 = result.5 * 11 / 10; // +Spe
+                result.2 = result.2 * 9 / 10; // -Def
+            }
+            PokemonNature::JOLLY => {
+                result.5 = result.5 * 11 / 10; // +Spe
+                result.3 = result.3 * 9 / 10; // -SpA
+            }
+            PokemonNature::NAIVE => {
+                result.5 = result.5 * 11 / 10; // +Spe
+                result.4 = result.4 * 9 / 10; // -SpD
+            }
+            // Neutral natures: no change
+            PokemonNature::HARDY
+            | PokemonNature::DOCILE
+            | PokemonNature::SERIOUS
+            | PokemonNature::BASHFUL
+            | PokemonNature::QUIRKY => {}
+        }
+        result
+    }
+}
+
 impl Pokemon {
     pub fn can_mega_evolve(&self) -> bool {
-        // this assumes that if you have the correct mega stone, you can always mega evolve
-        // even if another pkmn on the team already mega evolved
-        // come back to this and fix the logic because some teams have multiple megas
+        // only checks that this pkmn has the correct mega stone
+        // `Side::can_use_mega` enforces one mega evolution per side
         if let Some(_mega_evolve_data) = self.id.mega_evolve_target(self.item) {
             true
         } else {
@@ -688,104 +910,21 @@ impl Pokemon {
         }
     }
     pub fn calculate_stats_from_base_stats(&self) -> (i16, i16, i16, i16, i16, i16) {
+        // evs are stat points, each worth 2 points in the classic (2*base + iv + ev/4) formula
+        fn calc_stat(base: i16, iv: u8, sp: u8, level: i16) -> i16 {
+            ((2 * base as i32 + iv as i32 + 2 * sp as i32) * level as i32 / 100 + 5) as i16
+        }
         let base_stats = self.id.base_stats();
-        let mut result = (
-            base_stats.0 + self.evs.0 as i16 + 75,
-            base_stats.1 + self.evs.1 as i16 + 20,
-            base_stats.2 + self.evs.2 as i16 + 20,
-            base_stats.3 + self.evs.3 as i16 + 20,
-            base_stats.4 + self.evs.4 as i16 + 20,
-            base_stats.5 + self.evs.5 as i16 + 20,
+        let level = self.level as i16;
+        let result = (
+            calc_stat(base_stats.0, self.ivs.0, self.evs.0, level) + level + 5,
+            calc_stat(base_stats.1, self.ivs.1, self.evs.1, level),
+            calc_stat(base_stats.2, self.ivs.2, self.evs.2, level),
+            calc_stat(base_stats.3, self.ivs.3, self.evs.3, level),
+            calc_stat(base_stats.4, self.ivs.4, self.evs.4, level),
+            calc_stat(base_stats.5, self.ivs.5, self.evs.5, level),
         );
-        match self.nature {
-            PokemonNature::LONELY => {
-                result.1 = result.1 * 11 / 10; // +Atk
-                result.2 = result.2 * 9 / 10; // -Def
-            }
-            PokemonNature::ADAMANT => {
-                result.1 = result.1 * 11 / 10; // +Atk
-                result.3 = result.3 * 9 / 10; // -SpA
-            }
-            PokemonNature::NAUGHTY => {
-                result.1 = result.1 * 11 / 10; // +Atk
-                result.4 = result.4 * 9 / 10; // -SpD
-            }
-            PokemonNature::BRAVE => {
-                result.1 = result.1 * 11 / 10; // +Atk
-                result.5 = result.5 * 9 / 10; // -Spe
-            }
-            PokemonNature::BOLD => {
-                result.2 = result.2 * 11 / 10; // +Def
-                result.1 = result.1 * 9 / 10; // -Atk
-            }
-            PokemonNature::IMPISH => {
-                result.2 = result.2 * 11 / 10; // +Def
-                result.3 = result.3 * 9 / 10; // -SpA
-            }
-            PokemonNature::LAX => {
-                result.2 = result.2 * 11 / 10; // +Def
-                result.4 = result.4 * 9 / 10; // -SpD
-            }
-            PokemonNature::RELAXED => {
-                result.2 = result.2 * 11 / 10; // +Def
-                result.5 = result.5 * 9 / 10; // -Spe
-            }
-            PokemonNature::MODEST => {
-                result.3 = result.3 * 11 / 10; // +SpA
-                result.1 = result.1 * 9 / 10; // -Atk
-            }
-            PokemonNature::MILD => {
-                result.3 = result.3 * 11 / 10; // +SpA
-                result.2 = result.2 * 9 / 10; // -Def
-            }
-            PokemonNature::RASH => {
-                result.3 = result.3 * 11 / 10; // +SpA
-                result.4 = result.4 * 9 / 10; // -SpD
-            }
-            PokemonNature::QUIET => {
-                result.3 = result.3 * 11 / 10; // +SpA
-                result.5 = result.5 * 9 / 10; // -Spe
-            }
-            PokemonNature::CALM => {
-                result.4 = result.4 * 11 / 10; // +SpD
-                result.1 = result.1 * 9 / 10; // -Atk
-            }
-            PokemonNature::GENTLE => {
-                result.4 = result.4 * 11 / 10; // +SpD
-                result.2 = result.2 * 9 / 10; // -Def
-            }
-            PokemonNature::CAREFUL => {
-                result.4 = result.4 * 11 / 10; // +SpD
-                result.3 = result.3 * 9 / 10; // -SpA
-            }
-            PokemonNature::SASSY => {
-                result.4 = result.4 * 11 / 10; // +SpD
-                result.5 = result.5 * 9 / 10; // -Spe
-            }
-            PokemonNature::TIMID => {
-                result.5 = result.5 * 11 / 10; // +Spe
-                result.1 = result.1 * 9 / 10; // -Atk
-            }
-            PokemonNature::HASTY => {
-                result.5 = result.5 * 11 / 10; // +Spe
-                result.2 = result.2 * 9 / 10; // -Def
-            }
-            PokemonNature::JOLLY => {
-                result.5 = result.5 * 11 / 10; // +Spe
-                result.3 = result.3 * 9 / 10; // -SpA
-            }
-            PokemonNature::NAIVE => {
-                result.5 = result.5 * 11 / 10; // +Spe
-                result.4 = result.4 * 9 / 10; // -SpD
-            }
-            // Neutral natures: no change
-            PokemonNature::HARDY
-            | PokemonNature::DOCILE
-            | PokemonNature::SERIOUS
-            | PokemonNature::BASHFUL
-            | PokemonNature::QUIRKY => {}
-        }
-        result
+        self.nature.apply_to_stats(result)
     }
 
     fn add_moves_from_opponent_targets(
@@ -1154,6 +1293,99 @@ impl Pokemon {
         }
     }
 
+    // Max Moves always hit a single target: damaging moves may target either opponent and every
+    // status move becomes Max Guard on the user
+    pub fn add_max_move_options(
+        &self,
+        side_ref: SideReference,
+        slot_ref: &SlotReference,
+        vec: &mut Vec<MoveChoice>,
+        opponent_targets: (&Pokemon, &Pokemon),
+        taunted: bool,
+        dynamaxing: bool,
+    ) {
+        let move_choice = |target_slot, target_side, pokemon_move_index| {
+            if dynamaxing {
+                MoveChoice::MoveDynamax(target_slot, target_side, pokemon_move_index)
+            } else {
+                MoveChoice::Move(target_slot, target_side, pokemon_move_index)
+            }
+        };
+
+        let mut iter = self.moves.into_iter();
+        while let Some(p) = iter.next() {
+            if p.disabled || p.pp <= 0 {
+                continue;
+            }
+            if p.choice.category == MoveCategory::Status {
+                if !taunted {
+                    vec.push(move_choice(*slot_ref, side_ref, iter.pokemon_move_index));
+                }
+                continue;
+            }
+            if opponent_targets.0.hp > 0 {
+                vec.push(move_choice(
+                    SlotReference::SlotA,
+                    side_ref.get_other_side(),
+                    iter.pokemon_move_index,
+                ));
+            }
+            if opponent_targets.1.hp > 0 {
+                vec.push(move_choice(
+                    SlotReference::SlotB,
+                    side_ref.get_other_side(),
+                    iter.pokemon_move_index,
+                ));
+            }
+        }
+    }
+
+    // Z-Moves are built from the options this slot already has, so anything blocking the regular
+    // move (disable, encore, taunt, etc.) also blocks the z-move. Damaging z-moves are single target
+    pub fn add_z_move_options(
+        &self,
+        side_ref: SideReference,
+        vec: &mut Vec<MoveChoice>,
+        first_option: usize,
+        opponent_targets: (&Pokemon, &Pokemon),
+    ) {
+        let z_type = match z_crystal_type(&self.item) {
+            Some(z_type) => z_type,
+            None => return,
+        };
+
+        let mut z_options = vec![];
+        let mut damaging_move_indices = vec![];
+        for option in vec[first_option..].iter() {
+            if let MoveChoice::Move(target_slot, target_side, index) = option {
+                let choice = &self.moves[index].choice;
+                if choice.move_type != z_type {
+                    continue;
+                }
+                if choice.category == MoveCategory::Status {
+                    z_options.push(MoveChoice::MoveZ(*target_slot, *target_side, *index));
+                } else if !damaging_move_indices.contains(index) {
+                    damaging_move_indices.push(*index);
+                    if opponent_targets.0.hp > 0 {
+                        z_options.push(MoveChoice::MoveZ(
+                            SlotReference::SlotA,
+                            side_ref.get_other_side(),
+                            *index,
+                        ));
+                    }
+                    if opponent_targets.1.hp > 0 {
+                        z_options.push(MoveChoice::MoveZ(
+                            SlotReference::SlotB,
+                            side_ref.get_other_side(),
+                            *index,
+                        ));
+                    }
+                }
+            }
+        }
+        vec.extend(z_options);
+    }
+
     // should we taunt this Pokémon? i.e. does it have any status moves that would be worth preventing with taunt?
     pub fn tauntable(&self) -> bool {
         for mv in self.moves.into_iter() {
@@ -1264,6 +1496,7 @@ impl Pokemon {
                 self.id == PokemonName::OGERPONWELLSPRING
                     || self.id == PokemonName::OGERPONWELLSPRINGTERA
             }
+            item if z_crystal_type(&item).is_some() => true,
             _ => false,
         }
     }
@@ -1315,7 +1548,10 @@ impl Pokemon {
             }
             PokemonVolatileStatus::SUBSTITUTE => self.hp > self.maxhp / 4,
             PokemonVolatileStatus::FLINCH => {
-                if !first_move || [Abilities::INNERFOCUS].contains(&self.ability) {
+                if !first_move
+                    || [Abilities::INNERFOCUS].contains(&self.ability)
+                    || active_volatiles.contains(&PokemonVolatileStatus::DYNAMAX)
+                {
                     return false;
                 }
                 true
@@ -1488,6 +1724,9 @@ impl Side {
 
     #[cfg(feature = "terastallization")]
     pub fn can_use_tera(&self) -> bool {
+        if self.dynamax_used {
+            return false;
+        }
         for p in self.pokemon.into_iter() {
             if p.terastallized {
                 return false;
@@ -1501,8 +1740,66 @@ impl Side {
         false
     }
 
+    #[cfg(not(feature = "dynamax"))]
+    pub fn can_use_dynamax(&self) -> bool {
+        false
+    }
+
+    // dynamax is the only gimmick a side gets in a battle where it dynamaxes, so it rules out
+    // mega evolving, z-moves and terastallizing for the rest of the battle and the other way round
+    #[cfg(feature = "dynamax")]
+    pub fn can_use_dynamax(&self) -> bool {
+        !self.dynamax_used
+            && !self.mega_used
+            && !self.z_move_used
+            && !self
+                .pokemon
+                .into_iter()
+                .any(|p| p.mega_evolved || p.terastallized)
+    }
+
+    #[cfg(not(feature = "zmoves"))]
+    pub fn can_use_z_move(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "zmoves")]
+    pub fn can_use_z_move(&self) -> bool {
+        !self.z_move_used && !self.dynamax_used
+    }
+
+    // Dynamax doubled both hp values, so halve them again when it ends
+    pub fn revert_dynamax_hp(
+        &mut self,
+        side_ref: SideReference,
+        slot_ref: &SlotReference,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        let (active, active_index) = self.get_active_with_index(slot_ref);
+        let new_maxhp = active.maxhp / 2;
+        let new_hp = (active.hp + 1) / 2;
+        instructions.push(Instruction::ChangeMaxHP(ChangeStatInstruction {
+            side_ref,
+            pokemon_index: active_index,
+            amount: new_maxhp - active.maxhp,
+        }));
+        active.maxhp = new_maxhp;
+        if new_hp != active.hp {
+            instructions.push(Instruction::Heal(HealInstruction {
+                side_ref,
+                pokemon_index: active_index,
+                heal_amount: new_hp - active.hp,
+            }));
+            active.hp = new_hp;
+        }
+    }
+
     #[cfg(feature = "mega")]
     pub fn can_use_mega(&self) -> bool {
+        if self.mega_used || self.dynamax_used {
+            return false;
+        }
+        // states built without `mega_used` still have the mega-evolved pkmn on the team
         for p in self.pokemon.into_iter() {
             if p.mega_evolved {
                 return false;
@@ -1620,7 +1917,9 @@ impl State {
                 .retain(|(x, _)| match x {
                     MoveChoice::Move(_, _, _)
                     | MoveChoice::MoveTera(_, _, _)
-                    | MoveChoice::MoveMega(_, _, _) => true,
+                    | MoveChoice::MoveMega(_, _, _)
+                    | MoveChoice::MoveDynamax(_, _, _)
+                    | MoveChoice::MoveZ(_, _, _) => true,
                     MoveChoice::Switch(_) => false,
                     MoveChoice::TeamPreview(_, _) => false,
                     MoveChoice::None => true,
@@ -1632,7 +1931,9 @@ impl State {
                 .retain(|(_, x)| match x {
                     MoveChoice::Move(_, _, _)
                     | MoveChoice::MoveTera(_, _, _)
-                    | MoveChoice::MoveMega(_, _, _) => true,
+                    | MoveChoice::MoveMega(_, _, _)
+                    | MoveChoice::MoveDynamax(_, _, _)
+                    | MoveChoice::MoveZ(_, _, _) => true,
                     MoveChoice::Switch(_) => false,
                     MoveChoice::TeamPreview(_, _) => false,
                     MoveChoice::None => true,
@@ -1645,7 +1946,9 @@ impl State {
                 .retain(|(x, _)| match x {
                     MoveChoice::Move(_, _, _)
                     | MoveChoice::MoveTera(_, _, _)
-                    | MoveChoice::MoveMega(_, _, _) => true,
+                    | MoveChoice::MoveMega(_, _, _)
+                    | MoveChoice::MoveDynamax(_, _, _)
+                    | MoveChoice::MoveZ(_, _, _) => true,
                     MoveChoice::Switch(_) => false,
                     MoveChoice::TeamPreview(_, _) => false,
                     MoveChoice::None => true,
@@ -1657,7 +1960,9 @@ impl State {
                 .retain(|(_, x)| match x {
                     MoveChoice::Move(_, _, _)
                     | MoveChoice::MoveTera(_, _, _)
-                    | MoveChoice::MoveMega(_, _, _) => true,
+                    | MoveChoice::MoveMega(_, _, _)
+                    | MoveChoice::MoveDynamax(_, _, _)
+                    | MoveChoice::MoveZ(_, _, _) => true,
                     MoveChoice::Switch(_) => false,
                     MoveChoice::TeamPreview(_, _) => false,
                     MoveChoice::None => true,
@@ -1782,20 +2087,54 @@ impl State {
                 .contains(&PokemonVolatileStatus::TAUNT);
 
             let partner = side.get_active_immutable(&slot_ref.get_other_slot());
-            side.get_active_immutable(&slot_ref).add_available_moves(
-                side_ref,
-                &slot_ref,
-                slot_options,
-                &slot.last_used_move,
-                (&opponent_active_a, &opponent_active_b),
-                active_charging_mv_index,
-                partner,
-                encored,
-                disabled,
-                taunted,
-                side.can_use_tera(),
-                side.can_use_mega(),
-            );
+            let active = side.get_active_immutable(&slot_ref);
+            if slot
+                .volatile_statuses
+                .contains(&PokemonVolatileStatus::DYNAMAX)
+            {
+                active.add_max_move_options(
+                    side_ref,
+                    &slot_ref,
+                    slot_options,
+                    (opponent_active_a, opponent_active_b),
+                    taunted,
+                    false,
+                );
+            } else {
+                let first_option = slot_options.len();
+                active.add_available_moves(
+                    side_ref,
+                    &slot_ref,
+                    slot_options,
+                    &slot.last_used_move,
+                    (&opponent_active_a, &opponent_active_b),
+                    active_charging_mv_index,
+                    partner,
+                    encored,
+                    disabled,
+                    taunted,
+                    side.can_use_tera(),
+                    side.can_use_mega(),
+                );
+                if side.can_use_dynamax() && active_charging_mv_index.is_none() {
+                    active.add_max_move_options(
+                        side_ref,
+                        &slot_ref,
+                        slot_options,
+                        (opponent_active_a, opponent_active_b),
+                        taunted,
+                        true,
+                    );
+                }
+                if side.can_use_z_move() {
+                    active.add_z_move_options(
+                        side_ref,
+                        slot_options,
+                        first_option,
+                        (opponent_active_a, opponent_active_b),
+                    );
+                }
+            }
 
             if !side.trapped(
                 side.get_slot_immutable(&slot_ref),
@@ -2045,6 +2384,20 @@ impl State {
                     slot.volatile_status_durations.yawn = 0;
                     false
                 }
+                PokemonVolatileStatus::DYNAMAX => {
+                    let slot = side.get_slot(slot_ref);
+                    instructions.push(Instruction::ChangeVolatileStatusDuration(
+                        ChangeVolatileStatusDurationInstruction {
+                            side_ref,
+                            slot_ref: *slot_ref,
+                            volatile_status: *pkmn_volatile_status,
+                            amount: -slot.volatile_status_durations.dynamax,
+                        },
+                    ));
+                    slot.volatile_status_durations.dynamax = 0;
+                    side.revert_dynamax_hp(side_ref, slot_ref, instructions);
+                    false
+                }
                 PokemonVolatileStatus::TAUNT => {
                     let slot = side.get_slot(slot_ref);
                     instructions.push(Instruction::ChangeVolatileStatusDuration(
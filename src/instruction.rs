@@ -66,6 +66,7 @@ pub enum Instruction {
     ChangeSpecialAttack(ChangeStatInstruction),
     ChangeSpecialDefense(ChangeStatInstruction),
     ChangeSpeed(ChangeStatInstruction),
+    ChangeMaxHP(ChangeStatInstruction),
     DisableMove(DisableMoveInstruction),
     EnableMove(EnableMoveInstruction),
     ChangeWish(ChangeWishInstruction),
@@ -90,6 +91,9 @@ pub enum Instruction {
     DecrementTrickRoomTurnsRemaining,
     ToggleForceSwitch(ToggleForceSwitchInstruction),
     ToggleMegaEvolved(ToggleMegaEvolvedInstruction),
+    ToggleMegaUsed(ToggleMegaUsedInstruction),
+    ToggleDynamaxUsed(ToggleDynamaxUsedInstruction),
+    ToggleZMoveUsed(ToggleZMoveUsedInstruction),
     ToggleTerastallized(ToggleTerastallizedInstruction),
     IncrementTimesAttacked(IncrementTimesAttackedInstruction),
     InsertStellarBoostedType(InsertStellarBoostedTypeInstruction),
@@ -251,6 +255,13 @@ impl fmt::Debug for Instruction {
                     c.side_ref, c.pokemon_index, c.amount
                 )
             }
+            Instruction::ChangeMaxHP(c) => {
+                write!(
+                    f,
+                    "ChangeMaxHP {:?} {:?}: {}",
+                    c.side_ref, c.pokemon_index, c.amount
+                )
+            }
             Instruction::DisableMove(d) => {
                 write!(
                     f,
@@ -354,6 +365,15 @@ impl fmt::Debug for Instruction {
                     s.side_ref, s.pokemon_index
                 )
             }
+            Instruction::ToggleMegaUsed(s) => {
+                write!(f, "ToggleMegaUsed {:?}", s.side_ref)
+            }
+            Instruction::ToggleDynamaxUsed(s) => {
+                write!(f, "ToggleDynamaxUsed {:?}", s.side_ref)
+            }
+            Instruction::ToggleZMoveUsed(s) => {
+                write!(f, "ToggleZMoveUsed {:?}", s.side_ref)
+            }
             Instruction::SetLastUsedMove(s) => {
                 write!(
                     f,
@@ -688,6 +708,21 @@ pub struct ToggleMegaEvolvedInstruction {
     pub pokemon_index: PokemonIndex,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct ToggleMegaUsedInstruction {
+    pub side_ref: SideReference,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ToggleDynamaxUsedInstruction {
+    pub side_ref: SideReference,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ToggleZMoveUsedInstruction {
+    pub side_ref: SideReference,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ToggleTerastallizedInstruction {
     pub side_ref: SideReference,
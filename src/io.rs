@@ -5,15 +5,25 @@ use crate::engine::generate_instructions::{
 };
 use crate::engine::state::MoveChoice;
 use crate::instruction::{Instruction, StateInstructions};
-use crate::mcts::{perform_mcts, MctsResult};
+use crate::mcts::{
+    perform_mcts, search_duration, MctsConfig, MctsResult, MctsSideResult, PrincipalVariationPly,
+    RolloutPolicy, SelectionPolicy,
+};
+use crate::mcts_root_parallel::{perform_mcts_root_parallel, root_parallel_configs};
+use crate::mcts_team_preview::{
+    perform_mcts_team_preview, TeamPreviewResult, TeamPreviewSideResult,
+};
 use crate::mcts_threaded::perform_mcts_shared_tree;
-use crate::state::{PokemonIndex, SideReference, SlotReference, State};
+use crate::state::{pokemon_index_iter, PokemonIndex, Side, SideReference, SlotReference, State};
 use clap::Parser;
 use std::io;
 use std::io::Write;
 use std::process::exit;
 use std::str::FromStr;
 
+// how many teams per side `team-preview-all` searches unless it is told otherwise
+const TEAM_PREVIEW_TEAMS_KEPT: usize = 12;
+
 struct IOData {
     state: State,
     instruction_list: Vec<Vec<Instruction>>,
@@ -41,11 +51,149 @@ struct MonteCarloTreeSearch {
     #[clap(short, long, required = true)]
     state: String,
 
+    // 0 searches until the iteration or node budget is reached instead
     #[clap(short = 't', long, default_value_t = 5000)]
     time_to_search_ms: u64,
 
     #[clap(short = 'n', long, default_value_t = 1)]
     threads: usize,
+
+    // search one independent tree per thread and merge their roots instead of sharing a tree
+    #[clap(long)]
+    root_parallel: bool,
+
+    #[clap(flatten)]
+    config: MctsConfigArgs,
+}
+
+#[derive(Parser)]
+struct MctsConfigArgs {
+    #[clap(long, default_value_t = MctsConfig::default().min_visits_before_selection)]
+    min_visits_before_selection: usize,
+
+    #[clap(long, default_value_t = MctsConfig::default().exploration_constant)]
+    exploration_constant: f32,
+
+    #[clap(long, default_value_t = MctsConfig::default().max_depth)]
+    max_depth: u8,
+
+    #[clap(long, default_value_t = MctsConfig::default().sigmoid_slope)]
+    sigmoid_slope: f32,
+
+    #[clap(long, default_value_t = MctsConfig::default().virtual_loss_visits)]
+    virtual_loss_visits: u32,
+
+    #[clap(long)]
+    max_iterations: Option<u64>,
+
+    #[clap(long)]
+    max_nodes: Option<usize>,
+
+    /// bytes of nodes after which the tree stops growing
+    #[clap(long)]
+    max_memory: Option<usize>,
+
+    #[clap(long, default_value_t = false)]
+    early_stop: bool,
+
+    #[clap(long)]
+    seed: Option<u64>,
+
+    #[clap(long, default_value_t = MctsConfig::default().nash_iterations)]
+    nash_iterations: u32,
+
+    /// one of ucb1, exp3, regretmatching, factorizeducb1 or puct
+    #[clap(
+        long,
+        value_parser = SelectionPolicy::parse,
+        default_value_t = MctsConfig::default().selection_policy
+    )]
+    selection_policy: SelectionPolicy,
+
+    #[clap(long, default_value_t = MctsConfig::default().policy_exploration)]
+    policy_exploration: f32,
+
+    #[clap(long, default_value_t = MctsConfig::default().puct_constant)]
+    puct_constant: f32,
+
+    #[clap(long, default_value_t = false)]
+    progressive_widening: bool,
+
+    #[clap(long, default_value_t = MctsConfig::default().widening_constant)]
+    widening_constant: f32,
+
+    #[clap(long, default_value_t = MctsConfig::default().widening_exponent)]
+    widening_exponent: f32,
+
+    /// one of static, random or greedy
+    #[clap(
+        long,
+        value_parser = parse_rollout_policy,
+        default_value_t = MctsConfig::default().rollout_policy
+    )]
+    rollout_policy: RolloutPolicy,
+
+    #[clap(long, default_value_t = MctsConfig::default().rollout_turns)]
+    rollout_turns: u8,
+
+    #[clap(long, default_value_t = MctsConfig::default().rollout_mix)]
+    rollout_mix: f32,
+
+    #[clap(long, default_value_t = false)]
+    transposition_table: bool,
+
+    #[clap(long, default_value_t = MctsConfig::default().principal_variation_plies)]
+    principal_variation_plies: u8,
+
+    #[clap(long, default_value_t = MctsConfig::default().endgame_pokemon)]
+    endgame_pokemon: u8,
+
+    #[clap(long, default_value_t = MctsConfig::default().endgame_depth)]
+    endgame_depth: u8,
+}
+
+impl From<MctsConfigArgs> for MctsConfig {
+    fn from(args: MctsConfigArgs) -> Self {
+        MctsConfig {
+            min_visits_before_selection: args.min_visits_before_selection,
+            exploration_constant: args.exploration_constant,
+            max_depth: args.max_depth,
+            sigmoid_slope: args.sigmoid_slope,
+            virtual_loss_visits: args.virtual_loss_visits,
+            max_iterations: args.max_iterations,
+            max_nodes: args.max_nodes,
+            max_memory: args.max_memory,
+            early_stop: args.early_stop,
+            seed: args.seed,
+            nash_iterations: args.nash_iterations,
+            selection_policy: args.selection_policy,
+            policy_exploration: args.policy_exploration,
+            puct_constant: args.puct_constant,
+            prior_callback: None,
+            progressive_widening: args.progressive_widening,
+            widening_constant: args.widening_constant,
+            widening_exponent: args.widening_exponent,
+            rollout_policy: args.rollout_policy,
+            rollout_turns: args.rollout_turns,
+            rollout_mix: args.rollout_mix,
+            rollout_callback: None,
+            transposition_table: args.transposition_table,
+            principal_variation_plies: args.principal_variation_plies,
+            endgame_pokemon: args.endgame_pokemon,
+            endgame_depth: args.endgame_depth,
+        }
+    }
+}
+
+// a rollout callback can only be set from Rust, so the CALLBACK policy would have nothing to call
+fn parse_rollout_policy(name: &str) -> Result<RolloutPolicy, String> {
+    match RolloutPolicy::parse(name)? {
+        RolloutPolicy::CALLBACK => Err(
+            "the callback rollout policy needs a rollout callback, which can only be set from Rust"
+                .to_string(),
+        ),
+        policy => Ok(policy),
+    }
 }
 
 #[derive(Parser)]
@@ -91,8 +239,103 @@ impl Default for IOData {
     }
 }
 
+fn pprint_nash_strategy(side: &Side, side_result: &[MctsSideResult]) {
+    let mut strategy: Vec<(&MctsSideResult, f32)> = side_result
+        .iter()
+        .filter_map(|x| x.nash_probability.map(|p| (x, p)))
+        .filter(|(_, p)| *p >= 0.01)
+        .collect();
+    strategy.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    println!("\t{:<60}{:>12}", "Move", "% Played");
+    for (x, p) in strategy {
+        println!(
+            "\t{:<30}{:<30}{:>12.2}",
+            x.move_choice.0.to_string(side, &SlotReference::SlotA),
+            x.move_choice.1.to_string(side, &SlotReference::SlotB),
+            p * 100.0
+        );
+    }
+}
+
+fn pprint_principal_variation(state: &State, principal_variation: &[PrincipalVariationPly]) {
+    println!("\nPrincipal Variation:");
+    println!(
+        "\t{:<4}{:<60}{:<60}{:>12}{:>10}{:>10}",
+        "Ply", "Side One", "Side Two", "Avg Score", "Visits", "Chance"
+    );
+    let mut state = state.clone();
+    for (ply, x) in principal_variation.iter().enumerate() {
+        println!(
+            "\t{:<4}{:<30}{:<30}{:<30}{:<30}{:>12.2}{:>10}{:>10.2}",
+            ply + 1,
+            x.s1_move
+                .0
+                .to_string(&state.sides[0], &SlotReference::SlotA),
+            x.s1_move
+                .1
+                .to_string(&state.sides[0], &SlotReference::SlotB),
+            x.s2_move
+                .0
+                .to_string(&state.sides[1], &SlotReference::SlotA),
+            x.s2_move
+                .1
+                .to_string(&state.sides[1], &SlotReference::SlotB),
+            x.average_score,
+            x.visits,
+            x.instructions.percentage
+        );
+        state.apply_instructions(&x.instructions.instruction_list);
+    }
+}
+
+fn pprint_team_preview_side(side: &Side, result: &TeamPreviewSideResult) {
+    println!(
+        "\t{:<50}{:>12}{:>10}{:>12}",
+        "Leads / Back", "Matchup", "Visits", "Probability"
+    );
+    for x in result.teams.iter().take(10) {
+        let names: Vec<String> = x
+            .team
+            .iter()
+            .map(|index| format!("{}", side.pokemon[*index].id).to_lowercase())
+            .collect();
+        println!(
+            "\t{:<50}{:>12.2}{:>10}{:>12.3}",
+            format!("{}, {} / {}, {}", names[0], names[1], names[2], names[3]),
+            x.matchup,
+            x.visits,
+            x.probability
+        );
+    }
+
+    println!("\n\t{:<30}{:>12}{:>12}", "Pokemon", "Brought", "Leads");
+    for index in pokemon_index_iter() {
+        println!(
+            "\t{:<30}{:>12.3}{:>12.3}",
+            format!("{}", side.pokemon[index].id).to_lowercase(),
+            result.brings[index as usize],
+            result.leads[index as usize]
+        );
+    }
+}
+
+pub fn pprint_team_preview_result(state: &State, result: &TeamPreviewResult) {
+    println!("\nTotal Iterations: {}", result.search.iteration_count);
+
+    println!("\nSide One Teams: {}", result.s1.teams.len());
+    pprint_team_preview_side(&state.sides[0], &result.s1);
+
+    println!("\nSide Two Teams: {}", result.s2.teams.len());
+    pprint_team_preview_side(&state.sides[1], &result.s2);
+}
+
 pub fn pprint_mcts_result(state: &State, result: MctsResult) {
-    println!("\nTotal Iterations: {}\n", result.iteration_count);
+    println!("\nTotal Iterations: {}", result.iteration_count);
+    println!(
+        "Leaf Depth: {} max, {:.2} average\n",
+        result.max_leaf_depth, result.average_leaf_depth
+    );
 
     // Side One
     println!("Side One Options: {}", result.s1.len());
@@ -195,6 +438,17 @@ pub fn pprint_mcts_result(state: &State, result: MctsResult) {
             );
         }
     }
+
+    if result.s1.iter().any(|x| x.nash_probability.is_some()) {
+        println!("\nSide One Nash Strategy:");
+        pprint_nash_strategy(&state.sides[0], &result.s1);
+        println!("\nSide Two Nash Strategy:");
+        pprint_nash_strategy(&state.sides[1], &result.s2);
+    }
+
+    if !result.principal_variation.is_empty() {
+        pprint_principal_variation(state, &result.principal_variation);
+    }
 }
 
 fn pprint_state_instruction_vector(instructions: &Vec<StateInstructions>) {
@@ -225,20 +479,32 @@ pub fn main() {
             SubCommand::MonteCarloTreeSearch(mcts) => {
                 state = State::deserialize(mcts.state.as_str());
                 (side_one_options, side_two_options) = state.root_get_all_options();
-                let result = if mcts.threads > 1 {
+                let config = MctsConfig::from(mcts.config);
+                let max_time = search_duration(mcts.time_to_search_ms);
+                let result = if mcts.threads > 1 && mcts.root_parallel {
+                    perform_mcts_root_parallel(
+                        &mut state,
+                        side_one_options.clone(),
+                        side_two_options.clone(),
+                        max_time,
+                        &root_parallel_configs(&config, mcts.threads),
+                    )
+                } else if mcts.threads > 1 {
                     perform_mcts_shared_tree(
                         &mut state,
                         side_one_options.clone(),
                         side_two_options.clone(),
-                        std::time::Duration::from_millis(mcts.time_to_search_ms),
+                        max_time,
                         mcts.threads,
+                        &config,
                     )
                 } else {
                     perform_mcts(
                         &mut state,
                         side_one_options.clone(),
                         side_two_options.clone(),
-                        std::time::Duration::from_millis(mcts.time_to_search_ms),
+                        max_time,
+                        &config,
                     )
                 };
                 pprint_mcts_result(&state, result);
@@ -610,6 +876,7 @@ fn command_loop(mut io_data: IOData) {
                         side_one_options.clone(),
                         side_two_options.clone(),
                         std::time::Duration::from_millis(max_time_ms),
+                        &MctsConfig::default(),
                     );
                     let elapsed = start_time.elapsed();
                     pprint_mcts_result(&io_data.state, result);
@@ -648,11 +915,42 @@ fn command_loop(mut io_data: IOData) {
                     side_two_options.clone(),
                     std::time::Duration::from_millis(max_time_ms),
                     worker_count,
+                    &MctsConfig::default(),
                 );
                 let elapsed = start_time.elapsed();
                 pprint_mcts_result(&io_data.state, result);
                 println!("\nTook: {:?}", elapsed);
             }
+            "team-preview-all" | "tpa" => {
+                if !io_data.state.team_preview {
+                    println!("Team preview must be active");
+                    continue;
+                }
+                let max_time_ms = match args.next().and_then(|s| s.parse::<u64>().ok()) {
+                    Some(v) => v,
+                    None => {
+                        println!("Usage: team-preview-all <time_ms> [teams_kept_per_side]");
+                        continue;
+                    }
+                };
+                let keep = match args.next() {
+                    Some(s) => s.parse::<usize>().unwrap(),
+                    None => TEAM_PREVIEW_TEAMS_KEPT,
+                };
+
+                let start_time = std::time::Instant::now();
+                let result = perform_mcts_team_preview(
+                    &mut io_data.state,
+                    std::time::Duration::from_millis(max_time_ms),
+                    keep,
+                    1,
+                    &MctsConfig::default(),
+                );
+                let elapsed = start_time.elapsed();
+                pprint_team_preview_result(&io_data.state, &result);
+
+                println!("\nTook: {:?}", elapsed);
+            }
             "team-preview" | "tp" => {
                 if !io_data.state.team_preview {
                     println!("Team preview must be active");
@@ -719,6 +1017,7 @@ fn command_loop(mut io_data: IOData) {
                     side_one_options,
                     side_two_options,
                     std::time::Duration::from_millis(max_time_ms),
+                    &MctsConfig::default(),
                 );
                 let elapsed = start_time.elapsed();
                 pprint_mcts_result(&io_data.state, result);
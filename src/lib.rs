@@ -2,9 +2,14 @@
 pub mod engine;
 
 pub mod choices;
+pub mod endgame;
 pub mod instruction;
 pub mod io;
 pub mod mcts;
+pub mod mcts_export;
+pub mod mcts_information_set;
+pub mod mcts_root_parallel;
+pub mod mcts_team_preview;
 pub mod mcts_threaded;
 pub mod pokemon;
 pub mod state;
@@ -125,4 +130,4 @@ macro_rules! define_enum_with_from_str {
     };
 }
 
-assert_unique_feature!("gen9");
+assert_unique_feature!("gen1", "gen2", "gen3", "gen4", "gen5", "gen6", "gen7", "gen8", "gen9");
@@ -1,31 +1,1056 @@
+use crate::choices::{Choices, MoveCategory, MoveChoiceTarget};
+use crate::define_enum_with_from_str;
+use crate::endgame::{is_endgame, solve_endgame};
+use crate::engine::damage_calc::type_effectiveness_modifier;
 use crate::engine::evaluate::evaluate;
 use crate::engine::generate_instructions::generate_instructions_from_move_pair;
 use crate::engine::state::{MoveChoice, MoveOptions};
-use crate::instruction::StateInstructions;
-use crate::state::State;
+use crate::instruction::{Instruction, StateInstructions};
+use crate::mcts_export::ExportedNode;
+use crate::pokemon::PokemonName;
+use crate::state::{SideReference, SlotReference, State};
 use rand::distr::weighted::WeightedIndex;
 use rand::prelude::*;
 use rand::rng;
-use std::collections::HashMap;
-use std::time::Duration;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const MIN_VISITS_BEFORE_SELECTION: usize = 50;
+// the default `MctsConfig::max_depth`
+const MCTS_MAX_DEPTH: u8 = 4;
 
-fn sigmoid(x: f32) -> f32 {
-    // Tuned so that ~400 points is very close to 1.0
-    1.0 / (1.0 + (-0.0062 * x).exp())
+define_enum_with_from_str! {
+    #[repr(u8)]
+    /// How each side picks its option at a node during selection.
+    ///
+    /// `UCB1` picks the option with the highest upper confidence bound, which is deterministic
+    /// and can be exploited in simultaneous-move games. `EXP3` and `REGRETMATCHING` sample the
+    /// option from a mixed strategy that is updated with importance-weighted rewards.
+    ///
+    /// `FACTORIZEDUCB1` runs a UCB1 bandit per slot instead of per pair of slot choices, so a
+    /// slot's choice shares its statistics across every pair it is part of. Slot A's choice is
+    /// picked first, then slot B's choice among the pairs that are legal alongside it.
+    ///
+    /// `PUCT` picks the option with the highest `average_score + puct_constant * prior *
+    /// sqrt(parent_visits + 1) / (visits + 1)`, where each option's prior comes from
+    /// `MctsConfig::prior_callback`, so options the callback expects to be played are
+    /// searched first and most
+    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+    SelectionPolicy {
+        UCB1,
+        EXP3,
+        REGRETMATCHING,
+        FACTORIZEDUCB1,
+        PUCT,
+    }
+}
+
+define_enum_with_from_str! {
+    #[repr(u8)]
+    /// How a leaf is scored once selection and expansion are done.
+    ///
+    /// `STATIC` scores the leaf with `evaluate`. The other policies first play out
+    /// `MctsConfig::rollout_turns` turns from the leaf, picking both sides' options uniformly at
+    /// random (`RANDOM`), by the highest expected damage (`GREEDY`), or with
+    /// `MctsConfig::rollout_callback` (`CALLBACK`), sampling each outcome by its probability
+    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+    RolloutPolicy {
+        STATIC,
+        RANDOM,
+        GREEDY,
+        CALLBACK,
+    }
+}
+
+impl SelectionPolicy {
+    /// Parses a policy name, ignoring case. Unlike `from_str`, an unknown name is an error
+    /// listing the valid names instead of a panic
+    pub fn parse(name: &str) -> Result<Self, String> {
+        parse_policy(
+            name,
+            &[
+                SelectionPolicy::UCB1,
+                SelectionPolicy::EXP3,
+                SelectionPolicy::REGRETMATCHING,
+                SelectionPolicy::FACTORIZEDUCB1,
+                SelectionPolicy::PUCT,
+            ],
+        )
+    }
+}
+
+impl RolloutPolicy {
+    /// Parses a policy name, ignoring case. Unlike `from_str`, an unknown name is an error
+    /// listing the valid names instead of a panic
+    pub fn parse(name: &str) -> Result<Self, String> {
+        parse_policy(
+            name,
+            &[
+                RolloutPolicy::STATIC,
+                RolloutPolicy::RANDOM,
+                RolloutPolicy::GREEDY,
+                RolloutPolicy::CALLBACK,
+            ],
+        )
+    }
+}
+
+fn parse_policy<T: Copy + std::fmt::Display>(name: &str, policies: &[T]) -> Result<T, String> {
+    policies
+        .iter()
+        .find(|policy| policy.to_string().eq_ignore_ascii_case(name))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<String> = policies
+                .iter()
+                .map(|x| x.to_string().to_lowercase())
+                .collect();
+            format!(
+                "invalid policy '{}', expected one of {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Picks the index of the option `side` plays in a `RolloutPolicy::CALLBACK` rollout
+pub type RolloutCallback =
+    Arc<dyn Fn(&State, SideReference, &[(MoveChoice, MoveChoice)]) -> usize + Send + Sync>;
+
+/// The probability of each choice `side` may make for one of its slots, e.g. from usage data
+/// captured by the callback, see `usage_prior_callback`. Choices that are left out get no prior,
+/// and a slot whose listed choices add up to nothing gets a uniform prior
+pub type PriorCallback =
+    Arc<dyn Fn(&State, SideReference, SlotReference) -> Vec<(MoveChoice, f32)> + Send + Sync>;
+
+/// Tuning knobs shared by `perform_mcts` and `perform_mcts_shared_tree`.
+/// `MctsConfig::default()` reproduces the values the engine has always searched with
+#[derive(Clone)]
+pub struct MctsConfig {
+    /// each option at a node is visited round-robin this many times before ucb1 selection starts.
+    /// only used by the single-threaded search
+    pub min_visits_before_selection: usize,
+
+    /// the `c` in `average_score + sqrt(c * ln(parent_visits) / visits)`
+    pub exploration_constant: f32,
+
+    /// the search horizon in full turns: nodes this many turns below the root are not expanded
+    /// any further. a turn is counted when its end-of-turn effects resolve, see `child_depth`
+    pub max_depth: u8,
+
+    /// slope of the sigmoid mapping an evaluation difference to a score between 0 and 1
+    pub sigmoid_slope: f32,
+
+    /// visits temporarily added to a move while a thread is in flight through it in the shared tree
+    pub virtual_loss_visits: u32,
+
+    /// each search stops after this many iterations, not counting the visits a tree kept from
+    /// earlier searches. `None` searches until the time or another budget runs out
+    pub max_iterations: Option<u64>,
+
+    /// the search stops once the tree has allocated this many nodes
+    pub max_nodes: Option<usize>,
+
+    /// the tree stops growing once its nodes take up about this many bytes. unlike
+    /// `max_nodes` the search carries on, scoring leaves where the tree ends
+    pub max_memory: Option<usize>,
+
+    /// stop as soon as the most visited root option of each side can no longer be overtaken
+    /// within the remaining time and iteration budget
+    pub early_stop: bool,
+
+    /// seeds the random sampling of outcomes so a search can be replayed exactly.
+    /// a search is only reproducible when it is stopped by an iteration or node budget
+    /// rather than by time, and when the shared tree is searched with a single worker
+    pub seed: Option<u64>,
+
+    /// iterations of regret matching used to solve the root for a mixed strategy once the
+    /// search is done. 0 skips the solve and leaves `MctsSideResult::nash_probability` empty
+    pub nash_iterations: u32,
+
+    /// how each side picks its option at a node during selection
+    pub selection_policy: SelectionPolicy,
+
+    /// the share of `EXP3` and `REGRETMATCHING` selection that is spread uniformly over all
+    /// options, so that every option keeps being explored. `PUCT` mixes this share of a
+    /// uniform prior into every option's prior
+    pub policy_exploration: f32,
+
+    /// the weight of an option's prior against its average score in `PUCT` selection
+    pub puct_constant: f32,
+
+    /// each side's prior probability of every choice for one of its slots. `PUCT` selection
+    /// searches the options of both sides by these priors, and progressive widening opens
+    /// options in order of them. without a callback `PUCT` uses uniform priors
+    pub prior_callback: Option<PriorCallback>,
+
+    /// only open a node's options gradually as it is visited, most promising first.
+    /// a node visited `n` times considers `ceil(widening_constant * (n + 1) ^ widening_exponent)`
+    /// options for each side. only used by the `UCB1`, `FACTORIZEDUCB1` and `PUCT` selection
+    /// policies
+    pub progressive_widening: bool,
+
+    /// how many options progressive widening opens, see `progressive_widening`
+    pub widening_constant: f32,
+
+    /// how quickly progressive widening opens more options, see `progressive_widening`
+    pub widening_exponent: f32,
+
+    /// how a leaf is scored once selection and expansion are done
+    pub rollout_policy: RolloutPolicy,
+
+    /// the number of turns played out from a leaf by any rollout policy other than `STATIC`
+    pub rollout_turns: u8,
+
+    /// the weight of a played out rollout's score, the rest of the leaf's score being its
+    /// static evaluation
+    pub rollout_mix: f32,
+
+    /// picks the options played by `RolloutPolicy::CALLBACK`. Without one, `CALLBACK` scores
+    /// leaves like `STATIC`
+    pub rollout_callback: Option<RolloutCallback>,
+
+    /// share statistics between nodes that reach the same state, keyed by the state's hash and
+    /// the node's depth. the first node to reach a state keeps the visits, option statistics and
+    /// children, and every other node reaching it selects and backpropagates through that node
+    pub transposition_table: bool,
+
+    /// follow each side's most visited option and the most likely outcome of the pair for this
+    /// many plies from the root, and return that line in `MctsResult::principal_variation`.
+    /// 0 leaves it empty
+    pub principal_variation_plies: u8,
+
+    /// `perform_mcts` and `perform_mcts_shared_tree` solve the state with `solve_endgame`
+    /// instead of searching it once both sides have at most this many pokemon left, and search
+    /// it for the time left if not even one turn is solved in time. 0 never solves it
+    pub endgame_pokemon: u8,
+
+    /// the number of turns `solve_endgame` searches, if it has the time to
+    pub endgame_depth: u8,
+}
+
+impl MctsConfig {
+    // the rollout policy leaves are scored with. `CALLBACK` has nothing to call without a
+    // `rollout_callback`, so it falls back to `STATIC`
+    fn effective_rollout_policy(&self) -> RolloutPolicy {
+        match (self.rollout_policy, &self.rollout_callback) {
+            (RolloutPolicy::CALLBACK, None) => RolloutPolicy::STATIC,
+            (rollout_policy, _) => rollout_policy,
+        }
+    }
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            min_visits_before_selection: 50,
+            exploration_constant: 2.0,
+            max_depth: MCTS_MAX_DEPTH,
+            // Tuned so that ~400 points is very close to 1.0
+            sigmoid_slope: 0.0062,
+            virtual_loss_visits: 3,
+            max_iterations: None,
+            max_nodes: None,
+            max_memory: None,
+            early_stop: false,
+            seed: None,
+            nash_iterations: 0,
+            selection_policy: SelectionPolicy::UCB1,
+            policy_exploration: 0.1,
+            puct_constant: 1.5,
+            prior_callback: None,
+            progressive_widening: false,
+            widening_constant: 2.0,
+            widening_exponent: 0.5,
+            rollout_policy: RolloutPolicy::STATIC,
+            rollout_turns: 3,
+            rollout_mix: 0.5,
+            rollout_callback: None,
+            transposition_table: false,
+            principal_variation_plies: 0,
+            endgame_pokemon: 0,
+            endgame_depth: 2,
+        }
+    }
+}
+
+/// Converts a search time in milliseconds to a `Duration`, where 0 means no time limit
+/// and the search only stops on the budgets in `MctsConfig`
+pub fn search_duration(time_ms: u64) -> Duration {
+    if time_ms == 0 {
+        Duration::MAX
+    } else {
+        Duration::from_millis(time_ms)
+    }
+}
+
+// seeded from `seed` when one is given, otherwise from the thread-local generator
+pub(crate) fn search_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rng()),
+    }
+}
+
+/// The number of turns below the root of a node reached through `instructions` from a node
+/// `depth` turns below it.
+///
+/// Only outcomes that resolve the end of the turn count as a turn. An outcome that stops for a
+/// forced switch leaves the switch to be picked in the same turn, and picking leads at team
+/// preview happens before the first turn, so neither moves a node any further from the root
+pub fn child_depth(depth: u8, instructions: &StateInstructions) -> u8 {
+    if instructions.end_of_turn_triggered {
+        depth.saturating_add(1)
+    } else {
+        depth
+    }
+}
+
+// the depths of the leaves the iterations of a search ended on, for
+// `MctsResult::max_leaf_depth` and `MctsResult::average_leaf_depth`
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct LeafDepths {
+    pub(crate) max: u8,
+    pub(crate) total: u64,
+    pub(crate) count: u64,
+}
+
+impl LeafDepths {
+    pub(crate) fn add(&mut self, depth: u8) {
+        self.max = self.max.max(depth);
+        self.total += depth as u64;
+        self.count += 1;
+    }
+
+    pub(crate) fn average(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        (self.total as f64 / self.count as f64) as f32
+    }
+}
+
+pub(crate) fn sigmoid(x: f32, slope: f32) -> f32 {
+    1.0 / (1.0 + (-slope * x).exp())
+}
+
+// the iterations `MctsConfig::max_iterations` leaves once a search call has run `iterations`.
+// the limit applies to each call, so visits a tree kept from earlier searches do not count
+pub(crate) fn iterations_left(iterations: u64, config: &MctsConfig) -> u64 {
+    config.max_iterations.map_or(u64::MAX, |max_iterations| {
+        max_iterations.saturating_sub(iterations)
+    })
+}
+
+// estimates how many more iterations fit in the budget, assuming the search keeps its pace so far
+pub(crate) fn remaining_iterations(
+    iterations_done: u64,
+    elapsed: Duration,
+    max_time: Duration,
+    iterations_left: u64,
+) -> u64 {
+    if iterations_done == 0 || elapsed.is_zero() {
+        return iterations_left;
+    }
+    let time_left = max_time.saturating_sub(elapsed);
+    let by_time =
+        iterations_done as f64 * (time_left.as_secs_f64() / elapsed.as_secs_f64()).min(1e9);
+    by_time.min(iterations_left as f64) as u64
+}
+
+// the index of the first of the largest `values`
+pub(crate) fn index_of_max<T: PartialOrd>(values: impl Iterator<Item = T>) -> usize {
+    let mut best: Option<(usize, T)> = None;
+    for (index, value) in values.enumerate() {
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_value)| value > *best_value)
+        {
+            best = Some((index, value));
+        }
+    }
+    best.map_or(0, |(index, _)| index)
+}
+
+// true if the runner-up could not catch the most visited option
+// even if it received every one of the `remaining` iterations
+pub(crate) fn most_visited_is_decided(visits: impl Iterator<Item = u64>, remaining: u64) -> bool {
+    let mut option_count = 0;
+    let mut best = 0;
+    let mut second = 0;
+    for v in visits {
+        option_count += 1;
+        if v > best {
+            second = best;
+            best = v;
+        } else if v > second {
+            second = v;
+        }
+    }
+    option_count < 2 || best - second > remaining
+}
+
+// turns the per-option weights of `EXP3` (estimated total reward) or `REGRETMATCHING`
+// (cumulative regret) into the probability of selecting each option
+fn policy_probabilities(weights: &mut [f32], config: &MctsConfig) {
+    let option_count = weights.len() as f32;
+    let uniform = config.policy_exploration / option_count;
+    match config.selection_policy {
+        SelectionPolicy::EXP3 => {
+            let eta = config.policy_exploration / option_count;
+            let max_weight = weights.iter().copied().fold(f32::MIN, f32::max);
+            let mut total = 0.0;
+            for w in weights.iter_mut() {
+                *w = (eta * (*w - max_weight)).exp();
+                total += *w;
+            }
+            for w in weights.iter_mut() {
+                *w = (1.0 - config.policy_exploration) * *w / total + uniform;
+            }
+        }
+        SelectionPolicy::REGRETMATCHING => {
+            let positive_total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+            for w in weights.iter_mut() {
+                let matched = if positive_total > 0.0 {
+                    w.max(0.0) / positive_total
+                } else {
+                    1.0 / option_count
+                };
+                *w = (1.0 - config.policy_exploration) * matched + uniform;
+            }
+        }
+        SelectionPolicy::UCB1 | SelectionPolicy::FACTORIZEDUCB1 | SelectionPolicy::PUCT => {
+            weights.fill(1.0 / option_count)
+        }
+    }
+}
+
+// samples an option from the mixed strategy given by `weights`.
+// returns the option's index and the probability it was selected with
+pub(crate) fn sample_from_policy<R: Rng + ?Sized>(
+    weights: impl Iterator<Item = f32>,
+    config: &MctsConfig,
+    rng: &mut R,
+) -> (usize, f32) {
+    let mut probabilities: Vec<f32> = weights.collect();
+    policy_probabilities(&mut probabilities, config);
+    let mut threshold = rng.random::<f32>();
+    for (index, p) in probabilities.iter().enumerate() {
+        threshold -= p;
+        if threshold <= 0.0 {
+            return (index, *p);
+        }
+    }
+    let last = probabilities.len() - 1;
+    (last, probabilities[last])
+}
+
+// the change to an option's weight after the selected option, picked with `probability`,
+// scored `reward`. `EXP3` only credits the selected option with its importance-weighted reward,
+// while `REGRETMATCHING` also charges every option the reward that was actually received
+pub(crate) fn policy_weight_change(
+    selected: bool,
+    reward: f32,
+    probability: f32,
+    config: &MctsConfig,
+) -> f32 {
+    let estimated_reward = if selected { reward / probability } else { 0.0 };
+    match config.selection_policy {
+        SelectionPolicy::EXP3 => estimated_reward,
+        SelectionPolicy::REGRETMATCHING => estimated_reward - reward,
+        SelectionPolicy::UCB1 | SelectionPolicy::FACTORIZEDUCB1 | SelectionPolicy::PUCT => 0.0,
+    }
+}
+
+// the distinct choices of one slot across a side's pairs of slot choices, in order of appearance
+pub(crate) fn slot_choices(
+    options: impl Iterator<Item = (MoveChoice, MoveChoice)>,
+    slot: SlotReference,
+) -> Vec<MoveChoice> {
+    let mut choices = Vec::new();
+    for (slot_a_choice, slot_b_choice) in options {
+        let choice = match slot {
+            SlotReference::SlotA => slot_a_choice,
+            SlotReference::SlotB => slot_b_choice,
+        };
+        if !choices.contains(&choice) {
+            choices.push(choice);
+        }
+    }
+    choices
+}
+
+// the `FACTORIZEDUCB1` selection for one side among `option_count` pairs of slot choices:
+// picks slot A's choice with the highest ucb1, then the pair containing it whose slot B choice
+// has the highest ucb1. only the given pairs are considered, so the rules for which slot choices
+// can be combined still hold. returns the position of the picked pair
+pub(crate) fn select_factorized(
+    option_count: usize,
+    option_at: impl Fn(usize) -> (MoveChoice, MoveChoice),
+    slot_a_ucb1: impl Fn(&MoveChoice) -> f32,
+    slot_b_ucb1: impl Fn(&MoveChoice) -> f32,
+) -> usize {
+    let mut best_slot_a_choice = MoveChoice::None;
+    let mut best_ucb1 = f32::MIN;
+    for position in 0..option_count {
+        let slot_a_choice = option_at(position).0;
+        let this_ucb1 = slot_a_ucb1(&slot_a_choice);
+        if this_ucb1 > best_ucb1 {
+            best_ucb1 = this_ucb1;
+            best_slot_a_choice = slot_a_choice;
+        }
+    }
+
+    let mut choice = 0;
+    let mut best_ucb1 = f32::MIN;
+    for position in 0..option_count {
+        let (slot_a_choice, slot_b_choice) = option_at(position);
+        if slot_a_choice != best_slot_a_choice {
+            continue;
+        }
+        let this_ucb1 = slot_b_ucb1(&slot_b_choice);
+        if this_ucb1 > best_ucb1 {
+            best_ucb1 = this_ucb1;
+            choice = position;
+        }
+    }
+    choice
+}
+
+// maps a position among the open options back to the option's index.
+// `open` is `None` when every option is open
+pub(crate) fn open_option_index(open: Option<&[usize]>, position: usize) -> usize {
+    open.map_or(position, |open| open[position])
+}
+
+// the number of options progressive widening has opened at a node visited `times_visited` times
+pub(crate) fn open_option_count(
+    times_visited: u64,
+    option_count: usize,
+    config: &MctsConfig,
+) -> usize {
+    if !config.progressive_widening {
+        return option_count;
+    }
+    let open =
+        config.widening_constant * (times_visited as f32 + 1.0).powf(config.widening_exponent);
+    (open.ceil() as usize).clamp(1, option_count)
+}
+
+// the score of a leaf from side one's point of view: the result if the battle is over,
+// otherwise the evaluation relative to the root's evaluation
+pub(crate) fn static_score(state: &State, root_eval: f32, config: &MctsConfig) -> f32 {
+    let battle_is_over = state.battle_is_over();
+    if battle_is_over == 0.0 {
+        sigmoid(evaluate(state) - root_eval, config.sigmoid_slope)
+    } else if battle_is_over == -1.0 {
+        0.0
+    } else {
+        battle_is_over
+    }
+}
+
+fn rollout_option<R: Rng + ?Sized>(
+    state: &State,
+    side_ref: SideReference,
+    options: &[(MoveChoice, MoveChoice)],
+    config: &MctsConfig,
+    rng: &mut R,
+) -> (MoveChoice, MoveChoice) {
+    let index = match (config.rollout_policy, &config.rollout_callback) {
+        (RolloutPolicy::GREEDY, _) => {
+            let mut choice = 0;
+            let mut best_prior = f32::MIN;
+            for (index, option) in options.iter().enumerate() {
+                let this_prior = option_prior(state, side_ref, option);
+                if this_prior > best_prior {
+                    best_prior = this_prior;
+                    choice = index;
+                }
+            }
+            choice
+        }
+        (RolloutPolicy::CALLBACK, Some(callback)) => {
+            callback(state, side_ref, options).min(options.len() - 1)
+        }
+        _ => rng.random_range(0..options.len()),
+    };
+    options[index]
+}
+
+// the index of one of `outcomes`, sampled by their percentages
+pub(crate) fn sample_outcome<R: Rng + ?Sized>(
+    outcomes: &[StateInstructions],
+    rng: &mut R,
+) -> usize {
+    let total_weight: f32 = outcomes.iter().map(|x| x.percentage.max(0.0)).sum();
+    let mut threshold = rng.random::<f32>() * total_weight;
+    for (index, outcome) in outcomes.iter().enumerate() {
+        threshold -= outcome.percentage.max(0.0);
+        if threshold <= 0.0 {
+            return index;
+        }
+    }
+    outcomes.len() - 1
+}
+
+// plays out `config.rollout_turns` turns from `state` and scores where they end up.
+// `state` is left as it was
+fn playout<R: Rng + ?Sized>(
+    state: &mut State,
+    root_eval: f32,
+    move_options: &mut MoveOptions,
+    config: &MctsConfig,
+    rng: &mut R,
+) -> f32 {
+    let mut applied = Vec::with_capacity(config.rollout_turns as usize);
+    for _ in 0..config.rollout_turns {
+        if state.battle_is_over() != 0.0 {
+            break;
+        }
+        state.get_all_options(move_options);
+        let s1_move = rollout_option(
+            state,
+            SideReference::SideOne,
+            &move_options.side_one_combined_options,
+            config,
+            rng,
+        );
+        let s2_move = rollout_option(
+            state,
+            SideReference::SideTwo,
+            &move_options.side_two_combined_options,
+            config,
+            rng,
+        );
+        move_options.side_one_combined_options.clear();
+        move_options.side_two_combined_options.clear();
+
+        let mut outcomes = generate_instructions_from_move_pair(
+            state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, false,
+        );
+        let outcome = outcomes.swap_remove(sample_outcome(&outcomes, rng));
+        state.apply_instructions(&outcome.instruction_list);
+        applied.push(outcome);
+    }
+
+    let score = static_score(state, root_eval, config);
+    for outcome in applied.iter().rev() {
+        state.reverse_instructions(&outcome.instruction_list);
+    }
+    score
+}
+
+// the score backpropagated from a leaf, using `config.rollout_policy`
+pub(crate) fn rollout_score<R: Rng + ?Sized>(
+    state: &mut State,
+    root_eval: f32,
+    move_options: &mut MoveOptions,
+    config: &MctsConfig,
+    rng: &mut R,
+) -> f32 {
+    let static_score = static_score(state, root_eval, config);
+    if config.effective_rollout_policy() == RolloutPolicy::STATIC || state.battle_is_over() != 0.0 {
+        return static_score;
+    }
+    let rollout_score = playout(state, root_eval, move_options, config, rng);
+    config.rollout_mix * rollout_score + (1.0 - config.rollout_mix) * static_score
+}
+
+// the fixed-point steps a score of 1 is split into by `ScoreSum`. a u64 holds the scores of
+// more than a trillion visits at this scale
+const SCORE_SCALE: f64 = (1u64 << 24) as f64;
+
+/// A total of scores between 0 and 1, kept in fixed point.
+///
+/// Every score adds the same integer no matter how large the total already is, so unlike an
+/// `f32` total it keeps updating, and averages taken from it stay accurate, however many visits
+/// a search makes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScoreSum(pub(crate) u64);
+
+impl ScoreSum {
+    pub(crate) fn fixed_point(score: f32) -> u64 {
+        (score as f64 * SCORE_SCALE).round() as u64
+    }
+
+    pub fn add(&mut self, score: f32) {
+        self.0 += Self::fixed_point(score);
+    }
+
+    pub fn total(&self) -> f64 {
+        self.0 as f64 / SCORE_SCALE
+    }
+
+    /// The average of `visits` scores, 0 if there are none
+    pub fn average(&self, visits: u64) -> f32 {
+        if visits == 0 {
+            return 0.0;
+        }
+        (self.total() / visits as f64) as f32
+    }
+}
+
+// a cheap estimate of how good one slot's choice is: the expected base power of a damaging move
+// scaled by STAB and type effectiveness. hitting an ally counts against the choice
+fn slot_choice_prior(
+    state: &State,
+    side_ref: SideReference,
+    slot_ref: &SlotReference,
+    choice: &MoveChoice,
+) -> f32 {
+    let (target_slot, target_side, move_index) = match choice {
+        MoveChoice::Move(target_slot, target_side, move_index)
+        | MoveChoice::MoveTera(target_slot, target_side, move_index)
+        | MoveChoice::MoveMega(target_slot, target_side, move_index)
+        | MoveChoice::MoveDynamax(target_slot, target_side, move_index)
+        | MoveChoice::MoveZ(target_slot, target_side, move_index) => {
+            (target_slot, target_side, move_index)
+        }
+        MoveChoice::Switch(_) => return 20.0,
+        _ => return 0.0,
+    };
+    let (side, opponent) = state.get_both_sides_immutable(side_ref);
+    let attacker = side.get_active_immutable(slot_ref);
+    let choice = &attacker.moves[move_index].choice;
+    if choice.category == MoveCategory::Status {
+        return 40.0;
+    }
+
+    let stab = if attacker.has_type(&choice.move_type) {
+        1.5
+    } else {
+        1.0
+    };
+    let effectiveness = match choice.move_choice_target {
+        MoveChoiceTarget::AllFoes | MoveChoiceTarget::AllOther => {
+            [SlotReference::SlotA, SlotReference::SlotB]
+                .iter()
+                .map(|slot| opponent.get_active_immutable(slot))
+                .filter(|pkmn| pkmn.hp > 0)
+                .map(|pkmn| type_effectiveness_modifier(&choice.move_type, pkmn))
+                .sum()
+        }
+        _ if *target_side == side_ref => -1.0,
+        _ => type_effectiveness_modifier(
+            &choice.move_type,
+            opponent.get_active_immutable(target_slot),
+        ),
+    };
+    choice.base_power * choice.accuracy / 100.0 * stab * effectiveness
+}
+
+fn option_prior(state: &State, side_ref: SideReference, option: &(MoveChoice, MoveChoice)) -> f32 {
+    slot_choice_prior(state, side_ref, &SlotReference::SlotA, &option.0)
+        + slot_choice_prior(state, side_ref, &SlotReference::SlotB, &option.1)
+}
+
+// `slot`'s prior for each of `choices` from `callback`, normalized over `choices`
+fn slot_priors(
+    state: &State,
+    side_ref: SideReference,
+    slot: SlotReference,
+    choices: &[MoveChoice],
+    callback: &PriorCallback,
+) -> Vec<f32> {
+    let listed = callback(state, side_ref, slot);
+    let mut priors: Vec<f32> = choices
+        .iter()
+        .map(|choice| {
+            listed
+                .iter()
+                .filter(|(x, _)| x == choice)
+                .map(|(_, p)| p.max(0.0))
+                .sum()
+        })
+        .collect();
+    let total: f32 = priors.iter().sum();
+    if total > 0.0 {
+        priors.iter_mut().for_each(|p| *p /= total);
+    } else {
+        priors.fill(1.0 / choices.len() as f32);
+    }
+    priors
+}
+
+/// A `PriorCallback` from usage data: how often each pokemon uses each of its moves, e.g.
+/// Incineroar using Fake Out on 80% of its turns. A move's usage is split evenly between the
+/// choices that use it, one per target and per gimmick such as terastallizing. Choices the
+/// table does not list, such as switching, get no prior
+pub fn usage_prior_callback(usage: HashMap<PokemonName, HashMap<Choices, f32>>) -> PriorCallback {
+    Arc::new(move |state, side_ref, slot_ref| {
+        let side = &state.sides[side_ref as usize];
+        let move_usage = match usage.get(&side.get_active_immutable(&slot_ref).id) {
+            Some(move_usage) => move_usage,
+            None => return Vec::new(),
+        };
+        let mut move_options = MoveOptions::new();
+        state.get_all_options(&mut move_options);
+        let options = match side_ref {
+            SideReference::SideOne => &move_options.side_one_combined_options,
+            SideReference::SideTwo => &move_options.side_two_combined_options,
+        };
+        let choices = slot_choices(options.iter().copied(), slot_ref);
+        let move_index = |choice: &MoveChoice| match choice {
+            MoveChoice::Move(_, _, index)
+            | MoveChoice::MoveTera(_, _, index)
+            | MoveChoice::MoveMega(_, _, index)
+            | MoveChoice::MoveDynamax(_, _, index)
+            | MoveChoice::MoveZ(_, _, index) => Some(*index),
+            MoveChoice::Switch(_) | MoveChoice::TeamPreview(_, _) | MoveChoice::None => None,
+        };
+        let active = side.get_active_immutable(&slot_ref);
+        choices
+            .iter()
+            .filter_map(|choice| {
+                let index = move_index(choice)?;
+                let usage = move_usage.get(&active.moves[&index].id)?;
+                let targets = choices
+                    .iter()
+                    .filter(|x| move_index(x) == Some(index))
+                    .count();
+                Some((*choice, usage / targets as f32))
+            })
+            .collect()
+    })
+}
+
+// the prior of each of a side's options: the product of its slot choices' priors from
+// `MctsConfig::prior_callback`, normalized and mixed with `policy_exploration` of a uniform prior.
+// every option gets the same prior without a callback
+pub(crate) fn option_priors(
+    state: &State,
+    side_ref: SideReference,
+    options: &[(MoveChoice, MoveChoice)],
+    config: &MctsConfig,
+) -> Vec<f32> {
+    let uniform = 1.0 / options.len() as f32;
+    let callback = match &config.prior_callback {
+        Some(callback) => callback,
+        None => return vec![uniform; options.len()],
+    };
+    let slot_a_choices = slot_choices(options.iter().copied(), SlotReference::SlotA);
+    let slot_b_choices = slot_choices(options.iter().copied(), SlotReference::SlotB);
+    let slot_a_priors = slot_priors(
+        state,
+        side_ref,
+        SlotReference::SlotA,
+        &slot_a_choices,
+        callback,
+    );
+    let slot_b_priors = slot_priors(
+        state,
+        side_ref,
+        SlotReference::SlotB,
+        &slot_b_choices,
+        callback,
+    );
+    let slot_prior = |choices: &[MoveChoice], priors: &[f32], choice: &MoveChoice| {
+        choices
+            .iter()
+            .position(|x| x == choice)
+            .map_or(0.0, |index| priors[index])
+    };
+    let mut priors: Vec<f32> = options
+        .iter()
+        .map(|(slot_a, slot_b)| {
+            slot_prior(&slot_a_choices, &slot_a_priors, slot_a)
+                * slot_prior(&slot_b_choices, &slot_b_priors, slot_b)
+        })
+        .collect();
+    let total: f32 = priors.iter().sum();
+    for p in priors.iter_mut() {
+        let joint = if total > 0.0 { *p / total } else { uniform };
+        *p = (1.0 - config.policy_exploration) * joint + config.policy_exploration * uniform;
+    }
+    priors
+}
+
+// the indices of a side's options ordered by their prior, most promising first.
+// this is the order progressive widening opens them in. the priors are from
+// `MctsConfig::prior_callback` when there is one, otherwise from `option_prior`
+pub(crate) fn prior_order(
+    state: &State,
+    side_ref: SideReference,
+    options: &[(MoveChoice, MoveChoice)],
+    config: &MctsConfig,
+) -> Vec<usize> {
+    let priors: Vec<f32> = if config.prior_callback.is_some() {
+        option_priors(state, side_ref, options, config)
+    } else {
+        options
+            .iter()
+            .map(|option| option_prior(state, side_ref, option))
+            .collect()
+    };
+    let mut order: Vec<usize> = (0..priors.len()).collect();
+    order.sort_by(|a, b| priors[*b].total_cmp(&priors[*a]));
+    order
+}
+
+// regret matching+: play in proportion to the positive regrets, or uniformly if there are none
+fn regret_matching_strategy(regrets: &[f64], strategy: &mut [f64]) {
+    let positive_sum: f64 = regrets.iter().map(|x| x.max(0.0)).sum();
+    for (p, regret) in strategy.iter_mut().zip(regrets) {
+        *p = if positive_sum > 0.0 {
+            regret.max(0.0) / positive_sum
+        } else {
+            1.0 / regrets.len() as f64
+        };
+    }
+}
+
+fn update_regrets(regrets: &mut [f64], values: &[f64], strategy: &[f64]) {
+    let expected: f64 = values.iter().zip(strategy).map(|(v, p)| v * p).sum();
+    for (regret, value) in regrets.iter_mut().zip(values) {
+        *regret = (*regret + value - expected).max(0.0);
+    }
+}
+
+fn normalized(weights: Vec<f64>) -> Vec<f32> {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return vec![1.0 / weights.len() as f32; weights.len()];
+    }
+    weights.iter().map(|x| (x / total) as f32).collect()
+}
+
+/// Approximates a Nash equilibrium of the zero-sum game where `payoffs[i][j]` is side one's
+/// score when it plays option `i` against side two's option `j`, and side two scores
+/// `1.0 - payoffs[i][j]`.
+///
+/// Both sides run regret matching+ against each other for `iterations` rounds, and the
+/// returned strategies are their linearly weighted average strategies
+pub fn solve_matrix_game(payoffs: &[Vec<f32>], iterations: u32) -> (Vec<f32>, Vec<f32>) {
+    let rows = payoffs.len();
+    let columns = payoffs.first().map_or(0, |row| row.len());
+    if rows == 0 || columns == 0 {
+        return (vec![], vec![]);
+    }
+
+    let mut s1_regrets = vec![0.0; rows];
+    let mut s2_regrets = vec![0.0; columns];
+    let mut s1_strategy = vec![0.0; rows];
+    let mut s2_strategy = vec![0.0; columns];
+    let mut s1_values = vec![0.0; rows];
+    let mut s2_values = vec![0.0; columns];
+    let mut s1_average = vec![0.0; rows];
+    let mut s2_average = vec![0.0; columns];
+    for t in 1..=iterations {
+        regret_matching_strategy(&s1_regrets, &mut s1_strategy);
+        regret_matching_strategy(&s2_regrets, &mut s2_strategy);
+
+        for (value, row) in s1_values.iter_mut().zip(payoffs) {
+            *value = row
+                .iter()
+                .zip(&s2_strategy)
+                .map(|(payoff, q)| *payoff as f64 * q)
+                .sum();
+        }
+        for (j, value) in s2_values.iter_mut().enumerate() {
+            *value = payoffs
+                .iter()
+                .zip(&s1_strategy)
+                .map(|(row, p)| (1.0 - row[j] as f64) * p)
+                .sum();
+        }
+        update_regrets(&mut s1_regrets, &s1_values, &s1_strategy);
+        update_regrets(&mut s2_regrets, &s2_values, &s2_strategy);
+
+        for (average, p) in s1_average.iter_mut().zip(&s1_strategy) {
+            *average += t as f64 * p;
+        }
+        for (average, q) in s2_average.iter_mut().zip(&s2_strategy) {
+            *average += t as f64 * q;
+        }
+    }
+
+    (normalized(s1_average), normalized(s2_average))
+}
+
+// fills in `nash_probability` for both sides from the empirical payoff matrix at the root.
+// `pair_stats(i, j)` is the total score (for side one) and the visits of side one's option `i`
+// played against side two's option `j`. options that were never visited are left out of the
+// game, and a pair of visited options that was never played together is estimated from the
+// average scores of the two options
+pub(crate) fn solve_root_nash(
+    result: &mut MctsResult,
+    pair_stats: impl Fn(usize, usize) -> (ScoreSum, u64),
+    iterations: u32,
+) {
+    let s1_visited: Vec<usize> = (0..result.s1.len())
+        .filter(|&i| result.s1[i].visits > 0)
+        .collect();
+    let s2_visited: Vec<usize> = (0..result.s2.len())
+        .filter(|&j| result.s2[j].visits > 0)
+        .collect();
+    if s1_visited.is_empty() || s2_visited.is_empty() {
+        return;
+    }
+
+    let payoffs: Vec<Vec<f32>> = s1_visited
+        .iter()
+        .map(|&i| {
+            s2_visited
+                .iter()
+                .map(|&j| match pair_stats(i, j) {
+                    (total_score, visits) if visits > 0 => total_score.average(visits),
+                    _ => 0.5 * (result.s1[i].average_score() + 1.0 - result.s2[j].average_score()),
+                })
+                .collect()
+        })
+        .collect();
+    let (s1_strategy, s2_strategy) = solve_matrix_game(&payoffs, iterations);
+
+    for x in result.s1.iter_mut().chain(result.s2.iter_mut()) {
+        x.nash_probability = Some(0.0);
+    }
+    for (&i, p) in s1_visited.iter().zip(s1_strategy) {
+        result.s1[i].nash_probability = Some(p);
+    }
+    for (&j, q) in s2_visited.iter().zip(s2_strategy) {
+        result.s2[j].nash_probability = Some(q);
+    }
 }
 
 #[derive(Debug)]
 pub struct Node {
     pub root: bool,
     pub depth: u8,
-    pub parent: *mut Node,
-    pub times_visited: u32,
+
+    // the parent's index in the tree's arena, `None` at the root
+    pub parent: Option<usize>,
+    pub times_visited: u64,
+
+    // side one's total score over every visit to this node
+    pub total_score: ScoreSum,
+
+    // the option indices picked for each side the last time this node was selected through,
+    // and the probabilities they were picked with
+    pub selected: (usize, usize),
+    pub selected_probabilities: (f32, f32),
+
+    // only used by the `FACTORIZEDUCB1` selection policy, created the first time it selects here
+    pub slot_options: Option<Box<SlotOptions>>,
+
+    // the order progressive widening opens each side's options in, created the first time
+    // it selects here
+    pub widening_order: Option<Box<(Vec<usize>, Vec<usize>)>>,
+
+    // each side's option priors, only used by the `PUCT` selection policy and created the
+    // first time it selects here
+    pub priors: Option<Box<(Vec<f32>, Vec<f32>)>>,
 
     // represents the instructions that led to this node from the parent
     pub instructions: StateInstructions,
 
+    // the node holding this node's statistics, options and children when another node reached
+    // the same state first, see `MctsConfig::transposition_table`
+    pub transposition: Option<usize>,
+
     // represents the total score and number of visits for this node
     // de-coupled for s1 and s2
     pub s1_options: Option<Vec<MoveNode>>,
@@ -37,14 +1062,21 @@ impl Node {
         Node {
             root: false,
             depth,
-            parent: std::ptr::null_mut(),
+            parent: None,
             instructions: StateInstructions::default(),
+            transposition: None,
             times_visited: 0,
+            total_score: ScoreSum::default(),
+            selected: (0, 0),
+            selected_probabilities: (1.0, 1.0),
+            slot_options: None,
+            widening_order: None,
+            priors: None,
             s1_options: None,
             s2_options: None,
         }
     }
-    unsafe fn populate(
+    fn populate(
         &mut self,
         s1_options: &mut Vec<(MoveChoice, MoveChoice)>,
         s2_options: &mut Vec<(MoveChoice, MoveChoice)>,
@@ -53,16 +1085,18 @@ impl Node {
             .drain(..)
             .map(|x| MoveNode {
                 move_choice: x,
-                total_score: 0.0,
+                total_score: ScoreSum::default(),
                 visits: 0,
+                policy_weight: 0.0,
             })
             .collect();
         let s2_options_vec = s2_options
             .drain(..)
             .map(|x| MoveNode {
                 move_choice: x,
-                total_score: 0.0,
+                total_score: ScoreSum::default(),
                 visits: 0,
+                policy_weight: 0.0,
             })
             .collect();
 
@@ -70,368 +1104,1691 @@ impl Node {
         self.s2_options = Some(s2_options_vec);
     }
 
-    unsafe fn should_branch_on_damage(&self) -> bool {
-        if self.root {
-            return true;
-        }
+    // the bytes this node's options take up outside of the node itself
+    fn options_heap_usage(&self) -> usize {
+        [&self.s1_options, &self.s2_options]
+            .iter()
+            .map(|options| options.as_ref().map_or(0, |x| x.capacity()))
+            .sum::<usize>()
+            * size_of::<MoveNode>()
+    }
 
-        // if there aren't many options left, branch on damage if we're one node below the root
-        if (*self.parent).root && self.s1_options.as_ref().unwrap().len() < 20
-            || self.s2_options.as_ref().unwrap().len() < 20
-        {
-            return true;
-        }
-        false
+    // the bytes this node's instructions and options take up outside of the node itself
+    fn heap_usage(&self) -> usize {
+        self.instructions.instruction_list.capacity() * size_of::<Instruction>()
+            + self.options_heap_usage()
+    }
+
+    // side one's average score over every visit to this node
+    fn average_score(&self) -> f32 {
+        self.total_score.average(self.times_visited)
     }
 
-    pub fn maximize_ucb_for_side(&self, side_map: &[MoveNode]) -> usize {
+    pub fn maximize_ucb_for_side(&self, side_map: &[MoveNode], exploration_constant: f32) -> usize {
         let mut choice = 0;
         let mut best_ucb1 = f32::MIN;
         for (index, node) in side_map.iter().enumerate() {
-            let this_ucb1 = node.ucb1(self.times_visited);
+            let this_ucb1 = node.ucb1(self.times_visited, exploration_constant);
             if this_ucb1 > best_ucb1 {
                 best_ucb1 = this_ucb1;
                 choice = index;
             }
         }
-        choice
+        choice
+    }
+
+    // the options progressive widening has opened for each side, most promising first.
+    // `None` when progressive widening is off and every option is open
+    fn open_options(&self, config: &MctsConfig) -> (Option<&[usize]>, Option<&[usize]>) {
+        match &self.widening_order {
+            Some(order) if config.progressive_widening => {
+                let (s1_order, s2_order) = order.as_ref();
+                (
+                    Some(
+                        &s1_order[..open_option_count(self.times_visited, s1_order.len(), config)],
+                    ),
+                    Some(
+                        &s2_order[..open_option_count(self.times_visited, s2_order.len(), config)],
+                    ),
+                )
+            }
+            _ => (None, None),
+        }
+    }
+
+    // the option among `open` (every option when `None`) with the highest puct score
+    fn maximize_puct_among(
+        &self,
+        side_map: &[MoveNode],
+        priors: &[f32],
+        open: Option<&[usize]>,
+        puct_constant: f32,
+    ) -> usize {
+        let mut choice = open_option_index(open, 0);
+        let mut best_puct = f32::MIN;
+        for position in 0..open.map_or(side_map.len(), |open| open.len()) {
+            let index = open_option_index(open, position);
+            let this_puct = side_map[index].puct(priors[index], self.times_visited, puct_constant);
+            if this_puct > best_puct {
+                best_puct = this_puct;
+                choice = index;
+            }
+        }
+        choice
+    }
+
+    fn maximize_ucb_among(
+        &self,
+        side_map: &[MoveNode],
+        open: &[usize],
+        exploration_constant: f32,
+    ) -> usize {
+        let mut choice = open[0];
+        let mut best_ucb1 = f32::MIN;
+        for index in open.iter().copied() {
+            let this_ucb1 = side_map[index].ucb1(self.times_visited, exploration_constant);
+            if this_ucb1 > best_ucb1 {
+                best_ucb1 = this_ucb1;
+                choice = index;
+            }
+        }
+        choice
+    }
+
+    fn select_move_index(
+        options: &mut Vec<MoveNode>,
+        times_visited: u64,
+        times_visited_usize: usize,
+        config: &MctsConfig,
+    ) -> usize {
+        if options.len() * config.min_visits_before_selection == times_visited_usize {
+            options.sort_by(|a, b| b.total_score.cmp(&a.total_score));
+        }
+
+        if options.len() * config.min_visits_before_selection > times_visited_usize {
+            times_visited_usize % options.len()
+        } else {
+            let first_item = &options[0];
+            let first_item_ucb1 = first_item.ucb1(times_visited, config.exploration_constant);
+
+            let mut index = 1;
+            for i in 1..options.len() {
+                if first_item_ucb1 >= options[i].ucb1(times_visited, config.exploration_constant) {
+                    break;
+                }
+                index += 1;
+            }
+            index -= 1;
+
+            if index > 0 {
+                if index == 1 {
+                    options.swap(0, 1);
+                } else {
+                    let first = options.remove(0);
+                    options.insert(index, first);
+                }
+            }
+            0
+        }
+    }
+
+    // picks the option each side plays from this node, whose options must be populated
+    pub fn select_options<R: Rng + ?Sized>(
+        &mut self,
+        state: &State,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> (usize, usize) {
+        if config.progressive_widening && self.widening_order.is_none() {
+            let s1_moves = move_choices(self.s1_options.as_ref().unwrap());
+            let s2_moves = move_choices(self.s2_options.as_ref().unwrap());
+            let s1_order = prior_order(state, SideReference::SideOne, &s1_moves, config);
+            let s2_order = prior_order(state, SideReference::SideTwo, &s2_moves, config);
+            self.widening_order = Some(Box::new((s1_order, s2_order)));
+        }
+        if config.selection_policy == SelectionPolicy::PUCT && self.priors.is_none() {
+            let s1_moves = move_choices(self.s1_options.as_ref().unwrap());
+            let s2_moves = move_choices(self.s2_options.as_ref().unwrap());
+            let s1_priors = option_priors(state, SideReference::SideOne, &s1_moves, config);
+            let s2_priors = option_priors(state, SideReference::SideTwo, &s2_moves, config);
+            self.priors = Some(Box::new((s1_priors, s2_priors)));
+        }
+
+        let (s1_mc_index, s2_mc_index) = match config.selection_policy {
+            SelectionPolicy::UCB1 if config.progressive_widening => {
+                let (s1_open, s2_open) = self.open_options(config);
+                (
+                    self.maximize_ucb_among(
+                        self.s1_options.as_ref().unwrap(),
+                        s1_open.unwrap(),
+                        config.exploration_constant,
+                    ),
+                    self.maximize_ucb_among(
+                        self.s2_options.as_ref().unwrap(),
+                        s2_open.unwrap(),
+                        config.exploration_constant,
+                    ),
+                )
+            }
+            SelectionPolicy::PUCT => {
+                let (s1_open, s2_open) = self.open_options(config);
+                let (s1_priors, s2_priors) = self.priors.as_deref().unwrap();
+                (
+                    self.maximize_puct_among(
+                        self.s1_options.as_ref().unwrap(),
+                        s1_priors,
+                        s1_open,
+                        config.puct_constant,
+                    ),
+                    self.maximize_puct_among(
+                        self.s2_options.as_ref().unwrap(),
+                        s2_priors,
+                        s2_open,
+                        config.puct_constant,
+                    ),
+                )
+            }
+            SelectionPolicy::UCB1 => {
+                let times_visited_usize = self.times_visited as usize;
+                (
+                    Self::select_move_index(
+                        self.s1_options.as_mut().unwrap(),
+                        self.times_visited,
+                        times_visited_usize,
+                        config,
+                    ),
+                    Self::select_move_index(
+                        self.s2_options.as_mut().unwrap(),
+                        self.times_visited,
+                        times_visited_usize,
+                        config,
+                    ),
+                )
+            }
+            SelectionPolicy::FACTORIZEDUCB1 => {
+                let s1_options = self.s1_options.as_ref().unwrap();
+                let s2_options = self.s2_options.as_ref().unwrap();
+                if self.slot_options.is_none() {
+                    self.slot_options = Some(Box::new(SlotOptions::new(s1_options, s2_options)));
+                }
+                let slots = self.slot_options.as_ref().unwrap();
+                let (s1_open, s2_open) = self.open_options(config);
+                let slot_ucb1 = |slot_nodes: &[MoveNode], choice: &MoveChoice| {
+                    SlotOptions::slot_node(slot_nodes, choice)
+                        .ucb1(self.times_visited, config.exploration_constant)
+                };
+                let s1_position = select_factorized(
+                    s1_open.map_or(s1_options.len(), |open| open.len()),
+                    |position| s1_options[open_option_index(s1_open, position)].move_choice,
+                    |choice| slot_ucb1(&slots.s1_slot_a, choice),
+                    |choice| slot_ucb1(&slots.s1_slot_b, choice),
+                );
+                let s2_position = select_factorized(
+                    s2_open.map_or(s2_options.len(), |open| open.len()),
+                    |position| s2_options[open_option_index(s2_open, position)].move_choice,
+                    |choice| slot_ucb1(&slots.s2_slot_a, choice),
+                    |choice| slot_ucb1(&slots.s2_slot_b, choice),
+                );
+                (
+                    open_option_index(s1_open, s1_position),
+                    open_option_index(s2_open, s2_position),
+                )
+            }
+            _ => {
+                let (s1_index, s1_probability) = sample_from_policy(
+                    self.s1_options
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|x| x.policy_weight),
+                    config,
+                    rng,
+                );
+                let (s2_index, s2_probability) = sample_from_policy(
+                    self.s2_options
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|x| x.policy_weight),
+                    config,
+                    rng,
+                );
+                self.selected_probabilities = (s1_probability, s2_probability);
+                (s1_index, s2_index)
+            }
+        };
+        self.selected = (s1_mc_index, s2_mc_index);
+        (s1_mc_index, s2_mc_index)
+    }
+
+    fn should_not_expand(
+        &self,
+        state: &State,
+        s1_move: &(MoveChoice, MoveChoice),
+        s2_move: &(MoveChoice, MoveChoice),
+    ) -> bool {
+        if (state.battle_is_over() != 0.0 && !self.root)
+            || (s1_move == &(MoveChoice::None, MoveChoice::None)
+                && s2_move == &(MoveChoice::None, MoveChoice::None))
+        {
+            true
+        } else {
+            false
+        }
+    }
+
+    // updates the option pair that was selected at `parent` with a visit scored `score`
+    fn update_parent(parent: &mut Node, score: f32, config: &MctsConfig) {
+        let (s1_choice_index, s2_choice_index) = match config.selection_policy {
+            SelectionPolicy::UCB1 if !config.progressive_widening => {
+                let min_visits_before_selection = config.min_visits_before_selection as u64;
+                let s1_choice_index = if parent.times_visited >= min_visits_before_selection {
+                    0
+                } else {
+                    parent.times_visited as usize % parent.s1_options.as_ref().unwrap().len()
+                };
+                let s2_choice_index = if parent.times_visited >= min_visits_before_selection {
+                    0
+                } else {
+                    parent.times_visited as usize % parent.s2_options.as_ref().unwrap().len()
+                };
+                (s1_choice_index, s2_choice_index)
+            }
+            SelectionPolicy::UCB1 | SelectionPolicy::PUCT => parent.selected,
+            SelectionPolicy::FACTORIZEDUCB1 => {
+                let (s1_index, s2_index) = parent.selected;
+                let s1_move = parent.s1_options.as_ref().unwrap()[s1_index].move_choice;
+                let s2_move = parent.s2_options.as_ref().unwrap()[s2_index].move_choice;
+                parent
+                    .slot_options
+                    .as_mut()
+                    .unwrap()
+                    .add_result(&s1_move, &s2_move, score);
+                (s1_index, s2_index)
+            }
+            SelectionPolicy::EXP3 | SelectionPolicy::REGRETMATCHING => {
+                let (s1_probability, s2_probability) = parent.selected_probabilities;
+                let (s1_index, s2_index) = parent.selected;
+                for (index, x) in parent.s1_options.as_mut().unwrap().iter_mut().enumerate() {
+                    x.policy_weight +=
+                        policy_weight_change(index == s1_index, score, s1_probability, config);
+                }
+                for (index, x) in parent.s2_options.as_mut().unwrap().iter_mut().enumerate() {
+                    x.policy_weight += policy_weight_change(
+                        index == s2_index,
+                        1.0 - score,
+                        s2_probability,
+                        config,
+                    );
+                }
+                (s1_index, s2_index)
+            }
+        };
+
+        let parent_s1_movenode = &mut parent.s1_options.as_mut().unwrap()[s1_choice_index];
+        parent_s1_movenode.total_score.add(score);
+        parent_s1_movenode.visits += 1;
+
+        let parent_s2_movenode = &mut parent.s2_options.as_mut().unwrap()[s2_choice_index];
+        parent_s2_movenode.total_score.add(1.0 - score);
+        parent_s2_movenode.visits += 1;
+    }
+
+    pub fn rollout<R: Rng + ?Sized>(
+        &mut self,
+        state: &mut State,
+        root_eval: &f32,
+        move_options: &mut MoveOptions,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> f32 {
+        rollout_score(state, *root_eval, move_options, config, rng)
+    }
+}
+
+fn move_choices(options: &[MoveNode]) -> Vec<(MoveChoice, MoveChoice)> {
+    options.iter().map(|x| x.move_choice).collect()
+}
+
+// the index of one of the nodes in `branch`, sampled by the percentage of its instructions
+fn sample_node<R: Rng + ?Sized>(branch: &[Node], rng: &mut R) -> usize {
+    let weights: Vec<f64> = branch
+        .iter()
+        .map(|x| x.instructions.percentage as f64)
+        .collect();
+    let dist = WeightedIndex::new(weights).unwrap();
+    dist.sample(rng)
+}
+
+#[derive(Debug)]
+pub struct MoveNode {
+    pub move_choice: (MoveChoice, MoveChoice),
+    pub total_score: ScoreSum,
+    pub visits: u64,
+
+    // only used by the `EXP3` and `REGRETMATCHING` selection policies
+    pub policy_weight: f32,
+}
+
+impl MoveNode {
+    pub fn to_u16(&self) -> u16 {
+        move_pair_key(&self.move_choice)
+    }
+    pub fn ucb1(&self, parent_visits: u64, exploration_constant: f32) -> f32 {
+        if self.visits == 0 {
+            return f32::INFINITY;
+        }
+        let score = self.average_score()
+            + (exploration_constant * (parent_visits as f32).ln() / self.visits as f32).sqrt();
+        score
+    }
+    // an unvisited option's average score counts as a draw
+    pub fn puct(&self, prior: f32, parent_visits: u64, puct_constant: f32) -> f32 {
+        let average_score = if self.visits == 0 {
+            0.5
+        } else {
+            self.average_score()
+        };
+        average_score
+            + puct_constant * prior * ((parent_visits + 1) as f32).sqrt() / (self.visits + 1) as f32
+    }
+    pub fn average_score(&self) -> f32 {
+        self.total_score.average(self.visits)
+    }
+}
+
+/// Statistics for each slot's choices at a node, shared by every pair of slot choices the
+/// choice is part of. Each slot's choice is kept in a `MoveNode` with the other slot set to
+/// `MoveChoice::None`
+#[derive(Debug)]
+pub struct SlotOptions {
+    pub s1_slot_a: Vec<MoveNode>,
+    pub s1_slot_b: Vec<MoveNode>,
+    pub s2_slot_a: Vec<MoveNode>,
+    pub s2_slot_b: Vec<MoveNode>,
+}
+
+impl SlotOptions {
+    fn new(s1_options: &[MoveNode], s2_options: &[MoveNode]) -> SlotOptions {
+        let slot_nodes = |options: &[MoveNode], slot: SlotReference| -> Vec<MoveNode> {
+            slot_choices(options.iter().map(|x| x.move_choice), slot)
+                .into_iter()
+                .map(|choice| MoveNode {
+                    move_choice: (choice, MoveChoice::None),
+                    total_score: ScoreSum::default(),
+                    visits: 0,
+                    policy_weight: 0.0,
+                })
+                .collect()
+        };
+        SlotOptions {
+            s1_slot_a: slot_nodes(s1_options, SlotReference::SlotA),
+            s1_slot_b: slot_nodes(s1_options, SlotReference::SlotB),
+            s2_slot_a: slot_nodes(s2_options, SlotReference::SlotA),
+            s2_slot_b: slot_nodes(s2_options, SlotReference::SlotB),
+        }
+    }
+
+    fn slot_node<'a>(slot_nodes: &'a [MoveNode], choice: &MoveChoice) -> &'a MoveNode {
+        slot_nodes
+            .iter()
+            .find(|x| &x.move_choice.0 == choice)
+            .expect("every slot choice has a slot node")
+    }
+
+    fn add_result(
+        &mut self,
+        s1_move: &(MoveChoice, MoveChoice),
+        s2_move: &(MoveChoice, MoveChoice),
+        score: f32,
+    ) {
+        let results = [
+            (&mut self.s1_slot_a, s1_move.0, score),
+            (&mut self.s1_slot_b, s1_move.1, score),
+            (&mut self.s2_slot_a, s2_move.0, 1.0 - score),
+            (&mut self.s2_slot_b, s2_move.1, 1.0 - score),
+        ];
+        for (slot_nodes, choice, score) in results {
+            let slot_node = slot_nodes
+                .iter_mut()
+                .find(|x| x.move_choice.0 == choice)
+                .expect("every slot choice has a slot node");
+            slot_node.total_score.add(score);
+            slot_node.visits += 1;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MctsSideResult {
+    pub move_choice: (MoveChoice, MoveChoice),
+    pub total_score: f32,
+
+    /// the number of times the search played this option. `solve_endgame` plays none, so the
+    /// visits of its results are synthetic, see `solve_endgame`
+    pub visits: u64,
+
+    /// the probability of playing this option in the approximate Nash equilibrium at the root.
+    /// `None` unless `MctsConfig::nash_iterations` is set or the result is from `solve_endgame`
+    pub nash_probability: Option<f32>,
+}
+
+impl MctsSideResult {
+    pub fn average_score(&self) -> f32 {
+        if self.visits == 0 {
+            return 0.0;
+        }
+        let score = self.total_score / self.visits as f32;
+        score
+    }
+}
+
+/// One ply of the line the search expects to be played, see
+/// `MctsConfig::principal_variation_plies`
+#[derive(Clone)]
+pub struct PrincipalVariationPly {
+    pub s1_move: (MoveChoice, MoveChoice),
+    pub s2_move: (MoveChoice, MoveChoice),
+
+    /// the most likely outcome of `s1_move` and `s2_move`
+    pub instructions: StateInstructions,
+
+    /// the visits to the node that outcome leads to and side one's average score there
+    pub visits: u64,
+    pub average_score: f32,
+}
+
+#[derive(Clone)]
+pub struct MctsResult {
+    pub s1: Vec<MctsSideResult>,
+    pub s2: Vec<MctsSideResult>,
+    pub iteration_count: u64,
+
+    /// empty unless `MctsConfig::principal_variation_plies` is set
+    pub principal_variation: Vec<PrincipalVariationPly>,
+
+    /// the most turns below the root that an iteration of this search ended its selection on,
+    /// see `child_depth`
+    pub max_leaf_depth: u8,
+
+    /// the average number of turns below the root that this search's iterations ended their
+    /// selection on
+    pub average_leaf_depth: f32,
+}
+
+// the root is always the first node in `MctsTree::nodes`
+const ROOT_INDEX: usize = 0;
+
+// keyed by the parent's index in the arena and the pair of options, holding the range of the
+// arena the outcomes of that pair were allocated in
+type ChildMap = HashMap<(usize, u16, u16), Range<usize>>;
+
+// keyed by a state's hash and the depth it was reached at, holding the index of the node that
+// keeps the statistics of that state, see `MctsConfig::transposition_table`
+type TranspositionTable = HashMap<(u64, u8), usize>;
+
+// a node selection went through on the way to a leaf, and the node holding its statistics.
+// they are the same node unless it transposes to another one
+struct PathStep {
+    node: usize,
+    statistics: usize,
+}
+
+fn move_pair_key(move_pair: &(MoveChoice, MoveChoice)) -> u16 {
+    (move_pair.0.to_u8() as u16) << 8 | (move_pair.1.to_u8() as u16)
+}
+
+fn options_match(options: &[MoveNode], expected: &[(MoveChoice, MoveChoice)]) -> bool {
+    let expected: HashSet<&(MoveChoice, MoveChoice)> = expected.iter().collect();
+    options.len() == expected.len() && options.iter().all(|x| expected.contains(&x.move_choice))
+}
+
+/// A search tree that is kept alive between searches.
+///
+/// `search` can be called repeatedly to keep refining the same tree, and `advance` re-roots it
+/// at the outcome that actually happened so the search for the next turn starts warm.
+/// Each call to `search` scores rollouts against the evaluation of the state it is given
+pub struct MctsTree {
+    // every node of the tree, allocated in one arena and referred to by index. the outcomes
+    // of a pair of options are allocated next to each other
+    nodes: Vec<Node>,
+    children: ChildMap,
+    transpositions: TranspositionTable,
+
+    // the bytes the nodes' instructions and options take up outside of `nodes`
+    heap_usage: usize,
+}
+
+impl MctsTree {
+    pub fn new(
+        mut side_one_options: Vec<(MoveChoice, MoveChoice)>,
+        mut side_two_options: Vec<(MoveChoice, MoveChoice)>,
+    ) -> MctsTree {
+        let mut root_node = Node::new(0);
+        root_node.populate(&mut side_one_options, &mut side_two_options);
+        root_node.root = true;
+
+        MctsTree {
+            heap_usage: root_node.heap_usage(),
+            nodes: vec![root_node],
+            children: HashMap::new(),
+            transpositions: HashMap::new(),
+        }
+    }
+
+    /// The number of nodes currently allocated in the tree, including the root
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Roughly how many bytes the tree's nodes take up, see `MctsConfig::max_memory`
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.capacity() * size_of::<Node>()
+            + self.children.capacity() * size_of::<((usize, u16, u16), Range<usize>)>()
+            + self.transpositions.capacity() * size_of::<((u64, u8), usize)>()
+            + self.heap_usage
+    }
+
+    /// The number of states in the transposition table, zero unless
+    /// `MctsConfig::transposition_table` is set
+    pub fn transposition_count(&self) -> usize {
+        self.transpositions.len()
+    }
+
+    fn is_full(&self, config: &MctsConfig) -> bool {
+        config
+            .max_memory
+            .is_some_and(|max_memory| self.memory_usage() >= max_memory)
+    }
+
+    // follows the selected options down from the root to a node that has not expanded them,
+    // applying the instructions of each node on the way to `state` and adding it to `path`.
+    // returns the node holding the statistics there and the options selected at it
+    fn selection<R: Rng + ?Sized>(
+        &mut self,
+        state: &mut State,
+        path: &mut Vec<PathStep>,
+        move_options: &mut MoveOptions,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> (usize, usize, usize) {
+        let is_full = self.is_full(config);
+        let mut index = ROOT_INDEX;
+        loop {
+            let node = &mut self.nodes[index];
+            if node.s1_options.is_none() {
+                // a full tree is not expanded any further, so a new leaf has no use for options
+                if is_full {
+                    return (index, 0, 0);
+                }
+                state.get_all_options(move_options);
+                node.populate(
+                    &mut move_options.side_one_combined_options,
+                    &mut move_options.side_two_combined_options,
+                );
+                self.heap_usage += node.options_heap_usage();
+            }
+            let (s1_index, s2_index) = node.select_options(state, config, rng);
+
+            let key = (
+                index,
+                node.s1_options.as_ref().unwrap()[s1_index].to_u16(),
+                node.s2_options.as_ref().unwrap()[s2_index].to_u16(),
+            );
+            match self.children.get(&key) {
+                Some(branch) => {
+                    let child = branch.start + sample_node(&self.nodes[branch.clone()], rng);
+                    state.apply_instructions(&self.nodes[child].instructions.instruction_list);
+                    index = self.statistics_of(child, state, path, config);
+                    path.push(PathStep {
+                        node: child,
+                        statistics: index,
+                    });
+                }
+                None => return (index, s1_index, s2_index),
+            }
+        }
+    }
+
+    // the node holding the statistics of `index`, whose instructions have just been applied to
+    // `state`. without a transposition table every node holds its own. a node that transposes
+    // to one already on `path` keeps its own too, so selection never loops
+    fn statistics_of(
+        &mut self,
+        index: usize,
+        state: &State,
+        path: &[PathStep],
+        config: &MctsConfig,
+    ) -> usize {
+        if !config.transposition_table {
+            return index;
+        }
+        let statistics = match self.nodes[index].transposition {
+            Some(statistics) => statistics,
+            None => {
+                let key = (state.hash, self.nodes[index].depth);
+                let statistics = *self.transpositions.entry(key).or_insert(index);
+                if statistics != index {
+                    self.nodes[index].transposition = Some(statistics);
+                }
+                statistics
+            }
+        };
+        if statistics == ROOT_INDEX || path.iter().any(|step| step.statistics == statistics) {
+            return index;
+        }
+        statistics
+    }
+
+    // the node holding the statistics of `index`: itself unless it transposes to another node
+    fn transposed(&self, index: usize) -> usize {
+        self.nodes[index].transposition.unwrap_or(index)
+    }
+
+    fn should_branch_on_damage(&self, index: usize) -> bool {
+        let node = &self.nodes[index];
+        let parent = match node.parent {
+            Some(parent) => &self.nodes[parent],
+            None => return true,
+        };
+
+        // if there aren't many options left, branch on damage if we're one node below the root
+        if parent.root && node.s1_options.as_ref().unwrap().len() < 20
+            || node.s2_options.as_ref().unwrap().len() < 20
+        {
+            return true;
+        }
+        false
+    }
+
+    // allocates a node for every outcome of the selected options at `index` and returns one
+    // of them, sampled by its percentage, with its instructions applied to `state`. returns
+    // `index` itself when the tree does not grow any further there
+    fn expand<R: Rng + ?Sized>(
+        &mut self,
+        state: &mut State,
+        index: usize,
+        s1_move_index: usize,
+        s2_move_index: usize,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> usize {
+        let node = &self.nodes[index];
+        if node.depth >= config.max_depth || self.is_full(config) {
+            return index;
+        }
+        let s1_option = &node.s1_options.as_ref().unwrap()[s1_move_index];
+        let s2_option = &node.s2_options.as_ref().unwrap()[s2_move_index];
+        let s1_move = &s1_option.move_choice;
+        let s2_move = &s2_option.move_choice;
+        if node.should_not_expand(state, s1_move, s2_move) {
+            return index;
+        }
+        let key = (index, s1_option.to_u16(), s2_option.to_u16());
+        let depth = node.depth;
+        let new_instructions = generate_instructions_from_move_pair(
+            state,
+            &s1_move.0,
+            &s1_move.1,
+            &s2_move.0,
+            &s2_move.1,
+            self.should_branch_on_damage(index),
+        );
+
+        let start = self.nodes.len();
+        for state_instructions in new_instructions {
+            let mut new_node = Node::new(child_depth(depth, &state_instructions));
+            new_node.parent = Some(index);
+            new_node.instructions = state_instructions;
+            self.heap_usage += new_node.heap_usage();
+            self.nodes.push(new_node);
+        }
+
+        // sample a node from the new instruction list.
+        // this is the node that the rollout will be done on
+        let new_node_index = start + sample_node(&self.nodes[start..], rng);
+        state.apply_instructions(&self.nodes[new_node_index].instructions.instruction_list);
+        self.children.insert(key, start..self.nodes.len());
+        new_node_index
     }
 
-    fn select_move_index(
-        options: &mut Vec<MoveNode>,
-        times_visited: u32,
-        times_visited_usize: usize,
-    ) -> usize {
-        if options.len() * MIN_VISITS_BEFORE_SELECTION == times_visited_usize {
-            options.sort_by(|a, b| b.total_score.total_cmp(&a.total_score));
+    // adds a visit scored `score` to the root and to the statistics of every node on `path`,
+    // reversing the instructions of each node on the way from `state`
+    fn backpropagate(
+        &mut self,
+        path: &[PathStep],
+        score: f32,
+        state: &mut State,
+        config: &MctsConfig,
+    ) {
+        let mut index = path.last().map_or(ROOT_INDEX, |step| step.statistics);
+        for (position, step) in path.iter().enumerate().rev() {
+            let node = &mut self.nodes[index];
+            node.times_visited += 1;
+            node.total_score.add(score);
+
+            index = position
+                .checked_sub(1)
+                .map_or(ROOT_INDEX, |position| path[position].statistics);
+            Node::update_parent(&mut self.nodes[index], score, config);
+            state.reverse_instructions(&self.nodes[step.node].instructions.instruction_list);
         }
+        let root = &mut self.nodes[ROOT_INDEX];
+        root.times_visited += 1;
+        root.total_score.add(score);
+    }
 
-        if options.len() * MIN_VISITS_BEFORE_SELECTION > times_visited_usize {
-            times_visited_usize % options.len()
+    fn do_mcts<R: Rng + ?Sized>(
+        &mut self,
+        state: &mut State,
+        path: &mut Vec<PathStep>,
+        root_eval: &f32,
+        move_options: &mut MoveOptions,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> u8 {
+        path.clear();
+        let (leaf, s1_move, s2_move) = self.selection(state, path, move_options, config, rng);
+        let new_node = self.expand(state, leaf, s1_move, s2_move, config, rng);
+        let leaf = if new_node == leaf {
+            leaf
         } else {
-            let first_item = &options[0];
-            let first_item_ucb1 = first_item.ucb1(times_visited);
+            let statistics = self.statistics_of(new_node, state, path, config);
+            path.push(PathStep {
+                node: new_node,
+                statistics,
+            });
+            statistics
+        };
+        let depth = self.nodes[leaf].depth;
+        let rollout_result = self.nodes[leaf].rollout(state, root_eval, move_options, config, rng);
+        self.backpropagate(path, rollout_result, state, config);
+        depth
+    }
 
-            let mut index = 1;
-            for i in 1..options.len() {
-                if first_item_ucb1 >= options[i].ucb1(times_visited) {
-                    break;
+    pub fn search(
+        &mut self,
+        state: &mut State,
+        max_time: Duration,
+        config: &MctsConfig,
+    ) -> MctsResult {
+        let mut combined_options = MoveOptions::new();
+        let root_eval = evaluate(state);
+        let start_visits = self.nodes[ROOT_INDEX].times_visited;
+        let mut rng = search_rng(config.seed);
+        let mut leaf_depths = LeafDepths::default();
+        let mut path = Vec::new();
+        let track_hash = state.track_hash;
+        if config.transposition_table {
+            state.track_hash = true;
+            state.rehash();
+        }
+        let start_time = std::time::Instant::now();
+        'search: while start_time.elapsed() < max_time {
+            for _ in 0..1000 {
+                if iterations_left(self.nodes[ROOT_INDEX].times_visited - start_visits, config) == 0
+                    || matches!(config.max_nodes, Some(max_nodes) if self.nodes.len() >= max_nodes)
+                {
+                    break 'search;
                 }
-                index += 1;
+                leaf_depths.add(self.do_mcts(
+                    state,
+                    &mut path,
+                    &root_eval,
+                    &mut combined_options,
+                    config,
+                    &mut rng,
+                ));
             }
-            index -= 1;
 
-            if index > 0 {
-                if index == 1 {
-                    options.swap(0, 1);
-                } else {
-                    let first = options.remove(0);
-                    options.insert(index, first);
+            if config.early_stop {
+                let root_node = &self.nodes[ROOT_INDEX];
+                let remaining = remaining_iterations(
+                    root_node.times_visited - start_visits,
+                    start_time.elapsed(),
+                    max_time,
+                    iterations_left(root_node.times_visited - start_visits, config),
+                );
+                let s1_decided = most_visited_is_decided(
+                    root_node
+                        .s1_options
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|x| x.visits),
+                    remaining,
+                );
+                let s2_decided = most_visited_is_decided(
+                    root_node
+                        .s2_options
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|x| x.visits),
+                    remaining,
+                );
+                if s1_decided && s2_decided {
+                    break;
                 }
             }
-            0
         }
-    }
+        state.track_hash = track_hash;
 
-    pub unsafe fn selection(
-        &mut self,
-        state: &mut State,
-        move_options: &mut MoveOptions,
-        children: &mut HashMap<(usize, u16, u16), Box<[Node]>>,
-    ) -> (*mut Node, usize, usize) {
-        let return_node = self as *mut Node;
-        if self.s1_options.is_none() {
-            state.get_all_options(move_options);
-            self.populate(
-                &mut move_options.side_one_combined_options,
-                &mut move_options.side_two_combined_options,
+        let root_node = &self.nodes[ROOT_INDEX];
+        let mut result = MctsResult {
+            s1: root_node
+                .s1_options
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|v| MctsSideResult {
+                    move_choice: v.move_choice.clone(),
+                    total_score: v.total_score.total() as f32,
+                    visits: v.visits,
+                    nash_probability: None,
+                })
+                .collect(),
+            s2: root_node
+                .s2_options
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|v| MctsSideResult {
+                    move_choice: v.move_choice.clone(),
+                    total_score: v.total_score.total() as f32,
+                    visits: v.visits,
+                    nash_probability: None,
+                })
+                .collect(),
+            iteration_count: root_node.times_visited,
+            principal_variation: Vec::new(),
+            max_leaf_depth: leaf_depths.max,
+            average_leaf_depth: leaf_depths.average(),
+        };
+
+        if config.nash_iterations > 0 {
+            solve_root_nash(
+                &mut result,
+                |i, j| self.root_pair_stats(i, j),
+                config.nash_iterations,
             );
         }
+        result.principal_variation = self.principal_variation(config.principal_variation_plies);
+        result
+    }
 
-        let times_visited_usize = self.times_visited as usize;
-        let s1_mc_index = Self::select_move_index(
-            self.s1_options.as_mut().unwrap(),
-            self.times_visited,
-            times_visited_usize,
-        );
-        let s2_mc_index = Self::select_move_index(
-            self.s2_options.as_mut().unwrap(),
-            self.times_visited,
-            times_visited_usize,
+    // the total score (for side one) and the visits of the root's options `s1_index` and
+    // `s2_index` played against each other. an outcome that transposes to another node counts
+    // that node's statistics
+    fn root_pair_stats(&self, s1_index: usize, s2_index: usize) -> (ScoreSum, u64) {
+        self.branch(ROOT_INDEX, s1_index, s2_index).map_or(
+            (ScoreSum::default(), 0),
+            |(start, branch)| {
+                (start..start + branch.len())
+                    .map(|index| &self.nodes[self.transposed(index)])
+                    .fold((ScoreSum::default(), 0), |(score, visits), node| {
+                        (
+                            ScoreSum(score.0 + node.total_score.0),
+                            visits + node.times_visited,
+                        )
+                    })
+            },
+        )
+    }
+
+    // `root_pair_stats` for a pair of the root's options by their move choices, which keeps
+    // meaning the same pair after selection reorders the root's options
+    pub(crate) fn root_move_pair_stats(
+        &self,
+        s1_move: &(MoveChoice, MoveChoice),
+        s2_move: &(MoveChoice, MoveChoice),
+    ) -> (ScoreSum, u64) {
+        let root = &self.nodes[ROOT_INDEX];
+        let s1_index = root
+            .s1_options
+            .as_ref()
+            .and_then(|options| options.iter().position(|x| x.move_choice == *s1_move));
+        let s2_index = root
+            .s2_options
+            .as_ref()
+            .and_then(|options| options.iter().position(|x| x.move_choice == *s2_move));
+        match (s1_index, s2_index) {
+            (Some(s1_index), Some(s2_index)) => self.root_pair_stats(s1_index, s2_index),
+            _ => (ScoreSum::default(), 0),
+        }
+    }
+
+    // the outcomes of the options `s1_index` and `s2_index` at `index`, and the index of the
+    // first of them
+    fn branch(&self, index: usize, s1_index: usize, s2_index: usize) -> Option<(usize, &[Node])> {
+        let node = &self.nodes[index];
+        let key = (
+            index,
+            node.s1_options.as_ref()?[s1_index].to_u16(),
+            node.s2_options.as_ref()?[s2_index].to_u16(),
         );
+        self.children
+            .get(&key)
+            .map(|branch| (branch.start, &self.nodes[branch.clone()]))
+    }
 
-        let s1_key = self.s1_options.as_ref().unwrap()[s1_mc_index].to_u16();
-        let s2_key = self.s2_options.as_ref().unwrap()[s2_mc_index].to_u16();
-        let key = (return_node as usize, s1_key, s2_key);
-        match children.get_mut(&key) {
-            Some(child_vector) => {
-                let child_vec_ptr = child_vector as *mut Box<[Node]>;
-                let chosen_child = self.sample_node(child_vec_ptr);
-                state.apply_instructions(&(*chosen_child).instructions.instruction_list);
-                (*chosen_child).selection(state, move_options, children)
-            }
-            None => (return_node, s1_mc_index, s2_mc_index),
+    fn principal_variation(&self, plies: u8) -> Vec<PrincipalVariationPly> {
+        let mut line = Vec::new();
+        let mut index = ROOT_INDEX;
+        while line.len() < plies as usize {
+            let node = &self.nodes[index];
+            let (s1_options, s2_options) = match (&node.s1_options, &node.s2_options) {
+                (Some(s1), Some(s2)) if !s1.is_empty() && !s2.is_empty() => (s1, s2),
+                _ => break,
+            };
+            let s1_index = index_of_max(s1_options.iter().map(|x| x.visits));
+            let s2_index = index_of_max(s2_options.iter().map(|x| x.visits));
+            let (start, branch) = match self.branch(index, s1_index, s2_index) {
+                Some(branch) => branch,
+                None => break,
+            };
+            let outcome = start + index_of_max(branch.iter().map(|x| x.instructions.percentage));
+            index = self.transposed(outcome);
+            let node = &self.nodes[index];
+            line.push(PrincipalVariationPly {
+                s1_move: s1_options[s1_index].move_choice,
+                s2_move: s2_options[s2_index].move_choice,
+                instructions: self.nodes[outcome].instructions.clone(),
+                visits: node.times_visited,
+                average_score: node.average_score(),
+            });
         }
+        line
     }
 
-    unsafe fn sample_node(&self, move_vector: *mut Box<[Node]>) -> *mut Node {
-        let mut rng = rng();
-        let weights: Vec<f64> = (*move_vector)
-            .iter()
-            .map(|x| x.instructions.percentage as f64)
-            .collect();
-        let dist = WeightedIndex::new(weights).unwrap();
-        let chosen_node = &mut (&mut *move_vector)[dist.sample(&mut rng)];
-        let chosen_node_ptr = chosen_node as *mut Node;
-        chosen_node_ptr
+    /// Copies the top `plies` plies of the tree, leaving out nodes visited fewer than
+    /// `min_visits` times
+    pub fn export(&self, plies: u8, min_visits: u64) -> ExportedNode {
+        self.export_node(ROOT_INDEX, None, plies, min_visits)
     }
 
-    fn should_not_expand(
+    fn export_node(
         &self,
-        state: &State,
-        s1_move: &(MoveChoice, MoveChoice),
-        s2_move: &(MoveChoice, MoveChoice),
-    ) -> bool {
-        if (state.battle_is_over() != 0.0 && !self.root)
-            || (s1_move == &(MoveChoice::None, MoveChoice::None)
-                && s2_move == &(MoveChoice::None, MoveChoice::None))
+        index: usize,
+        move_pair: Option<((MoveChoice, MoveChoice), (MoveChoice, MoveChoice))>,
+        plies: u8,
+        min_visits: u64,
+    ) -> ExportedNode {
+        let statistics = self.transposed(index);
+        let node = &self.nodes[statistics];
+        let mut children = Vec::new();
+        if let (Some(s1_options), Some(s2_options), true) =
+            (&node.s1_options, &node.s2_options, plies > 0)
         {
-            true
-        } else {
-            false
+            for (s1_index, s1_option) in s1_options.iter().enumerate() {
+                for (s2_index, s2_option) in s2_options.iter().enumerate() {
+                    let (start, branch) = self
+                        .branch(statistics, s1_index, s2_index)
+                        .unwrap_or_default();
+                    for child_index in start..start + branch.len() {
+                        if self.nodes[self.transposed(child_index)].times_visited
+                            >= min_visits.max(1)
+                        {
+                            children.push(self.export_node(
+                                child_index,
+                                Some((s1_option.move_choice, s2_option.move_choice)),
+                                plies - 1,
+                                min_visits,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        ExportedNode {
+            move_pair,
+            instructions: self.nodes[index].instructions.clone(),
+            visits: node.times_visited,
+            average_score: node.average_score(),
+            children,
         }
     }
 
-    pub unsafe fn expand(
+    /// Re-roots the tree at the outcome of `side_one_move` and `side_two_move` that produced
+    /// `instructions`. `state` is the state after `instructions` have been applied.
+    ///
+    /// Returns `true` if that outcome had already been explored and its subtree was kept.
+    /// Otherwise the tree is replaced by a fresh one rooted at `state`
+    pub fn advance(
         &mut self,
-        state: &mut State,
-        s1_move_index: usize,
-        s2_move_index: usize,
-        children: &mut HashMap<(usize, u16, u16), Box<[Node]>>,
-    ) -> *mut Node {
-        if self.depth >= 4 {
-            return self as *mut Node;
-        }
-        let node_addr = self as *mut Node as usize;
-        let s1_option = &self.s1_options.as_ref().unwrap()[s1_move_index];
-        let s2_option = &self.s2_options.as_ref().unwrap()[s2_move_index];
-        let s1_move = &s1_option.move_choice;
-        let s2_move = &s2_option.move_choice;
-        if self.should_not_expand(state, &s1_move, &s2_move) {
-            return self as *mut Node;
-        }
-        let key = (node_addr, s1_option.to_u16(), s2_option.to_u16());
-        let should_branch_on_damage = self.should_branch_on_damage();
-        let mut new_instructions = generate_instructions_from_move_pair(
-            state,
-            &s1_move.0,
-            &s1_move.1,
-            &s2_move.0,
-            &s2_move.1,
-            should_branch_on_damage,
+        state: &State,
+        side_one_move: &(MoveChoice, MoveChoice),
+        side_two_move: &(MoveChoice, MoveChoice),
+        instructions: &StateInstructions,
+    ) -> bool {
+        let (mut side_one_options, mut side_two_options) = state.root_get_all_options();
+        let key = (
+            ROOT_INDEX,
+            move_pair_key(side_one_move),
+            move_pair_key(side_two_move),
         );
-        let mut this_pair_vec = Vec::with_capacity(new_instructions.len());
-        for state_instructions in new_instructions.drain(..) {
-            let new_depth = if state_instructions.end_of_turn_triggered {
-                self.depth + 1
-            } else {
-                self.depth
+
+        let outcome = self.children.get(&key).and_then(|branch| {
+            let index = branch.clone().find(|&index| {
+                self.nodes[index].instructions.instruction_list == instructions.instruction_list
+            })?;
+            // an outcome that transposes to another node keeps its subtree there
+            let index = self.transposed(index);
+            let node = &self.nodes[index];
+            // the options at a node deeper in the tree were not filtered the way
+            // `root_get_all_options` filters them, so the subtree is only usable if they agree
+            let compatible = match (&node.s1_options, &node.s2_options) {
+                (Some(s1), Some(s2)) => {
+                    options_match(s1, &side_one_options) && options_match(s2, &side_two_options)
+                }
+                _ => true,
             };
+            compatible.then_some(index)
+        });
 
-            let mut new_node = Node::new(new_depth);
-            new_node.parent = self;
-            new_node.instructions = state_instructions;
+        match outcome {
+            Some(index) => {
+                self.reroot(index);
+                let root_node = &mut self.nodes[ROOT_INDEX];
+                if root_node.s1_options.is_none() {
+                    root_node.populate(&mut side_one_options, &mut side_two_options);
+                    self.heap_usage += root_node.options_heap_usage();
+                }
+                true
+            }
+            None => {
+                *self = MctsTree::new(side_one_options, side_two_options);
+                false
+            }
+        }
+    }
 
-            this_pair_vec.push(new_node);
+    // makes `index` the root, moving the nodes that can be reached from it into a new arena
+    // and dropping the rest
+    fn reroot(&mut self, index: usize) {
+        let depth_offset = self.nodes[index].depth;
+        let mut branches_by_parent: HashMap<usize, Vec<(u16, u16, Range<usize>)>> = HashMap::new();
+        for ((parent, s1_key, s2_key), branch) in self.children.drain() {
+            branches_by_parent
+                .entry(parent)
+                .or_default()
+                .push((s1_key, s2_key, branch));
         }
 
-        // sample a node from the new instruction list.
-        // this is the node that the rollout will be done on
-        let mut boxed = this_pair_vec.into_boxed_slice();
-        let new_node_ptr = self.sample_node(&mut boxed);
-        state.apply_instructions(&(*new_node_ptr).instructions.instruction_list);
-        children.insert(key, boxed);
-        new_node_ptr
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+        let mut new_root = std::mem::replace(&mut old_nodes[index], Node::new(0));
+        new_root.root = true;
+        new_root.parent = None;
+        new_root.depth = 0;
+        self.nodes.push(new_root);
+
+        // pairs of a node's index in the old arena and in the new one
+        let mut stack = vec![(index, ROOT_INDEX)];
+        let mut moved = HashMap::from([(index, ROOT_INDEX)]);
+        while let Some((old_index, new_index)) = stack.pop() {
+            let branches = branches_by_parent.remove(&old_index).unwrap_or_default();
+            for (s1_key, s2_key, branch) in branches {
+                let start = self.nodes.len();
+                for old_child in branch {
+                    let mut node = std::mem::replace(&mut old_nodes[old_child], Node::new(0));
+                    node.parent = Some(new_index);
+                    node.depth -= depth_offset;
+                    stack.push((old_child, self.nodes.len()));
+                    moved.insert(old_child, self.nodes.len());
+                    self.nodes.push(node);
+                }
+                self.children
+                    .insert((new_index, s1_key, s2_key), start..self.nodes.len());
+            }
+        }
+
+        // a node transposing to one that was dropped holds its own statistics again
+        for node in self.nodes.iter_mut() {
+            node.transposition = node
+                .transposition
+                .and_then(|old_index| moved.get(&old_index).copied());
+        }
+        self.transpositions = std::mem::take(&mut self.transpositions)
+            .into_iter()
+            .filter_map(|((hash, depth), old_index)| {
+                let new_index = *moved.get(&old_index)?;
+                Some(((hash, depth - depth_offset), new_index))
+            })
+            .collect();
+        self.heap_usage = self.nodes.iter().map(Node::heap_usage).sum();
     }
+}
 
-    pub unsafe fn backpropagate(&mut self, score: f32, state: &mut State) {
-        self.times_visited += 1;
-        if self.root {
-            return;
+pub fn perform_mcts(
+    state: &mut State,
+    side_one_options: Vec<(MoveChoice, MoveChoice)>,
+    side_two_options: Vec<(MoveChoice, MoveChoice)>,
+    max_time: Duration,
+    config: &MctsConfig,
+) -> MctsResult {
+    let start_time = Instant::now();
+    if is_endgame(state, config) {
+        if let Some(result) = solve_endgame(
+            state,
+            &side_one_options,
+            &side_two_options,
+            max_time,
+            config,
+        ) {
+            return result;
         }
-        let s1_choice_index = if (*self.parent).times_visited >= MIN_VISITS_BEFORE_SELECTION as u32
-        {
-            0
-        } else {
-            (*self.parent).times_visited as usize
-                % (*self.parent).s1_options.as_ref().unwrap().len()
+    }
+    MctsTree::new(side_one_options, side_two_options).search(
+        state,
+        max_time.saturating_sub(start_time.elapsed()),
+        config,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::choices::{Choices, MOVES};
+    use crate::state::{PokemonIndex, PokemonMoveIndex};
+
+    #[test]
+    fn test_child_depth_only_counts_outcomes_that_end_the_turn() {
+        let mut instructions = StateInstructions::default();
+        assert_eq!(1, child_depth(1, &instructions));
+
+        instructions.end_of_turn_triggered = true;
+        assert_eq!(2, child_depth(1, &instructions));
+        assert_eq!(u8::MAX, child_depth(u8::MAX, &instructions));
+    }
+
+    #[test]
+    fn test_index_of_max_picks_the_first_of_tied_values() {
+        assert_eq!(1, index_of_max([1, 3, 2, 3].iter().copied()));
+        assert_eq!(0, index_of_max([0.5f32].iter().copied()));
+        assert_eq!(0, index_of_max(std::iter::empty::<u32>()));
+    }
+
+    #[test]
+    fn test_most_visited_is_decided_when_the_runner_up_cannot_catch_up() {
+        assert!(most_visited_is_decided([100, 40, 10].iter().copied(), 59));
+        assert!(!most_visited_is_decided([100, 40, 10].iter().copied(), 60));
+    }
+
+    #[test]
+    fn test_most_visited_is_decided_with_a_single_option() {
+        assert!(most_visited_is_decided([0].iter().copied(), 1_000));
+    }
+
+    #[test]
+    fn test_remaining_iterations_is_limited_by_time_and_iterations() {
+        let elapsed = Duration::from_millis(100);
+        let max_time = Duration::from_millis(300);
+        assert_eq!(2_000, remaining_iterations(1_000, elapsed, max_time, 5_000));
+        assert_eq!(500, remaining_iterations(1_000, elapsed, max_time, 500));
+    }
+
+    #[test]
+    fn test_policy_names_parse_ignoring_case_and_reject_unknown_names() {
+        assert_eq!(Ok(SelectionPolicy::PUCT), SelectionPolicy::parse("puct"));
+        assert_eq!(Ok(RolloutPolicy::GREEDY), RolloutPolicy::parse("Greedy"));
+        assert_eq!(
+            Err("invalid policy 'ucb', expected one of ucb1, exp3, regretmatching, factorizeducb1, puct".to_string()),
+            SelectionPolicy::parse("ucb")
+        );
+        assert!(RolloutPolicy::parse("").is_err());
+    }
+
+    #[test]
+    fn test_iterations_left_is_unlimited_without_max_iterations() {
+        let config = MctsConfig {
+            max_iterations: Some(1_000),
+            ..Default::default()
         };
-        let s2_choice_index = if (*self.parent).times_visited >= MIN_VISITS_BEFORE_SELECTION as u32
-        {
-            0
-        } else {
-            (*self.parent).times_visited as usize
-                % (*self.parent).s2_options.as_ref().unwrap().len()
+        assert_eq!(400, iterations_left(600, &config));
+        assert_eq!(0, iterations_left(1_200, &config));
+        assert_eq!(u64::MAX, iterations_left(600, &MctsConfig::default()));
+    }
+
+    #[test]
+    fn test_score_sum_keeps_counting_where_an_f32_total_stops() {
+        let visits = 5_000_000_000;
+        let mut total = ScoreSum(ScoreSum::fixed_point(0.75) * visits);
+        let before = total.total();
+        total.add(0.25);
+
+        assert!(total.total() > before);
+        assert_eq!(0.75, total.average(visits + 1));
+
+        let f32_total = 0.75 * visits as f32;
+        assert_eq!(f32_total, f32_total + 0.25);
+    }
+
+    #[test]
+    fn test_regret_matching_plays_in_proportion_to_positive_regret() {
+        let config = MctsConfig {
+            selection_policy: SelectionPolicy::REGRETMATCHING,
+            policy_exploration: 0.0,
+            ..Default::default()
         };
+        let mut weights = [3.0, 1.0, -2.0];
+        policy_probabilities(&mut weights, &config);
+        assert_eq!([0.75, 0.25, 0.0], weights);
+    }
 
-        let parent_s1_movenode = &mut (*self.parent).s1_options.as_mut().unwrap()[s1_choice_index];
-        parent_s1_movenode.total_score += score;
-        parent_s1_movenode.visits += 1;
+    #[test]
+    fn test_regret_matching_is_uniform_without_positive_regret() {
+        let config = MctsConfig {
+            selection_policy: SelectionPolicy::REGRETMATCHING,
+            ..Default::default()
+        };
+        let mut weights = [0.0, -1.0];
+        policy_probabilities(&mut weights, &config);
+        assert_eq!([0.5, 0.5], weights);
+    }
 
-        let parent_s2_movenode = &mut (*self.parent).s2_options.as_mut().unwrap()[s2_choice_index];
-        parent_s2_movenode.total_score += 1.0 - score;
-        parent_s2_movenode.visits += 1;
+    #[test]
+    fn test_exp3_favours_the_highest_estimated_reward_and_explores_the_rest() {
+        let config = MctsConfig {
+            selection_policy: SelectionPolicy::EXP3,
+            policy_exploration: 0.2,
+            ..Default::default()
+        };
+        let mut weights = [100.0, 0.0];
+        policy_probabilities(&mut weights, &config);
+        assert!(weights[0] > weights[1]);
+        assert!(weights[1] >= 0.1);
+        assert!((weights[0] + weights[1] - 1.0).abs() < 1e-6);
+    }
 
-        state.reverse_instructions(&self.instructions.instruction_list);
-        (*self.parent).backpropagate(score, state);
+    #[test]
+    fn test_select_factorized_only_picks_legal_pairs() {
+        // both slots would most like to switch to the same pokemon, which is not a legal pair
+        let options = [
+            (
+                MoveChoice::Switch(PokemonIndex::P2),
+                MoveChoice::Switch(PokemonIndex::P3),
+            ),
+            (
+                MoveChoice::Switch(PokemonIndex::P3),
+                MoveChoice::Switch(PokemonIndex::P2),
+            ),
+            (MoveChoice::None, MoveChoice::Switch(PokemonIndex::P2)),
+        ];
+        let ucb1 = |choice: &MoveChoice| match choice {
+            MoveChoice::Switch(PokemonIndex::P2) => 1.0,
+            MoveChoice::Switch(_) => 0.5,
+            _ => 0.0,
+        };
+
+        assert_eq!(
+            0,
+            select_factorized(options.len(), |i| options[i], ucb1, ucb1)
+        );
     }
 
-    pub fn rollout(&mut self, state: &mut State, root_eval: &f32) -> f32 {
-        let battle_is_over = state.battle_is_over();
-        if battle_is_over == 0.0 {
-            let eval = evaluate(state);
-            sigmoid(eval - root_eval)
-        } else {
-            if battle_is_over == -1.0 {
-                0.0
-            } else {
-                battle_is_over
-            }
-        }
+    #[test]
+    fn test_open_option_count_grows_with_visits() {
+        let config = MctsConfig {
+            progressive_widening: true,
+            ..Default::default()
+        };
+        assert_eq!(2, open_option_count(0, 50, &config));
+        assert_eq!(20, open_option_count(99, 50, &config));
+        assert_eq!(50, open_option_count(10_000, 50, &config));
+        assert_eq!(50, open_option_count(0, 50, &MctsConfig::default()));
     }
-}
 
-#[derive(Debug)]
-pub struct MoveNode {
-    pub move_choice: (MoveChoice, MoveChoice),
-    pub total_score: f32,
-    pub visits: u32,
-}
+    #[test]
+    fn test_prior_order_opens_damaging_moves_first() {
+        let mut state = State::default();
+        let pkmn = state.sides[0].get_active(&SlotReference::SlotA);
+        pkmn.moves.m0.choice = MOVES.get(&Choices::PROTECT).unwrap().clone();
+        pkmn.moves.m1.choice = MOVES.get(&Choices::HYDROPUMP).unwrap().clone();
+        let options = [
+            (MoveChoice::Switch(PokemonIndex::P2), MoveChoice::None),
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideOne,
+                    PokemonMoveIndex::M0,
+                ),
+                MoveChoice::None,
+            ),
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideTwo,
+                    PokemonMoveIndex::M1,
+                ),
+                MoveChoice::None,
+            ),
+        ];
 
-impl MoveNode {
-    pub fn to_u16(&self) -> u16 {
-        (self.move_choice.0.to_u8() as u16) << 8 | (self.move_choice.1.to_u8() as u16)
+        assert_eq!(
+            vec![2, 1, 0],
+            prior_order(
+                &state,
+                SideReference::SideOne,
+                &options,
+                &MctsConfig::default()
+            )
+        );
     }
-    pub fn ucb1(&self, parent_visits: u32) -> f32 {
-        if self.visits == 0 {
-            return f32::INFINITY;
+
+    // side one leads with protect 80% of the time and never switches
+    fn protect_prior(
+        _state: &State,
+        side_ref: SideReference,
+        slot_ref: SlotReference,
+    ) -> Vec<(MoveChoice, f32)> {
+        if side_ref != SideReference::SideOne || slot_ref != SlotReference::SlotA {
+            return Vec::new();
         }
-        let score = (self.total_score / self.visits as f32)
-            + (2.0 * (parent_visits as f32).ln() / self.visits as f32).sqrt();
-        score
+        vec![
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideOne,
+                    PokemonMoveIndex::M0,
+                ),
+                0.8,
+            ),
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideTwo,
+                    PokemonMoveIndex::M1,
+                ),
+                0.2,
+            ),
+        ]
     }
-    pub fn average_score(&self) -> f32 {
-        let score = self.total_score / self.visits as f32;
-        score
+
+    #[test]
+    fn test_option_priors_come_from_the_prior_callback() {
+        let state = State::default();
+        let options = [
+            (MoveChoice::Switch(PokemonIndex::P2), MoveChoice::None),
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideOne,
+                    PokemonMoveIndex::M0,
+                ),
+                MoveChoice::None,
+            ),
+            (
+                MoveChoice::Move(
+                    SlotReference::SlotA,
+                    SideReference::SideTwo,
+                    PokemonMoveIndex::M1,
+                ),
+                MoveChoice::None,
+            ),
+        ];
+        let config = MctsConfig {
+            prior_callback: Some(Arc::new(protect_prior)),
+            ..Default::default()
+        };
+
+        let priors = option_priors(&state, SideReference::SideOne, &options, &config);
+        let expected = [0.0, 0.8, 0.2].map(|p| 0.9 * p + 0.1 / 3.0);
+        for (prior, expected) in priors.iter().zip(expected) {
+            assert!((prior - expected).abs() < 1e-6);
+        }
+        assert_eq!(
+            vec![1.0 / 3.0; 3],
+            option_priors(&state, SideReference::SideTwo, &options, &config)
+        );
+        assert_eq!(
+            vec![1, 2, 0],
+            prior_order(&state, SideReference::SideOne, &options, &config)
+        );
     }
-}
 
-#[derive(Clone)]
-pub struct MctsSideResult {
-    pub move_choice: (MoveChoice, MoveChoice),
-    pub total_score: f32,
-    pub visits: u32,
-}
+    fn hydro_pump_state() -> State {
+        let mut state = State::default();
+        for side in state.sides.iter_mut() {
+            for pkmn in side.pokemon.pkmn[..2].iter_mut() {
+                pkmn.moves.m0.id = Choices::HYDROPUMP;
+                pkmn.moves.m0.choice = MOVES.get(&Choices::HYDROPUMP).unwrap().clone();
+            }
+        }
+        state
+    }
 
-impl MctsSideResult {
-    pub fn average_score(&self) -> f32 {
-        if self.visits == 0 {
-            return 0.0;
+    #[test]
+    fn test_usage_prior_splits_a_moves_usage_between_its_targets() {
+        let mut state = State::default();
+        let active = state.sides[0].get_active(&SlotReference::SlotA);
+        active.id = PokemonName::INCINEROAR;
+        active.replace_move(PokemonMoveIndex::M1, Choices::KNOCKOFF);
+        let callback = usage_prior_callback(HashMap::from([(
+            PokemonName::INCINEROAR,
+            HashMap::from([(Choices::KNOCKOFF, 0.6)]),
+        )]));
+
+        // one choice per target and per gimmick the enabled features allow
+        let priors = callback(&state, SideReference::SideOne, SlotReference::SlotA);
+        assert!(priors.len() >= 2);
+        for (_, prior) in priors.iter() {
+            assert!((prior - 0.6 / priors.len() as f32).abs() < 1e-6);
         }
-        let score = self.total_score / self.visits as f32;
-        score
+        assert!(priors.iter().all(|(choice, _)| matches!(
+            choice,
+            MoveChoice::Move(_, SideReference::SideTwo, PokemonMoveIndex::M1)
+                | MoveChoice::MoveTera(_, SideReference::SideTwo, PokemonMoveIndex::M1)
+                | MoveChoice::MoveMega(_, SideReference::SideTwo, PokemonMoveIndex::M1)
+                | MoveChoice::MoveDynamax(_, SideReference::SideTwo, PokemonMoveIndex::M1)
+                | MoveChoice::MoveZ(_, SideReference::SideTwo, PokemonMoveIndex::M1)
+        )));
+        assert!(callback(&state, SideReference::SideOne, SlotReference::SlotB).is_empty());
     }
-}
 
-pub struct MctsResult {
-    pub s1: Vec<MctsSideResult>,
-    pub s2: Vec<MctsSideResult>,
-    pub iteration_count: u32,
-}
+    #[test]
+    fn test_playout_leaves_the_state_unchanged() {
+        let mut state = hydro_pump_state();
+        let serialized_before = state.serialize();
+        let mut move_options = MoveOptions::new();
+        let mut rng = search_rng(Some(7));
+        for rollout_policy in [RolloutPolicy::RANDOM, RolloutPolicy::GREEDY]
+            .iter()
+            .copied()
+        {
+            let config = MctsConfig {
+                rollout_policy,
+                rollout_turns: 10,
+                ..Default::default()
+            };
+            for _ in 0..20 {
+                let score = playout(&mut state, 0.0, &mut move_options, &config, &mut rng);
+                assert!((0.0..=1.0).contains(&score));
+                assert_eq!(serialized_before, state.serialize());
+            }
+        }
+    }
 
-fn do_mcts(
-    root_node: &mut Node,
-    state: &mut State,
-    root_eval: &f32,
-    move_options: &mut MoveOptions,
-    children: &mut HashMap<(usize, u16, u16), Box<[Node]>>,
-) {
-    let (mut new_node, s1_move, s2_move) =
-        unsafe { root_node.selection(state, move_options, children) };
-    new_node = unsafe { (*new_node).expand(state, s1_move, s2_move, children) };
-    let rollout_result = unsafe { (*new_node).rollout(state, root_eval) };
-    unsafe { (*new_node).backpropagate(rollout_result, state) }
-}
+    #[test]
+    fn test_callback_rollout_without_a_callback_scores_like_static() {
+        let mut state = hydro_pump_state();
+        let mut move_options = MoveOptions::new();
+        let mut rng = search_rng(Some(7));
+        let config = MctsConfig {
+            rollout_policy: RolloutPolicy::CALLBACK,
+            rollout_turns: 10,
+            ..Default::default()
+        };
+        let root_eval = evaluate(&state);
 
-pub fn perform_mcts(
-    state: &mut State,
-    mut side_one_options: Vec<(MoveChoice, MoveChoice)>,
-    mut side_two_options: Vec<(MoveChoice, MoveChoice)>,
-    max_time: Duration,
-) -> MctsResult {
-    let mut root_node = Node::new(0);
-    unsafe {
-        root_node.populate(&mut side_one_options, &mut side_two_options);
+        assert_eq!(
+            static_score(&state, root_eval, &config),
+            rollout_score(&mut state, root_eval, &mut move_options, &config, &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_greedy_rollout_picks_the_option_dealing_the_most_damage() {
+        let mut state = hydro_pump_state();
+        let attacker = state.sides[0].get_active(&SlotReference::SlotA);
+        attacker.moves.m1.id = Choices::TACKLE;
+        attacker.moves.m1.choice = MOVES.get(&Choices::TACKLE).unwrap().clone();
+        let mut rng = search_rng(Some(7));
+        let config = MctsConfig {
+            rollout_policy: RolloutPolicy::GREEDY,
+            ..Default::default()
+        };
+        let hydro_pump = (
+            MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+            MoveChoice::None,
+        );
+        let tackle = (
+            MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M1,
+            ),
+            MoveChoice::None,
+        );
+        let switch = (MoveChoice::Switch(PokemonIndex::P2), MoveChoice::None);
+
+        for options in [[tackle, hydro_pump, switch], [hydro_pump, switch, tackle]].iter() {
+            for _ in 0..10 {
+                assert_eq!(
+                    hydro_pump,
+                    rollout_option(&state, SideReference::SideOne, options, &config, &mut rng)
+                );
+            }
+        }
     }
-    root_node.root = true;
-
-    let mut combined_options = MoveOptions::new();
-    let mut children: HashMap<(usize, u16, u16), Box<[Node]>> = HashMap::new();
-    let root_eval = evaluate(state);
-    let start_time = std::time::Instant::now();
-    while start_time.elapsed() < max_time {
-        for _ in 0..1000 {
-            do_mcts(
-                &mut root_node,
-                state,
-                &root_eval,
-                &mut combined_options,
-                &mut children,
+
+    #[test]
+    fn test_playout_without_rollout_turns_is_the_static_score() {
+        let mut state = hydro_pump_state();
+        let mut move_options = MoveOptions::new();
+        let mut rng = search_rng(Some(7));
+        let config = MctsConfig {
+            rollout_policy: RolloutPolicy::RANDOM,
+            rollout_turns: 0,
+            ..Default::default()
+        };
+        let static_score = static_score(&state, 0.0, &config);
+        for _ in 0..20 {
+            assert_eq!(
+                static_score,
+                playout(&mut state, 0.0, &mut move_options, &config, &mut rng)
             );
         }
 
-        /*
-        Cut off after 25 million iterations
+        let config = MctsConfig {
+            rollout_turns: 3,
+            ..config
+        };
+        assert!((0..20).any(|_| {
+            playout(&mut state, 0.0, &mut move_options, &config, &mut rng) != static_score
+        }));
+    }
+
+    // every transposing node points at a node of the same depth that holds the statistics
+    // itself, and every entry of the table is such a node
+    fn assert_transpositions_are_consistent(tree: &MctsTree) {
+        for node in tree.nodes.iter() {
+            if let Some(statistics) = node.transposition {
+                let statistics = &tree.nodes[statistics];
+                assert_eq!(0, node.times_visited);
+                assert!(node.s1_options.is_none());
+                assert!(statistics.transposition.is_none());
+                assert_eq!(node.depth, statistics.depth);
+            }
+        }
+        for ((_, depth), index) in tree.transpositions.iter() {
+            assert_eq!(*depth, tree.nodes[*index].depth);
+            assert!(tree.nodes[*index].transposition.is_none());
+        }
+    }
+
+    #[test]
+    fn test_transposed_nodes_search_through_the_node_holding_their_statistics() {
+        let config = MctsConfig {
+            max_iterations: Some(2_000),
+            transposition_table: true,
+            seed: Some(7),
+            ..Default::default()
+        };
+        let mut state = hydro_pump_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        let mut tree = MctsTree::new(s1_options, s2_options);
+        tree.search(&mut state, search_duration(0), &config);
 
-        Under normal circumstances the bot will only run for 2.5-3.5 million iterations
-        however towards the end of a battle the bot may perform tens of millions of iterations
+        assert!(tree.nodes.iter().any(|node| node.transposition.is_some()));
+        assert_transpositions_are_consistent(&tree);
+        let root = &tree.nodes[ROOT_INDEX];
+        let s1_visits: u64 = root.s1_options.iter().flatten().map(|x| x.visits).sum();
+        assert_eq!(root.times_visited, s1_visits);
 
-        Beyond about 30 million iterations some floating point nonsense happens where
-        MoveNode.total_score stops updating because f32 does not have enough precision
+        // advancing keeps the transpositions within the subtree that is kept
+        let outcome = tree
+            .export(1, 1)
+            .children
+            .into_iter()
+            .max_by_key(|child| child.visits)
+            .unwrap();
+        let (s1_move, s2_move) = outcome.move_pair.unwrap();
+        state.apply_instructions(&outcome.instructions.instruction_list);
+        assert!(tree.advance(&state, &s1_move, &s2_move, &outcome.instructions));
+        assert!(tree.transposition_count() > 0);
+        assert_transpositions_are_consistent(&tree);
+        let result = tree.search(&mut state, search_duration(0), &config);
+        assert_eq!(outcome.visits + 2_000, result.iteration_count);
+    }
 
-        I can push the problem farther out by using f64 but if the bot is running for this long
-        then it almost certainly sees a forced win
-        */
-        if root_node.times_visited == 25_000_000 {
-            break;
+    #[test]
+    fn test_rollout_score_without_mix_is_the_static_score() {
+        let mut state = hydro_pump_state();
+        let mut move_options = MoveOptions::new();
+        let mut rng = search_rng(Some(7));
+        let config = MctsConfig {
+            rollout_policy: RolloutPolicy::RANDOM,
+            rollout_mix: 0.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            static_score(&state, 0.0, &config),
+            rollout_score(&mut state, 0.0, &mut move_options, &config, &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_solve_matrix_game_mixes_evenly_in_rock_paper_scissors() {
+        let payoffs = vec![
+            vec![0.5, 0.0, 1.0],
+            vec![1.0, 0.5, 0.0],
+            vec![0.0, 1.0, 0.5],
+        ];
+        let (s1, s2) = solve_matrix_game(&payoffs, 10_000);
+        for p in s1.iter().chain(s2.iter()) {
+            assert!((p - 1.0 / 3.0).abs() < 0.01, "{:?} {:?}", s1, s2);
         }
     }
 
-    let result = MctsResult {
-        s1: root_node
-            .s1_options
-            .as_ref()
-            .unwrap()
-            .iter()
-            .map(|v| MctsSideResult {
-                move_choice: v.move_choice.clone(),
-                total_score: v.total_score,
-                visits: v.visits,
-            })
-            .collect(),
-        s2: root_node
-            .s2_options
-            .as_ref()
-            .unwrap()
-            .iter()
-            .map(|v| MctsSideResult {
-                move_choice: v.move_choice.clone(),
-                total_score: v.total_score,
-                visits: v.visits,
-            })
-            .collect(),
-        iteration_count: root_node.times_visited,
-    };
+    #[test]
+    fn test_solve_matrix_game_never_plays_a_dominated_option() {
+        let payoffs = vec![vec![0.9, 0.6], vec![0.4, 0.2]];
+        let (s1, s2) = solve_matrix_game(&payoffs, 1_000);
+        assert!(s1[0] > 0.99, "{:?}", s1);
+        assert!(s2[1] > 0.99, "{:?}", s2);
+    }
 
-    result
+    #[test]
+    fn test_solve_matrix_game_with_an_uneven_mix() {
+        // side one is indifferent when side two plays its first option 1/3 of the time
+        let payoffs = vec![vec![0.0, 1.0], vec![1.0, 0.5]];
+        let (s1, s2) = solve_matrix_game(&payoffs, 10_000);
+        assert!((s1[0] - 1.0 / 3.0).abs() < 0.01, "{:?}", s1);
+        assert!((s2[0] - 1.0 / 3.0).abs() < 0.01, "{:?}", s2);
+    }
 }
@@ -0,0 +1,124 @@
+use crate::engine::state::MoveChoice;
+use crate::instruction::StateInstructions;
+use crate::state::{Side, SlotReference, State};
+use std::fmt::Write;
+
+/// A copy of the top of a search tree, see `MctsTree::export` and `SharedMctsTree::export`
+pub struct ExportedNode {
+    /// the options each side played from the parent node to get here, `None` at the root
+    pub move_pair: Option<((MoveChoice, MoveChoice), (MoveChoice, MoveChoice))>,
+
+    /// the outcome of `move_pair` that led here
+    pub instructions: StateInstructions,
+
+    pub visits: u64,
+
+    /// side one's average score over every visit to this node
+    pub average_score: f32,
+
+    pub children: Vec<ExportedNode>,
+}
+
+fn option_names(side: &Side, option: &(MoveChoice, MoveChoice)) -> (String, String) {
+    (
+        option.0.to_string(side, &SlotReference::SlotA),
+        option.1.to_string(side, &SlotReference::SlotB),
+    )
+}
+
+fn escaped(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl ExportedNode {
+    /// The tree as JSON. `state` is the state at the root, used to name each side's options
+    pub fn to_json(&self, state: &State) -> String {
+        let mut json = String::new();
+        self.write_json(&mut state.clone(), None, &mut json);
+        json
+    }
+
+    /// The tree as a Graphviz DOT digraph. `state` is the state at the root, used to name each
+    /// side's options
+    pub fn to_dot(&self, state: &State) -> String {
+        let mut dot = String::from("digraph mcts {\n");
+        self.write_dot(&mut state.clone(), &mut 0, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    // `names` are the names of `move_pair` in the parent's state
+    fn write_json(
+        &self,
+        state: &mut State,
+        names: Option<((String, String), (String, String))>,
+        json: &mut String,
+    ) {
+        json.push('{');
+        match names {
+            Some((s1, s2)) => {
+                let _ = write!(
+                    json,
+                    "\"s1_move\":[\"{}\",\"{}\"],\"s2_move\":[\"{}\",\"{}\"],",
+                    escaped(&s1.0),
+                    escaped(&s1.1),
+                    escaped(&s2.0),
+                    escaped(&s2.1)
+                );
+            }
+            None => json.push_str("\"s1_move\":null,\"s2_move\":null,"),
+        }
+        let _ = write!(
+            json,
+            "\"percentage\":{},\"visits\":{},\"average_score\":{},\"children\":[",
+            self.instructions.percentage, self.visits, self.average_score
+        );
+        for (index, child) in self.children.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let names = child.move_pair.map(|(s1, s2)| {
+                (
+                    option_names(&state.sides[0], &s1),
+                    option_names(&state.sides[1], &s2),
+                )
+            });
+            state.apply_instructions(&child.instructions.instruction_list);
+            child.write_json(state, names, json);
+            state.reverse_instructions(&child.instructions.instruction_list);
+        }
+        json.push_str("]}");
+    }
+
+    // writes this node and everything below it, numbering nodes from `next_id`
+    fn write_dot(&self, state: &mut State, next_id: &mut usize, dot: &mut String) {
+        let id = *next_id;
+        *next_id += 1;
+        let _ = writeln!(
+            dot,
+            "    n{} [label=\"visits: {}\\navg score: {:.3}\"];",
+            id, self.visits, self.average_score
+        );
+        for child in self.children.iter() {
+            let label = match child.move_pair {
+                Some((s1, s2)) => {
+                    let (s1_a, s1_b) = option_names(&state.sides[0], &s1);
+                    let (s2_a, s2_b) = option_names(&state.sides[1], &s2);
+                    format!(
+                        "s1: {} / {}\\ns2: {} / {}\\n{:.2}%",
+                        escaped(&s1_a),
+                        escaped(&s1_b),
+                        escaped(&s2_a),
+                        escaped(&s2_b),
+                        child.instructions.percentage
+                    )
+                }
+                None => String::new(),
+            };
+            let _ = writeln!(dot, "    n{} -> n{} [label=\"{}\"];", id, *next_id, label);
+            state.apply_instructions(&child.instructions.instruction_list);
+            child.write_dot(state, next_id, dot);
+            state.reverse_instructions(&child.instructions.instruction_list);
+        }
+    }
+}
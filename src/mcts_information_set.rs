@@ -0,0 +1,410 @@
+use crate::choices::{Choices, MOVES};
+use crate::engine::abilities::Abilities;
+use crate::engine::evaluate::evaluate;
+use crate::engine::generate_instructions::generate_instructions_from_move_pair;
+use crate::engine::items::Items;
+use crate::engine::state::{MoveChoice, MoveOptions};
+use crate::mcts::{
+    child_depth, iterations_left, rollout_score, sample_outcome, search_rng, LeafDepths,
+    MctsConfig, MctsResult, MctsSideResult, MoveNode, ScoreSum,
+};
+use crate::state::{Move, Pokemon, PokemonMoveIndex, PokemonNature, PokemonType, State};
+use rand::distr::weighted::WeightedIndex;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// One set a pokemon on side two might be running, weighted against the pokemon's other
+/// candidate sets
+#[derive(Debug, Clone)]
+pub struct OpponentSet {
+    pub weight: f32,
+    pub item: Items,
+    pub ability: Abilities,
+    pub nature: PokemonNature,
+    pub evs: (u8, u8, u8, u8, u8, u8),
+    pub tera_type: PokemonType,
+    pub moves: [Choices; 4],
+}
+
+impl OpponentSet {
+    // gives `pkmn` this set. `known` is the pokemon as it is in the searched state: its stats are
+    // moved by the difference in evs and nature, it keeps its share of its max hp, and any of
+    // its moves that are also in this set stay in their move slot so options keep meaning the
+    // same move
+    fn apply(&self, pkmn: &mut Pokemon, known: &Pokemon) {
+        pkmn.item = self.item;
+        pkmn.ability = self.ability;
+        pkmn.base_ability = self.ability;
+        pkmn.nature = self.nature.clone();
+        pkmn.evs = self.evs;
+        pkmn.tera_type = self.tera_type;
+
+        let stats = self.stats_from(known);
+        pkmn.maxhp = stats.0;
+        pkmn.hp = (known.hp as i32 * stats.0 as i32 / known.maxhp.max(1) as i32) as i16;
+        pkmn.attack = stats.1;
+        pkmn.defense = stats.2;
+        pkmn.special_attack = stats.3;
+        pkmn.special_defense = stats.4;
+        pkmn.speed = stats.5;
+
+        let mut unplaced = self
+            .moves
+            .iter()
+            .filter(|id| **id != Choices::NONE && !known.moves.into_iter().any(|m| m.id == **id));
+        for move_index in [
+            PokemonMoveIndex::M0,
+            PokemonMoveIndex::M1,
+            PokemonMoveIndex::M2,
+            PokemonMoveIndex::M3,
+        ]
+        .iter()
+        {
+            let known_move = &known.moves[move_index];
+            pkmn.moves[move_index] =
+                if known_move.id != Choices::NONE && self.moves.contains(&known_move.id) {
+                    known_move.clone()
+                } else {
+                    let id = unplaced.next().copied().unwrap_or(Choices::NONE);
+                    Move {
+                        id,
+                        choice: MOVES.get(&id).unwrap().clone(),
+                        ..Default::default()
+                    }
+                };
+        }
+    }
+
+    // base stats are only known for a few pokemon, so stats come from undoing `known`'s
+    // nature and evs rather than from the stat formula. Rounding can leave them a point off
+    fn stats_from(&self, known: &Pokemon) -> (i16, i16, i16, i16, i16, i16) {
+        let level = known.level as i32;
+        let ev_change = |new: u8, old: u8| (2 * (new as i32 - old as i32) * level / 100) as i16;
+        let without_nature =
+            |stat: i16, multiplier: i16| (stat as i32 * 1000 / multiplier as i32) as i16;
+        let multipliers = known
+            .nature
+            .apply_to_stats((1000, 1000, 1000, 1000, 1000, 1000));
+
+        let stats = self.nature.apply_to_stats((
+            0,
+            without_nature(known.attack, multipliers.1) + ev_change(self.evs.1, known.evs.1),
+            without_nature(known.defense, multipliers.2) + ev_change(self.evs.2, known.evs.2),
+            without_nature(known.special_attack, multipliers.3)
+                + ev_change(self.evs.3, known.evs.3),
+            without_nature(known.special_defense, multipliers.4)
+                + ev_change(self.evs.4, known.evs.4),
+            without_nature(known.speed, multipliers.5) + ev_change(self.evs.5, known.evs.5),
+        ));
+        (
+            known.maxhp + ev_change(self.evs.0, known.evs.0),
+            stats.1,
+            stats.2,
+            stats.3,
+            stats.4,
+            stats.5,
+        )
+    }
+}
+
+struct InformationSetOption {
+    stats: MoveNode,
+
+    // the number of times this option was legal when its node was selected through
+    availability: u64,
+}
+
+// nodes are keyed by the pairs of options that lead to them, so every sampled state and every
+// chance outcome reached through the same options shares a node
+#[derive(Default)]
+struct InformationSetNode {
+    times_visited: u64,
+    s1_options: Vec<InformationSetOption>,
+    s2_options: Vec<InformationSetOption>,
+    children: HashMap<(u16, u16), InformationSetNode>,
+}
+
+// picks the available option with the highest ucb1, counting visits against availability
+// rather than the node's visits since an option may only be legal in some sampled states
+fn select_available(
+    options: &mut Vec<InformationSetOption>,
+    available: &[(MoveChoice, MoveChoice)],
+    exploration_constant: f32,
+) -> usize {
+    let mut choice = 0;
+    let mut best_ucb1 = f32::MIN;
+    for move_choice in available {
+        let index = match options
+            .iter()
+            .position(|x| x.stats.move_choice == *move_choice)
+        {
+            Some(index) => index,
+            None => {
+                options.push(InformationSetOption {
+                    stats: MoveNode {
+                        move_choice: *move_choice,
+                        total_score: ScoreSum::default(),
+                        visits: 0,
+                        policy_weight: 0.0,
+                    },
+                    availability: 0,
+                });
+                options.len() - 1
+            }
+        };
+        let option = &mut options[index];
+        option.availability += 1;
+        let this_ucb1 = option.stats.ucb1(option.availability, exploration_constant);
+        if this_ucb1 > best_ucb1 {
+            best_ucb1 = this_ucb1;
+            choice = index;
+        }
+    }
+    choice
+}
+
+struct Search<'a, R: Rng> {
+    root_eval: f32,
+    side_one_options: &'a [(MoveChoice, MoveChoice)],
+    move_options: MoveOptions,
+    config: &'a MctsConfig,
+    node_count: usize,
+    leaf_depths: LeafDepths,
+    rng: R,
+}
+
+impl<R: Rng> Search<'_, R> {
+    // one iteration through `node`, returning side one's score.
+    // `state` is left as it was
+    fn visit(
+        &mut self,
+        node: &mut InformationSetNode,
+        state: &mut State,
+        depth: u8,
+        root: bool,
+    ) -> f32 {
+        let (s1_available, s2_available) = if root {
+            (
+                self.side_one_options.to_vec(),
+                state.root_get_all_options().1,
+            )
+        } else {
+            state.get_all_options(&mut self.move_options);
+            (
+                self.move_options
+                    .side_one_combined_options
+                    .drain(..)
+                    .collect(),
+                self.move_options
+                    .side_two_combined_options
+                    .drain(..)
+                    .collect(),
+            )
+        };
+        if !root && node.times_visited == 0
+            || state.battle_is_over() != 0.0
+            || depth >= self.config.max_depth
+            || s1_available.is_empty()
+            || s2_available.is_empty()
+        {
+            node.times_visited += 1;
+            self.leaf_depths.add(depth);
+            return rollout_score(
+                state,
+                self.root_eval,
+                &mut self.move_options,
+                self.config,
+                &mut self.rng,
+            );
+        }
+
+        let exploration_constant = self.config.exploration_constant;
+        let s1_index = select_available(&mut node.s1_options, &s1_available, exploration_constant);
+        let s2_index = select_available(&mut node.s2_options, &s2_available, exploration_constant);
+        let s1_move = node.s1_options[s1_index].stats.move_choice;
+        let s2_move = node.s2_options[s2_index].stats.move_choice;
+
+        let mut outcomes = generate_instructions_from_move_pair(
+            state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, false,
+        );
+        let outcome = outcomes.swap_remove(sample_outcome(&outcomes, &mut self.rng));
+        state.apply_instructions(&outcome.instruction_list);
+        let depth = child_depth(depth, &outcome);
+        let key = (
+            node.s1_options[s1_index].stats.to_u16(),
+            node.s2_options[s2_index].stats.to_u16(),
+        );
+        let child = node.children.entry(key).or_insert_with(|| {
+            self.node_count += 1;
+            InformationSetNode::default()
+        });
+        let score = self.visit(child, state, depth, false);
+        state.reverse_instructions(&outcome.instruction_list);
+
+        node.times_visited += 1;
+        let s1_stats = &mut node.s1_options[s1_index].stats;
+        s1_stats.total_score.add(score);
+        s1_stats.visits += 1;
+        let s2_stats = &mut node.s2_options[s2_index].stats;
+        s2_stats.total_score.add(1.0 - score);
+        s2_stats.visits += 1;
+        score
+    }
+}
+
+fn side_result(options: &[InformationSetOption]) -> Vec<MctsSideResult> {
+    options
+        .iter()
+        .map(|x| MctsSideResult {
+            move_choice: x.stats.move_choice,
+            total_score: x.stats.total_score.total() as f32,
+            visits: x.stats.visits,
+            nash_probability: None,
+        })
+        .collect()
+}
+
+/// Searches a state where side two's sets are only partly known.
+///
+/// Every iteration samples one of `opponent_sets[i]` by weight for each of side two's pokemon
+/// `i` and searches that state. A pokemon with no candidate sets is searched as it is in `state`.
+/// Nodes are keyed by the options that lead to them, so their statistics are aggregated over
+/// every sampled state (and every chance outcome) that reaches them. An option that is only legal
+/// in some sampled states is scored against how often it was available.
+///
+/// Side two's results refer to move slots, which are only named by `state` for moves it already
+/// knows about. Of `config`, `selection_policy`, `puct_constant`, `prior_callback`,
+/// `progressive_widening`, `nash_iterations`, `virtual_loss_visits`,
+/// `min_visits_before_selection`, `early_stop`, `transposition_table`, `max_memory`,
+/// `principal_variation_plies` and the `endgame_*` fields are ignored
+pub fn perform_mcts_information_set(
+    state: &mut State,
+    side_one_options: Vec<(MoveChoice, MoveChoice)>,
+    opponent_sets: &[Vec<OpponentSet>; 6],
+    max_time: Duration,
+    config: &MctsConfig,
+) -> MctsResult {
+    let known = state.sides[1].pokemon.pkmn.clone();
+    let set_distributions: Vec<Option<WeightedIndex<f32>>> = opponent_sets
+        .iter()
+        .map(|sets| {
+            if sets.is_empty() {
+                None
+            } else {
+                Some(
+                    WeightedIndex::new(sets.iter().map(|x| x.weight))
+                        .expect("opponent set weights must be non-negative and not all zero"),
+                )
+            }
+        })
+        .collect();
+
+    let mut root = InformationSetNode::default();
+    let mut search = Search {
+        root_eval: evaluate(state),
+        side_one_options: &side_one_options,
+        move_options: MoveOptions::new(),
+        config,
+        node_count: 1,
+        leaf_depths: LeafDepths::default(),
+        rng: search_rng(config.seed),
+    };
+    let start_time = Instant::now();
+    'search: while start_time.elapsed() < max_time {
+        for _ in 0..1000 {
+            if iterations_left(root.times_visited, config) == 0
+                || matches!(config.max_nodes, Some(max_nodes) if search.node_count >= max_nodes)
+            {
+                break 'search;
+            }
+            for (index, distribution) in set_distributions.iter().enumerate() {
+                if let Some(distribution) = distribution {
+                    let set = &opponent_sets[index][distribution.sample(&mut search.rng)];
+                    set.apply(&mut state.sides[1].pokemon.pkmn[index], &known[index]);
+                }
+            }
+            search.visit(&mut root, state, 0, true);
+        }
+    }
+    state.sides[1].pokemon.pkmn = known;
+
+    MctsResult {
+        s1: side_result(&root.s1_options),
+        s2: side_result(&root.s2_options),
+        iteration_count: root.times_visited,
+        principal_variation: Vec::new(),
+        max_leaf_depth: search.leaf_depths.max,
+        average_leaf_depth: search.leaf_depths.average(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opponent_set(moves: [Choices; 4]) -> OpponentSet {
+        OpponentSet {
+            weight: 1.0,
+            item: Items::CHOICESCARF,
+            ability: Abilities::INTIMIDATE,
+            nature: PokemonNature::TIMID,
+            evs: (0, 0, 0, 32, 2, 32),
+            tera_type: PokemonType::FIRE,
+            moves,
+        }
+    }
+
+    #[test]
+    fn test_apply_keeps_known_moves_in_their_slot() {
+        let mut known = Pokemon::default();
+        known.moves.m1 = Move {
+            id: Choices::TACKLE,
+            choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+            ..Default::default()
+        };
+        let mut pkmn = known.clone();
+
+        opponent_set([
+            Choices::FLAMETHROWER,
+            Choices::TACKLE,
+            Choices::PROTECT,
+            Choices::NONE,
+        ])
+        .apply(&mut pkmn, &known);
+
+        assert_eq!(Choices::FLAMETHROWER, pkmn.moves.m0.id);
+        assert_eq!(Choices::TACKLE, pkmn.moves.m1.id);
+        assert_eq!(Choices::PROTECT, pkmn.moves.m2.id);
+        assert_eq!(Choices::NONE, pkmn.moves.m3.id);
+    }
+
+    #[test]
+    fn test_apply_moves_stats_by_the_change_in_evs_and_nature() {
+        let known = Pokemon {
+            level: 50,
+            maxhp: 150,
+            hp: 75,
+            attack: 100,
+            defense: 100,
+            special_attack: 100,
+            speed: 100,
+            nature: PokemonNature::SERIOUS,
+            evs: (0, 0, 0, 0, 0, 0),
+            ..Default::default()
+        };
+        let mut pkmn = known.clone();
+
+        opponent_set([Choices::TACKLE, Choices::NONE, Choices::NONE, Choices::NONE])
+            .apply(&mut pkmn, &known);
+
+        assert_eq!(Items::CHOICESCARF, pkmn.item);
+        assert_eq!(Abilities::INTIMIDATE, pkmn.ability);
+        assert_eq!(150, pkmn.maxhp);
+        assert_eq!(75, pkmn.hp);
+        assert_eq!(90, pkmn.attack);
+        assert_eq!(100, pkmn.defense);
+        assert_eq!(132, pkmn.special_attack);
+        assert_eq!(145, pkmn.speed);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::endgame::{is_endgame, solve_endgame};
+use crate::engine::state::MoveChoice;
+use crate::mcts::{solve_root_nash, MctsConfig, MctsResult, MctsSideResult, MctsTree, ScoreSum};
+use crate::state::State;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `tree_count` copies of `config` for `perform_mcts_root_parallel`. When `config.seed` is set
+/// each copy gets its own seed derived from it, otherwise every tree is seeded at random
+pub fn root_parallel_configs(config: &MctsConfig, tree_count: usize) -> Vec<MctsConfig> {
+    (0..tree_count as u64)
+        .map(|tree| MctsConfig {
+            seed: config.seed.map(|seed| seed.wrapping_add(tree)),
+            ..config.clone()
+        })
+        .collect()
+}
+
+// every tree's statistics for each of `options`, matched by move choice
+fn merged_side(
+    options: &[(MoveChoice, MoveChoice)],
+    results: &[&[MctsSideResult]],
+) -> Vec<MctsSideResult> {
+    options
+        .iter()
+        .map(|move_choice| {
+            let mut merged = MctsSideResult {
+                move_choice: *move_choice,
+                total_score: 0.0,
+                visits: 0,
+                nash_probability: None,
+            };
+            for x in results
+                .iter()
+                .flat_map(|side| side.iter())
+                .filter(|x| x.move_choice == *move_choice)
+            {
+                merged.total_score += x.total_score;
+                merged.visits += x.visits;
+            }
+            merged
+        })
+        .collect()
+}
+
+/// Searches one independent `MctsTree` per config on its own thread and merges the root
+/// statistics of every tree once they all stop.
+///
+/// Nothing is shared between the trees while they search, so unlike
+/// `perform_mcts_shared_tree` there is no contention between threads, at the cost of every
+/// tree searching the same states again. Each option's visits and total score are the sums
+/// over every tree, `iteration_count` is the sum of every tree's iterations and the leaf depths
+/// are over every tree's iterations.
+///
+/// The first config decides whether `state` is solved with `solve_endgame` instead, and its
+/// `nash_iterations` solve the root's Nash equilibrium from the merged statistics. The
+/// principal variation is the one of the tree that ran the most iterations. Use
+/// `root_parallel_configs` to search several copies of a single config
+pub fn perform_mcts_root_parallel(
+    state: &mut State,
+    side_one_options: Vec<(MoveChoice, MoveChoice)>,
+    side_two_options: Vec<(MoveChoice, MoveChoice)>,
+    max_time: Duration,
+    configs: &[MctsConfig],
+) -> MctsResult {
+    let first_config = configs
+        .first()
+        .expect("root parallel search needs at least one config");
+    let start_time = Instant::now();
+    if is_endgame(state, first_config) {
+        if let Some(result) = solve_endgame(
+            state,
+            &side_one_options,
+            &side_two_options,
+            max_time,
+            first_config,
+        ) {
+            return result;
+        }
+    }
+    let max_time = max_time.saturating_sub(start_time.elapsed());
+
+    let searches: Vec<(MctsTree, MctsResult)> = thread::scope(|scope| {
+        let handles: Vec<_> = configs
+            .iter()
+            .map(|config| {
+                let mut state = state.clone();
+                let side_one_options = side_one_options.clone();
+                let side_two_options = side_two_options.clone();
+                scope.spawn(move || {
+                    // the root's equilibrium is solved once, from the merged statistics
+                    let config = MctsConfig {
+                        nash_iterations: 0,
+                        ..config.clone()
+                    };
+                    let mut tree = MctsTree::new(side_one_options, side_two_options);
+                    let result = tree.search(&mut state, max_time, &config);
+                    (tree, result)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("a search thread panicked"))
+            .collect()
+    });
+
+    let iteration_count = searches.iter().map(|(_, x)| x.iteration_count).sum();
+    let s1_results: Vec<&[MctsSideResult]> = searches.iter().map(|(_, x)| &x.s1[..]).collect();
+    let s2_results: Vec<&[MctsSideResult]> = searches.iter().map(|(_, x)| &x.s2[..]).collect();
+    let mut result = MctsResult {
+        s1: merged_side(&side_one_options, &s1_results),
+        s2: merged_side(&side_two_options, &s2_results),
+        iteration_count,
+        principal_variation: Vec::new(),
+        max_leaf_depth: searches
+            .iter()
+            .map(|(_, x)| x.max_leaf_depth)
+            .max()
+            .unwrap_or(0),
+        average_leaf_depth: if iteration_count == 0 {
+            0.0
+        } else {
+            let total: f64 = searches
+                .iter()
+                .map(|(_, x)| x.average_leaf_depth as f64 * x.iteration_count as f64)
+                .sum();
+            (total / iteration_count as f64) as f32
+        },
+    };
+
+    if first_config.nash_iterations > 0 {
+        let s1_moves: Vec<(MoveChoice, MoveChoice)> =
+            result.s1.iter().map(|x| x.move_choice).collect();
+        let s2_moves: Vec<(MoveChoice, MoveChoice)> =
+            result.s2.iter().map(|x| x.move_choice).collect();
+        solve_root_nash(
+            &mut result,
+            |i, j| {
+                searches
+                    .iter()
+                    .map(|(tree, _)| tree.root_move_pair_stats(&s1_moves[i], &s2_moves[j]))
+                    .fold((ScoreSum::default(), 0), |(score, visits), (x, y)| {
+                        (ScoreSum(score.0 + x.0), visits + y)
+                    })
+            },
+            first_config.nash_iterations,
+        );
+    }
+
+    if let Some((_, deepest)) = searches.into_iter().max_by_key(|(_, x)| x.iteration_count) {
+        result.principal_variation = deepest.principal_variation;
+    }
+    result
+}
@@ -0,0 +1,258 @@
+use crate::choices::{Choices, MoveCategory};
+use crate::engine::damage_calc::type_effectiveness_modifier;
+use crate::engine::state::MoveChoice;
+use crate::mcts::{perform_mcts, MctsConfig, MctsResult, MctsSideResult};
+use crate::mcts_threaded::perform_mcts_shared_tree;
+use crate::state::{pokemon_index_iter, Pokemon, PokemonIndex, Side, SideReference, State};
+use std::time::Duration;
+
+// a lead counts this many times as much as a pokemon in the back towards a team's matchup
+const LEAD_WEIGHT: f32 = 2.0;
+
+/// One team a side can bring at team preview, see `perform_mcts_team_preview`
+#[derive(Debug, Clone)]
+pub struct TeamPreviewTeam {
+    /// the two leads, then the two pokemon in the back
+    pub team: [PokemonIndex; 4],
+
+    /// the team's `team_preview_matchup`, which teams are pruned by
+    pub matchup: f32,
+    pub visits: u64,
+
+    /// the share of the search this team got: its `nash_probability` when one was solved,
+    /// otherwise its share of the side's visits
+    pub probability: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TeamPreviewSideResult {
+    /// every team that was searched, most probable first
+    pub teams: Vec<TeamPreviewTeam>,
+
+    /// the probability of bringing each of the side's pokemon, by party index
+    pub brings: [f32; 6],
+
+    /// the probability of leading with each of the side's pokemon, by party index
+    pub leads: [f32; 6],
+}
+
+pub struct TeamPreviewResult {
+    pub s1: TeamPreviewSideResult,
+    pub s2: TeamPreviewSideResult,
+
+    /// the search over the teams that were kept, with one option per team
+    pub search: MctsResult,
+}
+
+/// Every team `side` can bring at team preview: each pair of leads with each pair of the
+/// remaining pokemon in the back, which is 90 teams for a party of six. Pokemon without any hp
+/// are only brought when there are not four others to bring
+pub fn all_team_preview_teams(side: &Side) -> Vec<[PokemonIndex; 4]> {
+    let mut party: Vec<PokemonIndex> = pokemon_index_iter().collect();
+    party.sort_by_key(|index| side.pokemon[*index].hp <= 0);
+    let healthy = party
+        .iter()
+        .filter(|index| side.pokemon[**index].hp > 0)
+        .count();
+    party.truncate(healthy.max(4));
+
+    let mut teams = Vec::new();
+    for (a, first_lead) in party.iter().enumerate() {
+        for second_lead in party[a + 1..].iter() {
+            let back: Vec<PokemonIndex> = party
+                .iter()
+                .copied()
+                .filter(|index| index != first_lead && index != second_lead)
+                .collect();
+            for (c, first_back) in back.iter().enumerate() {
+                for second_back in back[c + 1..].iter() {
+                    teams.push([*first_lead, *second_lead, *first_back, *second_back]);
+                }
+            }
+        }
+    }
+    teams
+}
+
+// the expected base power of `attacker`'s best damaging move against `defender`, scaled by STAB
+// and type effectiveness
+fn best_move_power(attacker: &Pokemon, defender: &Pokemon) -> f32 {
+    attacker
+        .moves
+        .into_iter()
+        .filter(|mv| mv.id != Choices::NONE && mv.choice.category != MoveCategory::Status)
+        .map(|mv| {
+            let stab = if attacker.has_type(&mv.choice.move_type) {
+                1.5
+            } else {
+                1.0
+            };
+            mv.choice.base_power * mv.choice.accuracy / 100.0
+                * stab
+                * type_effectiveness_modifier(&mv.choice.move_type, defender)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// A cheap estimate of how well `team` matches up against the other side's whole party: the
+/// power each of its pokemon hits the other side's pokemon with less the power it is hit with,
+/// averaged over the other side's pokemon and with the leads counted twice
+pub fn team_preview_matchup(
+    state: &State,
+    side_ref: SideReference,
+    team: &[PokemonIndex; 4],
+) -> f32 {
+    let (side, opponent) = state.get_both_sides_immutable(side_ref);
+    let opponents: Vec<&Pokemon> = opponent.pokemon.pkmn.iter().filter(|x| x.hp > 0).collect();
+    if opponents.is_empty() {
+        return 0.0;
+    }
+    team.iter()
+        .enumerate()
+        .map(|(position, index)| {
+            let pkmn = &side.pokemon[*index];
+            let matchup: f32 = opponents
+                .iter()
+                .map(|other| best_move_power(pkmn, other) - best_move_power(other, pkmn))
+                .sum();
+            let weight = if position < 2 { LEAD_WEIGHT } else { 1.0 };
+            weight * matchup / opponents.len() as f32
+        })
+        .sum()
+}
+
+// `side_ref`'s teams with the best matchups, best first. `keep` of 0 keeps every team
+fn kept_teams(
+    state: &State,
+    side_ref: SideReference,
+    keep: usize,
+) -> Vec<([PokemonIndex; 4], f32)> {
+    let mut teams: Vec<([PokemonIndex; 4], f32)> =
+        all_team_preview_teams(&state.sides[side_ref as usize])
+            .into_iter()
+            .map(|team| (team, team_preview_matchup(state, side_ref, &team)))
+            .collect();
+    teams.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if keep > 0 {
+        teams.truncate(keep);
+    }
+    teams
+}
+
+fn side_result(
+    teams: &[([PokemonIndex; 4], f32)],
+    options: &[(MoveChoice, MoveChoice)],
+    results: &[MctsSideResult],
+) -> TeamPreviewSideResult {
+    let total_visits: u64 = results.iter().map(|x| x.visits).sum();
+    let mut side = TeamPreviewSideResult {
+        teams: Vec::with_capacity(results.len()),
+        brings: [0.0; 6],
+        leads: [0.0; 6],
+    };
+    for x in results {
+        let (team, matchup) = match options.iter().position(|option| *option == x.move_choice) {
+            Some(index) => teams[index],
+            None => continue,
+        };
+        let probability = x.nash_probability.unwrap_or(if total_visits == 0 {
+            0.0
+        } else {
+            x.visits as f32 / total_visits as f32
+        });
+        for (position, index) in team.iter().enumerate() {
+            side.brings[*index as usize] += probability;
+            if position < 2 {
+                side.leads[*index as usize] += probability;
+            }
+        }
+        side.teams.push(TeamPreviewTeam {
+            team,
+            matchup,
+            visits: x.visits,
+            probability,
+        });
+    }
+    side.teams
+        .sort_by(|a, b| b.probability.total_cmp(&a.probability));
+    side
+}
+
+/// Searches team preview over every team each side can bring, see `all_team_preview_teams`,
+/// instead of teams picked by the caller.
+///
+/// Each side's teams are ranked by `team_preview_matchup` and only the best `keep` of them
+/// (every team when `keep` is 0) are searched, with a shared tree when `worker_count` is more
+/// than 1. Both sides get a distribution over the teams that were searched, and over which of
+/// their pokemon are brought and lead.
+///
+/// Panics unless `state.team_preview` is set
+pub fn perform_mcts_team_preview(
+    state: &mut State,
+    max_time: Duration,
+    keep: usize,
+    worker_count: usize,
+    config: &MctsConfig,
+) -> TeamPreviewResult {
+    if !state.team_preview {
+        panic!("perform_mcts_team_preview needs a state in team preview");
+    }
+    let s1_teams = kept_teams(state, SideReference::SideOne, keep);
+    let s2_teams = kept_teams(state, SideReference::SideTwo, keep);
+    let s1_options = State::generate_team_preview_options(s1_teams.iter().map(|x| x.0).collect());
+    let s2_options = State::generate_team_preview_options(s2_teams.iter().map(|x| x.0).collect());
+
+    let search = if worker_count > 1 {
+        perform_mcts_shared_tree(
+            state,
+            s1_options.clone(),
+            s2_options.clone(),
+            max_time,
+            worker_count,
+            config,
+        )
+    } else {
+        perform_mcts(
+            state,
+            s1_options.clone(),
+            s2_options.clone(),
+            max_time,
+            config,
+        )
+    };
+
+    TeamPreviewResult {
+        s1: side_result(&s1_teams, &s1_options, &search.s1),
+        s2: side_result(&s2_teams, &s2_options, &search.s2),
+        search,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_full_party_has_90_teams() {
+        let state = State::default();
+
+        let teams = all_team_preview_teams(&state.sides[0]);
+
+        assert_eq!(90, teams.len());
+        let options = State::generate_team_preview_options(teams);
+        for (i, option) in options.iter().enumerate() {
+            assert!(!options[i + 1..].contains(option));
+        }
+    }
+
+    #[test]
+    fn test_pokemon_without_hp_are_only_brought_to_fill_a_team() {
+        let mut state = State::default();
+        state.sides[0].pokemon.pkmn[5].hp = 0;
+
+        let teams = all_team_preview_teams(&state.sides[0]);
+
+        assert_eq!(30, teams.len());
+        assert!(teams.iter().all(|team| !team.contains(&PokemonIndex::P5)));
+    }
+}
@@ -1,85 +1,271 @@
+use crate::endgame::{is_endgame, solve_endgame};
 use crate::engine::evaluate::evaluate;
 use crate::engine::generate_instructions::generate_instructions_from_move_pair;
 use crate::engine::state::{MoveChoice, MoveOptions};
-use crate::instruction::StateInstructions;
-use crate::mcts::{MctsResult, MctsSideResult};
-use crate::state::State;
+use crate::instruction::{Instruction, StateInstructions};
+use crate::mcts::{
+    child_depth, index_of_max, iterations_left, most_visited_is_decided, open_option_count,
+    open_option_index, option_priors, policy_weight_change, prior_order, remaining_iterations,
+    rollout_score, sample_from_policy, search_rng, select_factorized, slot_choices,
+    solve_root_nash, LeafDepths, MctsConfig, MctsResult, MctsSideResult, PrincipalVariationPly,
+    ScoreSum, SelectionPolicy,
+};
+use crate::mcts_export::ExportedNode;
+use crate::state::{SideReference, SlotReference, State};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use rand::prelude::*;
-use rand::rng;
-use std::sync::atomic::{AtomicI8, AtomicU32, AtomicU64, Ordering};
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::ops::Range;
+use std::sync::atomic::{
+    AtomicBool, AtomicI8, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+};
 use std::sync::{Arc, OnceLock};
-use std::thread;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
 const MCTS_DEADLINE_CHECK_INTERVAL: u32 = 1_000;
-const MCTS_MAX_ITERATIONS_PER_TREE: u32 = 25_000_000;
-const MCTS_MAX_DEPTH: u8 = 5;
-const SCORE_SCALE: f32 = 400.0;
-
-// added to a MoveNode's `visits` only, so while a
-// thread is in flight through that move it reads like this many extra losing
-// playouts: its ucb1 drops and other threads are steered onto different moves.
-// this is purely a diversification deterrent, so the magnitude is a tuning knob
-const VIRTUAL_LOSS_VISITS: u32 = 3;
 
 // node map type alias for clarity.
-// key: (parent node address, s1_move_index, s2_move_index)
+// key: (parent node index, s1_move_index, s2_move_index)
 // value: the branch (weighted list of outcome nodes for that move pair)
-type ChildMap = DashMap<(usize, usize, usize), SharedBranch>;
+type ChildMap = DashMap<(NodeIndex, usize, usize), SharedBranch>;
+
+// the index of a node in its tree's `NodeArena`
+type NodeIndex = u32;
+
+// the root is always the first node of the arena
+const ROOT: NodeIndex = 0;
+
+// the first chunk of a `NodeArena` holds 2^ARENA_FIRST_CHUNK_BITS nodes and every chunk after
+// it twice as many as the one before, so `ARENA_CHUNKS` chunks cover every `NodeIndex`
+const ARENA_FIRST_CHUNK_BITS: u32 = 10;
+const ARENA_CHUNKS: usize = (u32::BITS - ARENA_FIRST_CHUNK_BITS + 1) as usize;
+const ARENA_CAPACITY: u64 = u32::MAX as u64 + 1;
+
+// an append-only arena holding every node of a `SharedMctsTree`. a chunk never moves once it
+// is allocated, so workers can read nodes while others add new ones. a node is set before its
+// index is shared through the `ChildMap`, and is never replaced while a search runs
+struct NodeArena {
+    chunks: [OnceLock<Box<[OnceLock<Node>]>>; ARENA_CHUNKS],
+    len: AtomicU64,
+}
+
+impl NodeArena {
+    fn new() -> Self {
+        Self {
+            chunks: std::array::from_fn(|_| OnceLock::new()),
+            len: AtomicU64::new(0),
+        }
+    }
+
+    // the chunk holding `index` and its offset in that chunk
+    fn location(index: NodeIndex) -> (usize, usize) {
+        let position = index as u64 + (1 << ARENA_FIRST_CHUNK_BITS);
+        let chunk = u64::BITS - 1 - position.leading_zeros() - ARENA_FIRST_CHUNK_BITS;
+        let offset = position - (1 << (chunk + ARENA_FIRST_CHUNK_BITS));
+        (chunk as usize, offset as usize)
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire) as usize
+    }
+
+    fn get(&self, index: NodeIndex) -> &Node {
+        let (chunk, offset) = Self::location(index);
+        self.chunks[chunk]
+            .get()
+            .and_then(|chunk| chunk[offset].get())
+            .expect("a node is set before its index is shared")
+    }
+
+    // adds `nodes` next to each other and returns the index of the first one, or `None` if
+    // the arena has no room left for them
+    fn push_all(&self, nodes: Vec<Node>) -> Option<NodeIndex> {
+        let count = nodes.len() as u64;
+        let start = self
+            .len
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |len| {
+                len.checked_add(count).filter(|end| *end <= ARENA_CAPACITY)
+            })
+            .ok()?;
+        for (index, node) in (start..).zip(nodes) {
+            let (chunk, offset) = Self::location(index as NodeIndex);
+            let slots = self.chunks[chunk].get_or_init(|| {
+                (0..1usize << (chunk as u32 + ARENA_FIRST_CHUNK_BITS))
+                    .map(|_| OnceLock::new())
+                    .collect()
+            });
+            // every index is handed out once, so the slot is always empty
+            let _ = slots[offset].set(node);
+        }
+        Some(start as NodeIndex)
+    }
+
+    // the node holding the statistics of `index`: itself unless it transposes to another node
+    fn transposed(&self, index: NodeIndex) -> NodeIndex {
+        self.get(index)
+            .transposition
+            .get()
+            .copied()
+            .unwrap_or(index)
+    }
+
+    fn get_mut(&mut self, index: NodeIndex) -> &mut Node {
+        let (chunk, offset) = Self::location(index);
+        self.chunks[chunk]
+            .get_mut()
+            .and_then(|chunk| chunk[offset].get_mut())
+            .expect("a node is set before its index is shared")
+    }
+
+    // moves the node at `index` out of the arena
+    fn take(&mut self, index: NodeIndex) -> Option<Node> {
+        let (chunk, offset) = Self::location(index);
+        self.chunks[chunk].get_mut()?[offset].take()
+    }
+}
+
+// keyed by a state's hash and the depth it was reached at, holding the index of the node that
+// keeps the statistics of that state, see `MctsConfig::transposition_table`
+type SharedTranspositionTable = DashMap<(u64, u8), NodeIndex>;
+
+// a `ScoreSum` that every worker adds to
+#[derive(Default)]
+struct AtomicScoreSum(AtomicU64);
+
+impl AtomicScoreSum {
+    fn add(&self, score: f32) {
+        self.0
+            .fetch_add(ScoreSum::fixed_point(score), Ordering::AcqRel);
+    }
+
+    fn load(&self) -> ScoreSum {
+        ScoreSum(self.0.load(Ordering::Acquire))
+    }
+}
 
-fn sigmoid(x: f32) -> f32 {
-    // Tuned so that ~400 points is very close to 1.0
-    1.0 / (1.0 + (-0.0062 * x).exp())
+// the `LeafDepths` of a search that every worker adds to
+#[derive(Default)]
+struct AtomicLeafDepths {
+    max: AtomicU8,
+    total: AtomicU64,
+    count: AtomicU64,
+}
+
+impl AtomicLeafDepths {
+    fn add(&self, depth: u8) {
+        self.max.fetch_max(depth, Ordering::AcqRel);
+        self.total.fetch_add(depth as u64, Ordering::AcqRel);
+        self.count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn reset(&self) {
+        self.max.store(0, Ordering::Release);
+        self.total.store(0, Ordering::Release);
+        self.count.store(0, Ordering::Release);
+    }
+
+    fn load(&self) -> LeafDepths {
+        LeafDepths {
+            max: self.max.load(Ordering::Acquire),
+            total: self.total.load(Ordering::Acquire),
+            count: self.count.load(Ordering::Acquire),
+        }
+    }
 }
 
 pub struct MoveNode {
     move_choice: (MoveChoice, MoveChoice),
-    total_score: AtomicU64,
-    visits: AtomicU32,
+    total_score: AtomicScoreSum,
+    visits: AtomicU64,
+
+    // the bits of an f32, only used by the `EXP3` and `REGRETMATCHING` selection policies
+    policy_weight: AtomicU32,
 }
 
 impl MoveNode {
     fn new(move_choice: (MoveChoice, MoveChoice)) -> Self {
         Self {
             move_choice,
-            total_score: AtomicU64::new(0),
-            visits: AtomicU32::new(0),
+            total_score: AtomicScoreSum::default(),
+            visits: AtomicU64::new(0),
+            policy_weight: AtomicU32::new(0.0f32.to_bits()),
         }
     }
 
-    fn add_virtual_loss(&self) {
-        self.visits.fetch_add(VIRTUAL_LOSS_VISITS, Ordering::AcqRel);
+    fn policy_weight(&self) -> f32 {
+        f32::from_bits(self.policy_weight.load(Ordering::Acquire))
     }
 
-    fn remove_virtual_loss(&self) {
-        self.visits.fetch_sub(VIRTUAL_LOSS_VISITS, Ordering::AcqRel);
+    fn add_policy_weight(&self, change: f32) {
+        let _ = self
+            .policy_weight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+                Some((f32::from_bits(bits) + change).to_bits())
+            });
     }
 
-    fn add_result(&self, score: f32) {
-        self.total_score
-            .fetch_add((score * SCORE_SCALE).round() as u64, Ordering::AcqRel);
-        self.visits.fetch_add(1, Ordering::AcqRel);
+    // `MctsConfig::virtual_loss_visits` is added to `visits` only, so while a thread is in
+    // flight through this move it reads like that many extra losing playouts: its ucb1 drops
+    // and other threads are steered onto different moves. this is purely a diversification
+    // deterrent, so the magnitude is a tuning knob
+    fn add_virtual_loss(&self, config: &MctsConfig) {
+        self.visits
+            .fetch_add(config.virtual_loss_visits as u64, Ordering::AcqRel);
     }
 
-    fn total_score_f32(&self) -> f32 {
-        self.total_score.load(Ordering::Acquire) as f32 / SCORE_SCALE
+    fn remove_virtual_loss(&self, config: &MctsConfig) {
+        self.visits
+            .fetch_sub(config.virtual_loss_visits as u64, Ordering::AcqRel);
     }
 
-    fn ucb1(&self, parent_visits: u32) -> f32 {
+    fn add_result(&self, score: f32) {
+        self.total_score.add(score);
+        self.visits.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn ucb1(&self, parent_visits: u64, config: &MctsConfig) -> f32 {
         let visits = self.visits.load(Ordering::Acquire);
         if visits == 0 {
             return f32::INFINITY;
         }
-        let average_score = self.total_score_f32() / visits as f32;
-        let exploration = 2.0 * (parent_visits as f32).ln().max(0.0) / visits as f32;
+        let average_score = self.total_score.load().average(visits);
+        let exploration =
+            config.exploration_constant * (parent_visits as f32).ln().max(0.0) / visits as f32;
         average_score + exploration.sqrt()
     }
+
+    // counts virtual losses as visits that lost, like `ucb1`
+    fn puct(&self, prior: f32, parent_visits: u64, config: &MctsConfig) -> f32 {
+        let visits = self.visits.load(Ordering::Acquire);
+        let average_score = if visits == 0 {
+            0.5
+        } else {
+            self.total_score.load().average(visits)
+        };
+        average_score
+            + config.puct_constant * prior * ((parent_visits + 1) as f32).sqrt()
+                / (visits + 1) as f32
+    }
 }
 
 pub struct SharedNodeOptions {
     s1: Vec<MoveNode>,
     s2: Vec<MoveNode>,
+
+    // only used by the `FACTORIZEDUCB1` selection policy, created the first time it selects here
+    slots: OnceLock<SharedSlotOptions>,
+
+    // the order progressive widening opens each side's options in, created the first time
+    // it selects here
+    widening_order: OnceLock<(Vec<usize>, Vec<usize>)>,
+
+    // each side's option priors, only used by the `PUCT` selection policy and created the
+    // first time it selects here
+    priors: OnceLock<(Vec<f32>, Vec<f32>)>,
 }
 
 impl SharedNodeOptions {
@@ -90,6 +276,9 @@ impl SharedNodeOptions {
         Self {
             s1: s1_options.into_iter().map(MoveNode::new).collect(),
             s2: s2_options.into_iter().map(MoveNode::new).collect(),
+            slots: OnceLock::new(),
+            widening_order: OnceLock::new(),
+            priors: OnceLock::new(),
         }
     }
 
@@ -107,49 +296,217 @@ impl SharedNodeOptions {
                 .drain(..)
                 .map(MoveNode::new)
                 .collect(),
+            slots: OnceLock::new(),
+            widening_order: OnceLock::new(),
+            priors: OnceLock::new(),
+        }
+    }
+
+    // the bytes the options take up, leaving out the slot options, widening order and priors
+    fn heap_usage(&self) -> usize {
+        size_of::<Self>() + (self.s1.capacity() + self.s2.capacity()) * size_of::<MoveNode>()
+    }
+
+    // the options progressive widening has opened for each side, most promising first.
+    // `None` when progressive widening is off and every option is open
+    fn open_options(
+        &self,
+        state: &State,
+        times_visited: u64,
+        config: &MctsConfig,
+    ) -> (Option<&[usize]>, Option<&[usize]>) {
+        if !config.progressive_widening {
+            return (None, None);
+        }
+        let (s1_order, s2_order) = self.widening_order.get_or_init(|| {
+            let s1_moves: Vec<(MoveChoice, MoveChoice)> =
+                self.s1.iter().map(|x| x.move_choice).collect();
+            let s2_moves: Vec<(MoveChoice, MoveChoice)> =
+                self.s2.iter().map(|x| x.move_choice).collect();
+            (
+                prior_order(state, SideReference::SideOne, &s1_moves, config),
+                prior_order(state, SideReference::SideTwo, &s2_moves, config),
+            )
+        });
+        (
+            Some(&s1_order[..open_option_count(times_visited, s1_order.len(), config)]),
+            Some(&s2_order[..open_option_count(times_visited, s2_order.len(), config)]),
+        )
+    }
+
+    fn priors(&self, state: &State, config: &MctsConfig) -> &(Vec<f32>, Vec<f32>) {
+        self.priors.get_or_init(|| {
+            let s1_moves: Vec<(MoveChoice, MoveChoice)> =
+                self.s1.iter().map(|x| x.move_choice).collect();
+            let s2_moves: Vec<(MoveChoice, MoveChoice)> =
+                self.s2.iter().map(|x| x.move_choice).collect();
+            (
+                option_priors(state, SideReference::SideOne, &s1_moves, config),
+                option_priors(state, SideReference::SideTwo, &s2_moves, config),
+            )
+        })
+    }
+
+    fn slots(&self) -> &SharedSlotOptions {
+        self.slots
+            .get_or_init(|| SharedSlotOptions::new(&self.s1, &self.s2))
+    }
+
+    // the slot nodes of both sides that the pair `(s1_index, s2_index)` is made of,
+    // paired with whether they are scored from side one's point of view
+    fn slot_nodes(&self, s1_index: usize, s2_index: usize) -> [(&MoveNode, bool); 4] {
+        let slots = self.slots();
+        let (s1_slot_a, s1_slot_b) = self.s1[s1_index].move_choice;
+        let (s2_slot_a, s2_slot_b) = self.s2[s2_index].move_choice;
+        [
+            (
+                SharedSlotOptions::slot_node(&slots.s1_slot_a, &s1_slot_a),
+                true,
+            ),
+            (
+                SharedSlotOptions::slot_node(&slots.s1_slot_b, &s1_slot_b),
+                true,
+            ),
+            (
+                SharedSlotOptions::slot_node(&slots.s2_slot_a, &s2_slot_a),
+                false,
+            ),
+            (
+                SharedSlotOptions::slot_node(&slots.s2_slot_b, &s2_slot_b),
+                false,
+            ),
+        ]
+    }
+
+    fn add_virtual_loss(&self, s1_index: usize, s2_index: usize, config: &MctsConfig) {
+        self.s1[s1_index].add_virtual_loss(config);
+        self.s2[s2_index].add_virtual_loss(config);
+        if config.selection_policy == SelectionPolicy::FACTORIZEDUCB1 {
+            for (slot_node, _) in self.slot_nodes(s1_index, s2_index) {
+                slot_node.add_virtual_loss(config);
+            }
         }
     }
+
+    fn remove_virtual_loss(&self, s1_index: usize, s2_index: usize, config: &MctsConfig) {
+        self.s1[s1_index].remove_virtual_loss(config);
+        self.s2[s2_index].remove_virtual_loss(config);
+        if config.selection_policy == SelectionPolicy::FACTORIZEDUCB1 {
+            for (slot_node, _) in self.slot_nodes(s1_index, s2_index) {
+                slot_node.remove_virtual_loss(config);
+            }
+        }
+    }
+
+    fn add_result(&self, s1_index: usize, s2_index: usize, score: f32, config: &MctsConfig) {
+        self.s1[s1_index].add_result(score);
+        self.s2[s2_index].add_result(1.0 - score);
+        if config.selection_policy == SelectionPolicy::FACTORIZEDUCB1 {
+            for (slot_node, side_one) in self.slot_nodes(s1_index, s2_index) {
+                slot_node.add_result(if side_one { score } else { 1.0 - score });
+            }
+        }
+    }
+}
+
+// the shared-tree counterpart of `mcts::SlotOptions`
+pub struct SharedSlotOptions {
+    s1_slot_a: Vec<MoveNode>,
+    s1_slot_b: Vec<MoveNode>,
+    s2_slot_a: Vec<MoveNode>,
+    s2_slot_b: Vec<MoveNode>,
 }
 
+impl SharedSlotOptions {
+    fn new(s1_options: &[MoveNode], s2_options: &[MoveNode]) -> Self {
+        let slot_nodes = |options: &[MoveNode], slot: SlotReference| -> Vec<MoveNode> {
+            slot_choices(options.iter().map(|x| x.move_choice), slot)
+                .into_iter()
+                .map(|choice| MoveNode::new((choice, MoveChoice::None)))
+                .collect()
+        };
+        Self {
+            s1_slot_a: slot_nodes(s1_options, SlotReference::SlotA),
+            s1_slot_b: slot_nodes(s1_options, SlotReference::SlotB),
+            s2_slot_a: slot_nodes(s2_options, SlotReference::SlotA),
+            s2_slot_b: slot_nodes(s2_options, SlotReference::SlotB),
+        }
+    }
+
+    fn slot_node<'a>(slot_nodes: &'a [MoveNode], choice: &MoveChoice) -> &'a MoveNode {
+        slot_nodes
+            .iter()
+            .find(|x| &x.move_choice.0 == choice)
+            .expect("every slot choice has a slot node")
+    }
+}
+
+// the outcome nodes of a move pair, which sit next to each other in the `NodeArena`
+#[derive(Clone, Copy)]
 pub struct SharedBranch {
-    nodes: Arc<[Node]>,
+    start: NodeIndex,
+    len: u32,
     total_weight: f32,
 }
 
 impl SharedBranch {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> *const Node {
-        if self.nodes.len() <= 1 || self.total_weight <= 0.0 {
-            return &self.nodes[0];
+    // the bytes a branch, its entry in the `ChildMap` and its `nodes` take up
+    fn memory_usage(nodes: &[Node]) -> usize {
+        size_of::<((NodeIndex, usize, usize), SharedBranch)>()
+            + nodes.iter().map(Node::memory_usage).sum::<usize>()
+    }
+
+    fn indices(&self) -> Range<NodeIndex> {
+        self.start..self.start + self.len
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, nodes: &NodeArena, rng: &mut R) -> NodeIndex {
+        if self.len <= 1 || self.total_weight <= 0.0 {
+            return self.start;
         }
         let mut threshold = rng.random_range(0.0..self.total_weight);
-        for node in self.nodes.iter() {
-            threshold -= node.instructions.percentage.max(0.0);
+        for index in self.indices() {
+            threshold -= nodes.get(index).instructions.percentage.max(0.0);
             if threshold <= 0.0 {
-                return node;
+                return index;
             }
         }
-        &self.nodes[self.nodes.len() - 1]
+        self.start + self.len - 1
     }
 }
 
 struct PathStep {
-    parent: *const Node,
-    child: *const Node,
+    parent: NodeIndex,
+    child: NodeIndex,
+
+    // the node holding `child`'s statistics, `child` itself unless it transposes to another node
+    statistics: NodeIndex,
     s1_index: usize,
     s2_index: usize,
+
+    // the probabilities `s1_index` and `s2_index` were selected with
+    s1_probability: f32,
+    s2_probability: f32,
 }
 
 pub struct Node {
     root: bool,
     instructions: StateInstructions,
+
+    // the node holding this node's statistics, options and children when another node reached
+    // the same state first, see `MctsConfig::transposition_table`
+    transposition: OnceLock<NodeIndex>,
     depth: u8,
-    times_visited: AtomicU32,
+    times_visited: AtomicU64,
+
+    // side one's total score over every visit to this node
+    total_score: AtomicScoreSum,
 
     // virtual_losses is the number of threads currently in flight through this node. added to
     // `times_visited` in select_move_pair to estimate the parent-visit count
     // for the ucb1 exploration term, which otherwise lags because
     // `times_visited` is only bumped at backprop. incremented by exactly 1 per
-    // in-flight thread (unlike VIRTUAL_LOSS_VISITS) because it is a placeholder
+    // in-flight thread (unlike `MctsConfig::virtual_loss_visits`) because it is a placeholder
     // for the real `times_visited += 1` that the thread will add when it
     // backpropagates
     // I8 effectively means you can't use more than 127 threads without risking overflow
@@ -166,15 +523,17 @@ impl Node {
     fn new_root(
         s1_options: Vec<(MoveChoice, MoveChoice)>,
         s2_options: Vec<(MoveChoice, MoveChoice)>,
-    ) -> Arc<Self> {
-        let node = Arc::new(Self {
+    ) -> Self {
+        let node = Self {
             root: true,
             instructions: StateInstructions::default(),
+            transposition: OnceLock::new(),
             depth: 0,
-            times_visited: AtomicU32::new(0),
+            times_visited: AtomicU64::new(0),
+            total_score: AtomicScoreSum::default(),
             virtual_losses: AtomicI8::new(0),
             options: OnceLock::new(),
-        });
+        };
         let _ = node
             .options
             .set(Box::new(SharedNodeOptions::new(s1_options, s2_options)));
@@ -185,124 +544,257 @@ impl Node {
         Self {
             root: false,
             instructions,
+            transposition: OnceLock::new(),
             depth,
-            times_visited: AtomicU32::new(0),
+            times_visited: AtomicU64::new(0),
+            total_score: AtomicScoreSum::default(),
             virtual_losses: AtomicI8::new(0),
             options: OnceLock::new(),
         }
     }
 
-    fn as_key(&self) -> usize {
-        self as *const Node as usize
+    fn add_visit(&self, score: f32) {
+        self.total_score.add(score);
+        self.times_visited.fetch_add(1, Ordering::AcqRel);
+    }
+
+    // side one's average score over every visit to this node
+    fn average_score(&self) -> f32 {
+        self.total_score
+            .load()
+            .average(self.times_visited.load(Ordering::Acquire))
+    }
+
+    // the bytes the node, its instructions and its options take up
+    fn memory_usage(&self) -> usize {
+        size_of::<Node>()
+            + self.instructions.instruction_list.capacity() * size_of::<Instruction>()
+            + self.options.get().map_or(0, |options| options.heap_usage())
     }
 
-    fn ensure_options(&self, state: &State, move_options: &mut MoveOptions) -> &SharedNodeOptions {
+    fn ensure_options(
+        &self,
+        state: &State,
+        move_options: &mut MoveOptions,
+        memory_usage: &AtomicUsize,
+    ) -> &SharedNodeOptions {
         self.options.get_or_init(|| {
             state.get_all_options(move_options);
-            Box::new(SharedNodeOptions::from_move_options(move_options))
+            let options = Box::new(SharedNodeOptions::from_move_options(move_options));
+            memory_usage.fetch_add(options.heap_usage(), Ordering::AcqRel);
+            options
         })
     }
 
-    fn select_move_pair(&self, state: &State, move_options: &mut MoveOptions) -> (usize, usize) {
-        let options = self.ensure_options(state, move_options);
+    // returns the selected option index for each side and the probability it was selected with
+    fn select_move_pair<R: Rng + ?Sized>(
+        &self,
+        state: &State,
+        move_options: &mut MoveOptions,
+        memory_usage: &AtomicUsize,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> ((usize, f32), (usize, f32)) {
+        let options = self.ensure_options(state, move_options, memory_usage);
+        if matches!(
+            config.selection_policy,
+            SelectionPolicy::EXP3 | SelectionPolicy::REGRETMATCHING
+        ) {
+            return (
+                sample_from_policy(options.s1.iter().map(|x| x.policy_weight()), config, rng),
+                sample_from_policy(options.s2.iter().map(|x| x.policy_weight()), config, rng),
+            );
+        }
         let parent_visits = self
             .times_visited
             .load(Ordering::Acquire)
-            .saturating_add(self.virtual_losses.load(Ordering::Acquire).max(0) as u32)
+            .saturating_add(self.virtual_losses.load(Ordering::Acquire).max(0) as u64)
             .max(1);
+        let (s1_open, s2_open) =
+            options.open_options(state, self.times_visited.load(Ordering::Acquire), config);
+        if config.selection_policy == SelectionPolicy::FACTORIZEDUCB1 {
+            let slots = options.slots();
+            let slot_ucb1 = |slot_nodes: &[MoveNode], choice: &MoveChoice| {
+                SharedSlotOptions::slot_node(slot_nodes, choice).ucb1(parent_visits, config)
+            };
+            let s1_position = select_factorized(
+                s1_open.map_or(options.s1.len(), |open| open.len()),
+                |position| options.s1[open_option_index(s1_open, position)].move_choice,
+                |choice| slot_ucb1(&slots.s1_slot_a, choice),
+                |choice| slot_ucb1(&slots.s1_slot_b, choice),
+            );
+            let s2_position = select_factorized(
+                s2_open.map_or(options.s2.len(), |open| open.len()),
+                |position| options.s2[open_option_index(s2_open, position)].move_choice,
+                |choice| slot_ucb1(&slots.s2_slot_a, choice),
+                |choice| slot_ucb1(&slots.s2_slot_b, choice),
+            );
+            return (
+                (open_option_index(s1_open, s1_position), 1.0),
+                (open_option_index(s2_open, s2_position), 1.0),
+            );
+        }
+        if config.selection_policy == SelectionPolicy::PUCT {
+            let (s1_priors, s2_priors) = options.priors(state, config);
+            return (
+                (
+                    self.maximize_puct_for_side(
+                        &options.s1,
+                        s1_priors,
+                        s1_open,
+                        parent_visits,
+                        config,
+                    ),
+                    1.0,
+                ),
+                (
+                    self.maximize_puct_for_side(
+                        &options.s2,
+                        s2_priors,
+                        s2_open,
+                        parent_visits,
+                        config,
+                    ),
+                    1.0,
+                ),
+            );
+        }
         (
-            self.maximize_ucb_for_side(&options.s1, parent_visits),
-            self.maximize_ucb_for_side(&options.s2, parent_visits),
+            (
+                self.maximize_ucb_for_side(&options.s1, s1_open, parent_visits, config),
+                1.0,
+            ),
+            (
+                self.maximize_ucb_for_side(&options.s2, s2_open, parent_visits, config),
+                1.0,
+            ),
         )
     }
 
     fn selection<R: Rng + ?Sized>(
-        root: &Arc<Node>,
         state: &mut State,
         rng: &mut R,
-        children: &ChildMap,
         path: &mut Vec<PathStep>,
         move_options: &mut MoveOptions,
-    ) -> (*const Node, usize, usize) {
-        // raw pointers walk both the root (a standalone Arc<Node>) and children
-        // (Nodes living inside a branch's Arc<[Node]>) uniformly. every node is
-        // owned by children/root for the whole search, so the pointers stay
-        // valid
-        let mut current: *const Node = Arc::as_ptr(root);
+        context: &SearchContext,
+    ) -> (NodeIndex, (usize, f32), (usize, f32)) {
+        let config = context.config;
+        let mut current = ROOT;
         loop {
-            let node = unsafe { &*current };
-            let (s1_index, s2_index) = node.select_move_pair(state, move_options);
+            let node = context.nodes.get(current);
+
+            // a full tree is not expanded any further, so a new leaf has no use for options
+            if node.options.get().is_none() && context.is_full() {
+                return (current, (0, 1.0), (0, 1.0));
+            }
+            let ((s1_index, s1_probability), (s2_index, s2_probability)) =
+                node.select_move_pair(state, move_options, context.memory_usage, config, rng);
             let options = node.options.get().expect("options set during selection");
 
-            let key = (node.as_key(), s1_index, s2_index);
-            match children.get(&key) {
+            let key = (current, s1_index, s2_index);
+            // copied out of the DashMap so its lock is not held while the state changes
+            let branch = context.children.get(&key).map(|branch| *branch);
+            match branch {
                 Some(branch) => {
-                    let child = branch.sample(rng);
-
-                    // drop the DashMap ref before mutating state to avoid
-                    // holding the lock longer than necessary. the sampled node
-                    // stays alive via the branch's Arc<[Node]> in the ChildMap
-                    drop(branch);
-
-                    let child_ref = unsafe { &*child };
-                    options.s1[s1_index].add_virtual_loss();
-                    options.s2[s2_index].add_virtual_loss();
-                    child_ref.virtual_losses.fetch_add(1, Ordering::AcqRel);
-                    state.apply_instructions(&child_ref.instructions.instruction_list);
+                    let child = branch.sample(context.nodes, rng);
+                    options.add_virtual_loss(s1_index, s2_index, config);
+                    state.apply_instructions(
+                        &context.nodes.get(child).instructions.instruction_list,
+                    );
+                    let statistics = context.statistics_of(child, state, path);
+                    context
+                        .nodes
+                        .get(statistics)
+                        .virtual_losses
+                        .fetch_add(1, Ordering::AcqRel);
                     path.push(PathStep {
                         parent: current,
                         child,
+                        statistics,
                         s1_index,
                         s2_index,
+                        s1_probability,
+                        s2_probability,
                     });
-                    current = child;
+                    current = statistics;
                 }
                 None => {
                     // this is the leaf, stop selection
-                    return (current, s1_index, s2_index);
+                    return (
+                        current,
+                        (s1_index, s1_probability),
+                        (s2_index, s2_probability),
+                    );
                 }
             }
         }
     }
 
-    fn maximize_ucb_for_side(&self, side_options: &[MoveNode], parent_visits: u32) -> usize {
-        side_options
-            .iter()
-            .enumerate()
-            .max_by(|(_, a), (_, b)| {
-                a.ucb1(parent_visits)
-                    .partial_cmp(&b.ucb1(parent_visits))
+    // `open` is the options progressive widening has opened, or `None` if every option is open
+    fn maximize_ucb_for_side(
+        &self,
+        side_options: &[MoveNode],
+        open: Option<&[usize]>,
+        parent_visits: u64,
+        config: &MctsConfig,
+    ) -> usize {
+        (0..open.map_or(side_options.len(), |open| open.len()))
+            .map(|position| open_option_index(open, position))
+            .max_by(|a, b| {
+                side_options[*a]
+                    .ucb1(parent_visits, config)
+                    .partial_cmp(&side_options[*b].ucb1(parent_visits, config))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0)
+    }
+
+    // `open` is the options progressive widening has opened, or `None` if every option is open
+    fn maximize_puct_for_side(
+        &self,
+        side_options: &[MoveNode],
+        priors: &[f32],
+        open: Option<&[usize]>,
+        parent_visits: u64,
+        config: &MctsConfig,
+    ) -> usize {
+        (0..open.map_or(side_options.len(), |open| open.len()))
+            .map(|position| open_option_index(open, position))
+            .max_by(|a, b| {
+                side_options[*a]
+                    .puct(priors[*a], parent_visits, config)
+                    .partial_cmp(&side_options[*b].puct(priors[*b], parent_visits, config))
                     .unwrap_or(std::cmp::Ordering::Equal)
             })
-            .map(|(i, _)| i)
             .unwrap_or(0)
     }
 
     /// looks up or creates the child branch for `(s1_index, s2_index)` and
     /// returns one sampled child, applying virtual loss bookkeeping.  Returns
-    /// `None` when the node should not be expanded (depth cap reached, battle
-    /// over, or both sides have no valid move).
+    /// `None` when the node should not be expanded (depth cap or memory cap
+    /// reached, battle over, or both sides have no valid move).
     fn expand<R: Rng + ?Sized>(
-        &self,
+        index: NodeIndex,
         state: &mut State,
         s1_index: usize,
         s2_index: usize,
         parent_is_root: bool,
         rng: &mut R,
-        children: &ChildMap,
-    ) -> Option<*const Node> {
-        if self.depth >= MCTS_MAX_DEPTH {
+        context: &SearchContext,
+    ) -> Option<NodeIndex> {
+        let node = context.nodes.get(index);
+        if node.depth >= context.config.max_depth || context.is_full() {
             return None;
         }
 
-        let options = self
+        let options = node
             .options
             .get()
             .expect("options initialised before expand");
         let s1_move = &options.s1[s1_index].move_choice;
         let s2_move = &options.s2[s2_index].move_choice;
 
-        if (state.battle_is_over() != 0.0 && !self.root)
+        if (state.battle_is_over() != 0.0 && !node.root)
             || (s1_move == &(MoveChoice::None, MoveChoice::None)
                 && s2_move == &(MoveChoice::None, MoveChoice::None))
         {
@@ -312,7 +804,7 @@ impl Node {
         // faithful port of the single-threaded should_branch_on_damage:
         // the root always branches, and a node one level below the root
         // branches when either side has few combined options.
-        let should_branch_on_damage = if self.root {
+        let should_branch_on_damage = if node.root {
             true
         } else {
             (parent_is_root && options.s1.len() < 20) || options.s2.len() < 20
@@ -332,102 +824,198 @@ impl Node {
             .into_iter()
             .map(|instr| {
                 total_weight += instr.percentage.max(0.0);
-                // depth only increments when the end of the turn is reached,
-                // matching the single-threaded engine
-                let child_depth = if instr.end_of_turn_triggered {
-                    self.depth.saturating_add(1)
-                } else {
-                    self.depth
-                };
-                Node::new_child(instr, child_depth)
+                let depth = child_depth(node.depth, &instr);
+                Node::new_child(instr, depth)
             })
-            .collect::<Arc<[Node]>>();
-        let branch = SharedBranch {
-            nodes,
-            total_weight,
-        };
+            .collect::<Vec<Node>>();
 
-        let key = (self.as_key(), s1_index, s2_index);
+        let key = (index, s1_index, s2_index);
         // entry() on DashMap is atomic per-shard: only one thread will
-        // construct the branch; all others get the winner's branch
-        let branch_ref = children.entry(key).or_insert(branch);
+        // construct the branch; all others get the winner's branch. only the
+        // winner adds its nodes to the arena, so losing leaves nothing behind
+        let branch = match context.children.entry(key) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let memory_usage = SharedBranch::memory_usage(&nodes);
+                let len = nodes.len() as u32;
+                let start = context.nodes.push_all(nodes)?;
+                context
+                    .memory_usage
+                    .fetch_add(memory_usage, Ordering::AcqRel);
+                *entry.insert(SharedBranch {
+                    start,
+                    len,
+                    total_weight,
+                })
+            }
+        };
 
-        Some(branch_ref.sample(rng))
+        Some(branch.sample(context.nodes, rng))
     }
 
-    fn rollout(&self, state: &State, root_eval: f32) -> f32 {
-        let battle_is_over = state.battle_is_over();
-        if battle_is_over == 0.0 {
-            sigmoid(evaluate(state) - root_eval)
-        } else if battle_is_over == -1.0 {
-            0.0
-        } else {
-            battle_is_over
-        }
+    fn rollout<R: Rng + ?Sized>(
+        &self,
+        state: &mut State,
+        root_eval: f32,
+        move_options: &mut MoveOptions,
+        config: &MctsConfig,
+        rng: &mut R,
+    ) -> f32 {
+        rollout_score(state, root_eval, move_options, config, rng)
     }
 
     // walk `path` in reverse, updating visit counts and scores,
     // removes virtual losses, and reverse-applying instructions to restore `state` to how it
-    // was in the root
-    fn backpropagate(path: &[PathStep], leaf: &Node, score: f32, state: &mut State) {
-        leaf.times_visited.fetch_add(1, Ordering::AcqRel);
+    // was in the root. `leaf` is the node holding the statistics of the last node on the path
+    fn backpropagate(
+        path: &[PathStep],
+        leaf: NodeIndex,
+        score: f32,
+        state: &mut State,
+        context: &SearchContext,
+    ) {
+        let config = context.config;
+        context.nodes.get(leaf).add_visit(score);
 
         for step in path.iter().rev() {
-            let (parent, child) = unsafe { (&*step.parent, &*step.child) };
+            let parent = context.nodes.get(step.parent);
             let options = parent.options.get().expect("path parent has options");
-            options.s1[step.s1_index].add_result(score);
-            options.s1[step.s1_index].remove_virtual_loss();
-            options.s2[step.s2_index].add_result(1.0 - score);
-            options.s2[step.s2_index].remove_virtual_loss();
-            parent.times_visited.fetch_add(1, Ordering::AcqRel);
-            child.virtual_losses.fetch_sub(1, Ordering::AcqRel);
-            state.reverse_instructions(&child.instructions.instruction_list);
+            options.add_result(step.s1_index, step.s2_index, score, config);
+            options.remove_virtual_loss(step.s1_index, step.s2_index, config);
+            if matches!(
+                config.selection_policy,
+                SelectionPolicy::EXP3 | SelectionPolicy::REGRETMATCHING
+            ) {
+                for (index, x) in options.s1.iter().enumerate() {
+                    x.add_policy_weight(policy_weight_change(
+                        index == step.s1_index,
+                        score,
+                        step.s1_probability,
+                        config,
+                    ));
+                }
+                for (index, x) in options.s2.iter().enumerate() {
+                    x.add_policy_weight(policy_weight_change(
+                        index == step.s2_index,
+                        1.0 - score,
+                        step.s2_probability,
+                        config,
+                    ));
+                }
+            }
+            parent.add_visit(score);
+            context
+                .nodes
+                .get(step.statistics)
+                .virtual_losses
+                .fetch_sub(1, Ordering::AcqRel);
+            state
+                .reverse_instructions(&context.nodes.get(step.child).instructions.instruction_list);
         }
     }
 }
 
+// the parts of a `SharedMctsTree` that every worker of a search shares
+struct SearchContext<'a> {
+    nodes: &'a NodeArena,
+    children: &'a ChildMap,
+    transpositions: &'a SharedTranspositionTable,
+    memory_usage: &'a AtomicUsize,
+    config: &'a MctsConfig,
+}
+
+impl SearchContext<'_> {
+    // whether the tree has reached `MctsConfig::max_memory` and should not grow any further
+    fn is_full(&self) -> bool {
+        self.config
+            .max_memory
+            .is_some_and(|max_memory| self.memory_usage.load(Ordering::Acquire) >= max_memory)
+    }
+
+    // the node holding the statistics of `index`, whose instructions have just been applied to
+    // `state`. without a transposition table every node holds its own. a node that transposes
+    // to one already on `path` keeps its own too, so selection never loops
+    fn statistics_of(&self, index: NodeIndex, state: &State, path: &[PathStep]) -> NodeIndex {
+        if !self.config.transposition_table {
+            return index;
+        }
+        let node = self.nodes.get(index);
+        let statistics = *node.transposition.get_or_init(|| {
+            *self
+                .transpositions
+                .entry((state.hash, node.depth))
+                .or_insert(index)
+        });
+        if statistics == ROOT || path.iter().any(|step| step.statistics == statistics) {
+            return index;
+        }
+        statistics
+    }
+}
+
 fn do_mcts<R: Rng + ?Sized>(
-    root: &Arc<Node>,
     state: &mut State,
     root_eval: f32,
     rng: &mut R,
-    children: &ChildMap,
     path: &mut Vec<PathStep>,
     move_options: &mut MoveOptions,
-) {
+    context: &SearchContext,
+) -> u8 {
     path.clear();
+    let config = context.config;
 
-    let (leaf, s1_index, s2_index) =
-        Node::selection(root, state, rng, children, path, move_options);
-    let leaf = unsafe { &*leaf };
+    let (leaf_index, (s1_index, s1_probability), (s2_index, s2_probability)) =
+        Node::selection(state, rng, path, move_options, context);
+    let leaf = context.nodes.get(leaf_index);
 
     // is the leaf's parent the root? required by the doubles
     // should_branch_on_damage heuristic. an empty path means the leaf
     // itself is the root (in which case parent_is_root is unused).
     let parent_is_root = path
         .last()
-        .map(|step| unsafe { (*step.parent).root })
+        .map(|step| context.nodes.get(step.parent).root)
         .unwrap_or(false);
 
-    let options = leaf.options.get().expect("options set during selection");
-    options.s1[s1_index].add_virtual_loss();
-    options.s2[s2_index].add_virtual_loss();
-    let expanded = leaf.expand(state, s1_index, s2_index, parent_is_root, rng, children);
+    let options = match leaf.options.get() {
+        Some(options) => options,
+
+        // the tree is full and the leaf was never selected through
+        None => {
+            let score = leaf.rollout(state, root_eval, move_options, config, rng);
+            Node::backpropagate(path, leaf_index, score, state, context);
+            return leaf.depth;
+        }
+    };
+    options.add_virtual_loss(s1_index, s2_index, config);
+    let expanded = Node::expand(
+        leaf_index,
+        state,
+        s1_index,
+        s2_index,
+        parent_is_root,
+        rng,
+        context,
+    );
     match expanded {
-        Some(child) => {
-            let child = unsafe { &*child };
+        Some(child_index) => {
+            state.apply_instructions(&context.nodes.get(child_index).instructions.instruction_list);
+            let statistics = context.statistics_of(child_index, state, path);
+            let child = context.nodes.get(statistics);
             child.virtual_losses.fetch_add(1, Ordering::AcqRel);
-            state.apply_instructions(&child.instructions.instruction_list);
             path.push(PathStep {
-                parent: leaf,
-                child,
+                parent: leaf_index,
+                child: child_index,
+                statistics,
                 s1_index,
                 s2_index,
+                s1_probability,
+                s2_probability,
             });
 
-            let score = child.rollout(state, root_eval);
+            let score = child.rollout(state, root_eval, move_options, config, rng);
 
-            Node::backpropagate(path, child, score, state);
+            Node::backpropagate(path, statistics, score, state, context);
+            child.depth
         }
 
         // if expansion returns None,
@@ -436,91 +1024,595 @@ fn do_mcts<R: Rng + ?Sized>(
         // we do a rollout on the leaf and backpropagate without adding a child
         None => {
             // remove the virtual loss we added before expansion, since we're not actually expanding
-            options.s1[s1_index].remove_virtual_loss();
-            options.s2[s2_index].remove_virtual_loss();
+            options.remove_virtual_loss(s1_index, s2_index, config);
 
-            let score = leaf.rollout(state, root_eval);
+            let score = leaf.rollout(state, root_eval, move_options, config, rng);
 
-            Node::backpropagate(path, leaf, score, state);
+            Node::backpropagate(path, leaf_index, score, state, context);
+            leaf.depth
         }
     }
 }
 
-pub fn perform_mcts_shared_tree(
-    state: &mut State,
-    side_one_options: Vec<(MoveChoice, MoveChoice)>,
-    side_two_options: Vec<(MoveChoice, MoveChoice)>,
+// the early-stop check for the shared tree. `started_iterations` is the shared counter of
+// this search call, which may run slightly ahead of the visits that have been backpropagated
+fn root_is_decided(
+    root: &Node,
+    started_iterations: u64,
+    start_time: Instant,
     max_time: Duration,
-    worker_count: usize,
-) -> MctsResult {
-    let root_eval = evaluate(state);
-    let deadline = Instant::now() + max_time;
-    let root = Node::new_root(side_one_options, side_two_options);
-    let started_iterations = Arc::new(AtomicU32::new(0));
-
-    // global map shared by all threads.
-    let children: Arc<ChildMap> = Arc::new(DashMap::with_capacity(1 << 16));
-
-    thread::scope(|scope| {
-        for _ in 0..worker_count {
-            let root = root.clone();
-            let started_iterations = started_iterations.clone();
-            let children = children.clone();
-            let mut worker_state = state.clone();
-            scope.spawn(move || {
-                let mut rng = rng();
-                let mut iterations_until_deadline_check = 0u32;
-                let mut path = Vec::with_capacity(16);
-                let mut move_options = MoveOptions::new();
-
-                loop {
-                    if iterations_until_deadline_check == 0 {
-                        if Instant::now() >= deadline {
+    config: &MctsConfig,
+) -> bool {
+    let options = root.options.get().expect("root options initialized");
+    let remaining = remaining_iterations(
+        started_iterations,
+        start_time.elapsed(),
+        max_time,
+        iterations_left(started_iterations, config),
+    );
+    most_visited_is_decided(
+        options.s1.iter().map(|x| x.visits.load(Ordering::Acquire)),
+        remaining,
+    ) && most_visited_is_decided(
+        options.s2.iter().map(|x| x.visits.load(Ordering::Acquire)),
+        remaining,
+    )
+}
+
+fn options_match(options: &[MoveNode], expected: &[(MoveChoice, MoveChoice)]) -> bool {
+    let expected: HashSet<&(MoveChoice, MoveChoice)> = expected.iter().collect();
+    options.len() == expected.len() && options.iter().all(|x| expected.contains(&x.move_choice))
+}
+
+// the statistics of the root's options, without a solved strategy or principal variation
+fn root_result(root: &Node, leaf_depths: LeafDepths) -> MctsResult {
+    let options = root.options.get().expect("root options initialized");
+    let side_result = |v: &MoveNode| MctsSideResult {
+        move_choice: v.move_choice,
+        total_score: v.total_score.load().total() as f32,
+        visits: v.visits.load(Ordering::Acquire),
+        nash_probability: None,
+    };
+    MctsResult {
+        s1: options.s1.iter().map(side_result).collect(),
+        s2: options.s2.iter().map(side_result).collect(),
+        iteration_count: root.times_visited.load(Ordering::Acquire),
+        principal_variation: Vec::new(),
+        max_leaf_depth: leaf_depths.max,
+        average_leaf_depth: leaf_depths.average(),
+    }
+}
+
+/// Called with the root's statistics while a search runs in the background, see
+/// `SharedMctsTree::search_in_background`
+pub type ProgressCallback = Box<dyn Fn(&MctsResult) + Send + Sync>;
+
+/// The shared-tree counterpart of `MctsTree`: a search tree that is kept alive between
+/// searches and can be re-rooted with `advance` once the outcome of a turn is known
+pub struct SharedMctsTree {
+    nodes: NodeArena,
+    children: ChildMap,
+    transpositions: SharedTranspositionTable,
+
+    // roughly the bytes the tree's nodes take up, see `MctsConfig::max_memory`
+    memory_usage: AtomicUsize,
+
+    // the depths of the leaves reached by the current or last search
+    leaf_depths: AtomicLeafDepths,
+}
+
+impl SharedMctsTree {
+    pub fn new(
+        side_one_options: Vec<(MoveChoice, MoveChoice)>,
+        side_two_options: Vec<(MoveChoice, MoveChoice)>,
+    ) -> SharedMctsTree {
+        let root = Node::new_root(side_one_options, side_two_options);
+        let memory_usage = AtomicUsize::new(root.memory_usage());
+        let nodes = NodeArena::new();
+        let root_index = nodes.push_all(vec![root]);
+        debug_assert_eq!(root_index, Some(ROOT));
+        SharedMctsTree {
+            nodes,
+            children: DashMap::with_capacity(1 << 16),
+            transpositions: DashMap::new(),
+            memory_usage,
+            leaf_depths: AtomicLeafDepths::default(),
+        }
+    }
+
+    /// The number of nodes currently allocated in the tree, including the root
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Roughly how many bytes the tree's nodes take up, see `MctsConfig::max_memory`
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage.load(Ordering::Acquire)
+    }
+
+    /// The number of states in the transposition table, zero unless
+    /// `MctsConfig::transposition_table` is set
+    pub fn transposition_count(&self) -> usize {
+        self.transpositions.len()
+    }
+
+    pub fn search(
+        &mut self,
+        state: &mut State,
+        max_time: Duration,
+        worker_count: usize,
+        config: &MctsConfig,
+    ) -> MctsResult {
+        self.search_until_stopped(
+            state,
+            max_time,
+            worker_count,
+            config,
+            &AtomicBool::new(false),
+            None,
+        )
+    }
+
+    /// Starts `search` on background threads and returns straight away.
+    ///
+    /// `progress`, if given, is called with the root's statistics every `progress.0`
+    /// iterations, from whichever worker starts that iteration
+    pub fn search_in_background(
+        self,
+        mut state: State,
+        max_time: Duration,
+        worker_count: usize,
+        config: &MctsConfig,
+        progress: Option<(u32, ProgressCallback)>,
+    ) -> BackgroundSearch {
+        let tree = Arc::new(self);
+        let stop = Arc::new(AtomicBool::new(false));
+        let config = config.clone();
+        let handle = {
+            let tree = Arc::clone(&tree);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                tree.search_until_stopped(
+                    &mut state,
+                    max_time,
+                    worker_count,
+                    &config,
+                    &stop,
+                    progress.as_ref(),
+                )
+            })
+        };
+        BackgroundSearch { tree, stop, handle }
+    }
+
+    // `search`, also stopping as soon as `stop` is set
+    fn search_until_stopped(
+        &self,
+        state: &mut State,
+        max_time: Duration,
+        worker_count: usize,
+        config: &MctsConfig,
+        stop: &AtomicBool,
+        progress: Option<&(u32, ProgressCallback)>,
+    ) -> MctsResult {
+        let root_eval = evaluate(state);
+        let track_hash = state.track_hash;
+        if config.transposition_table {
+            state.track_hash = true;
+            state.rehash();
+        }
+        let start_time = Instant::now();
+        // `None` when `max_time` is too long to be represented, i.e. no time limit
+        let deadline = start_time.checked_add(max_time);
+        let root = self.nodes.get(ROOT);
+        let children = &self.children;
+        let context = &SearchContext {
+            nodes: &self.nodes,
+            children,
+            transpositions: &self.transpositions,
+            memory_usage: &self.memory_usage,
+            config,
+        };
+        let leaf_depths = &self.leaf_depths;
+        leaf_depths.reset();
+
+        // counts this call's iterations only, so `max_iterations` applies to every search
+        // even when the root kept visits from earlier searches
+        let started_iterations = AtomicU64::new(0);
+
+        // every worker gets its own generator, seeded from the master generator
+        let mut master_rng = search_rng(config.seed);
+
+        thread::scope(|scope| {
+            for _ in 0..worker_count {
+                let started_iterations = &started_iterations;
+                let mut worker_state = state.clone();
+                let mut rng = StdRng::seed_from_u64(master_rng.random());
+                scope.spawn(move || {
+                    let mut iterations_until_deadline_check = 0u32;
+                    let mut path = Vec::with_capacity(16);
+                    let mut move_options = MoveOptions::new();
+
+                    loop {
+                        // checked every iteration so a search can be stopped straight away
+                        if stop.load(Ordering::Acquire) {
                             break;
                         }
-                        iterations_until_deadline_check = MCTS_DEADLINE_CHECK_INTERVAL;
-                    }
-                    if started_iterations.fetch_add(1, Ordering::AcqRel)
-                        >= MCTS_MAX_ITERATIONS_PER_TREE
-                    {
-                        break;
+                        if iterations_until_deadline_check == 0 {
+                            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                                break;
+                            }
+                            if config.early_stop
+                                && root_is_decided(
+                                    root,
+                                    started_iterations.load(Ordering::Acquire),
+                                    start_time,
+                                    max_time,
+                                    config,
+                                )
+                            {
+                                stop.store(true, Ordering::Release);
+                                break;
+                            }
+                            iterations_until_deadline_check = MCTS_DEADLINE_CHECK_INTERVAL;
+                        }
+                        if config
+                            .max_nodes
+                            .is_some_and(|max_nodes| context.nodes.len() >= max_nodes)
+                        {
+                            break;
+                        }
+                        let iteration = started_iterations.fetch_add(1, Ordering::AcqRel);
+                        if iterations_left(iteration, config) == 0 {
+                            break;
+                        }
+                        if let Some((interval, callback)) = progress {
+                            if *interval > 0
+                                && iteration > 0
+                                && iteration.is_multiple_of(*interval as u64)
+                            {
+                                callback(&root_result(root, leaf_depths.load()));
+                            }
+                        }
+
+                        let depth = do_mcts(
+                            &mut worker_state,
+                            root_eval,
+                            &mut rng,
+                            &mut path,
+                            &mut move_options,
+                            context,
+                        );
+                        leaf_depths.add(depth);
+                        iterations_until_deadline_check -= 1;
                     }
+                });
+            }
+        });
 
-                    do_mcts(
-                        &root,
-                        &mut worker_state,
-                        root_eval,
-                        &mut rng,
-                        &children,
-                        &mut path,
-                        &mut move_options,
-                    );
-                    iterations_until_deadline_check -= 1;
-                }
+        state.track_hash = track_hash;
+
+        let mut result = root_result(root, leaf_depths.load());
+
+        if config.nash_iterations > 0 {
+            solve_root_nash(
+                &mut result,
+                |i, j| {
+                    children
+                        .get(&(ROOT, i, j))
+                        .map_or((ScoreSum::default(), 0), |branch| {
+                            branch
+                                .indices()
+                                .map(|index| self.nodes.get(self.nodes.transposed(index)))
+                                .fold((ScoreSum::default(), 0), |(score, visits), node| {
+                                    (
+                                        ScoreSum(score.0 + node.total_score.load().0),
+                                        visits + node.times_visited.load(Ordering::Acquire),
+                                    )
+                                })
+                        })
+                },
+                config.nash_iterations,
+            );
+        }
+        result.principal_variation = self.principal_variation(config.principal_variation_plies);
+        result
+    }
+
+    fn principal_variation(&self, plies: u8) -> Vec<PrincipalVariationPly> {
+        let mut line = Vec::new();
+        let mut index = ROOT;
+        while line.len() < plies as usize {
+            let node = self.nodes.get(index);
+            let options = match node.options.get() {
+                Some(options) if !options.s1.is_empty() && !options.s2.is_empty() => options,
+                _ => break,
+            };
+            let s1_index =
+                index_of_max(options.s1.iter().map(|x| x.visits.load(Ordering::Acquire)));
+            let s2_index =
+                index_of_max(options.s2.iter().map(|x| x.visits.load(Ordering::Acquire)));
+            let next_branch = match self.children.get(&(index, s1_index, s2_index)) {
+                Some(next_branch) => *next_branch,
+                None => break,
+            };
+            let outcome = next_branch.start
+                + index_of_max(
+                    next_branch
+                        .indices()
+                        .map(|x| self.nodes.get(x).instructions.percentage),
+                ) as NodeIndex;
+            let next_index = self.nodes.transposed(outcome);
+            let next = self.nodes.get(next_index);
+            line.push(PrincipalVariationPly {
+                s1_move: options.s1[s1_index].move_choice,
+                s2_move: options.s2[s2_index].move_choice,
+                instructions: self.nodes.get(outcome).instructions.clone(),
+                visits: next.times_visited.load(Ordering::Acquire),
+                average_score: next.average_score(),
             });
+            index = next_index;
         }
-    });
+        line
+    }
 
-    let options = root.options.get().expect("root options initialized");
-    MctsResult {
-        s1: options
+    /// Copies the top `plies` plies of the tree, leaving out nodes visited fewer than
+    /// `min_visits` times
+    pub fn export(&self, plies: u8, min_visits: u64) -> ExportedNode {
+        self.export_node(ROOT, None, plies, min_visits)
+    }
+
+    fn export_node(
+        &self,
+        index: NodeIndex,
+        move_pair: Option<((MoveChoice, MoveChoice), (MoveChoice, MoveChoice))>,
+        plies: u8,
+        min_visits: u64,
+    ) -> ExportedNode {
+        let statistics = self.nodes.transposed(index);
+        let node = self.nodes.get(statistics);
+        let mut children = Vec::new();
+        if let (Some(options), true) = (node.options.get(), plies > 0) {
+            for (s1_index, s1_option) in options.s1.iter().enumerate() {
+                for (s2_index, s2_option) in options.s2.iter().enumerate() {
+                    let branch = match self.children.get(&(statistics, s1_index, s2_index)) {
+                        Some(branch) => *branch,
+                        None => continue,
+                    };
+                    for child in branch.indices() {
+                        if self
+                            .nodes
+                            .get(self.nodes.transposed(child))
+                            .times_visited
+                            .load(Ordering::Acquire)
+                            >= min_visits.max(1)
+                        {
+                            children.push(self.export_node(
+                                child,
+                                Some((s1_option.move_choice, s2_option.move_choice)),
+                                plies - 1,
+                                min_visits,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        ExportedNode {
+            move_pair,
+            instructions: self.nodes.get(index).instructions.clone(),
+            visits: node.times_visited.load(Ordering::Acquire),
+            average_score: node.average_score(),
+            children,
+        }
+    }
+
+    /// Re-roots the tree at the outcome of `side_one_move` and `side_two_move` that produced
+    /// `instructions`. `state` is the state after `instructions` have been applied.
+    ///
+    /// Returns `true` if that outcome had already been explored and its subtree was kept.
+    /// Otherwise the tree is replaced by a fresh one rooted at `state`
+    pub fn advance(
+        &mut self,
+        state: &State,
+        side_one_move: &(MoveChoice, MoveChoice),
+        side_two_move: &(MoveChoice, MoveChoice),
+        instructions: &StateInstructions,
+    ) -> bool {
+        let (side_one_options, side_two_options) = state.root_get_all_options();
+        let root_options = self
+            .nodes
+            .get(ROOT)
+            .options
+            .get()
+            .expect("root options initialized");
+        let s1_index = root_options
             .s1
             .iter()
-            .map(|v| MctsSideResult {
-                move_choice: v.move_choice,
-                total_score: v.total_score_f32(),
-                visits: v.visits.load(Ordering::Acquire),
-            })
-            .collect(),
-        s2: options
+            .position(|x| &x.move_choice == side_one_move);
+        let s2_index = root_options
             .s2
             .iter()
-            .map(|v| MctsSideResult {
-                move_choice: v.move_choice,
-                total_score: v.total_score_f32(),
-                visits: v.visits.load(Ordering::Acquire),
+            .position(|x| &x.move_choice == side_two_move);
+
+        let outcome = s1_index
+            .zip(s2_index)
+            .and_then(|(s1_index, s2_index)| {
+                self.children
+                    .get(&(ROOT, s1_index, s2_index))
+                    .map(|branch| *branch)
             })
-            .collect(),
-        iteration_count: root.times_visited.load(Ordering::Acquire),
+            .and_then(|branch| {
+                let index = branch.indices().find(|index| {
+                    self.nodes.get(*index).instructions.instruction_list
+                        == instructions.instruction_list
+                })?;
+                // an outcome that transposes to another node keeps its subtree there
+                let index = self.nodes.transposed(index);
+                // the options at a node deeper in the tree were not filtered the way
+                // `root_get_all_options` filters them, so the subtree is only usable if they agree
+                let compatible = match self.nodes.get(index).options.get() {
+                    Some(options) => {
+                        options_match(&options.s1, &side_one_options)
+                            && options_match(&options.s2, &side_two_options)
+                    }
+                    None => true,
+                };
+                compatible.then_some(index)
+            });
+
+        match outcome {
+            Some(index) => {
+                self.reroot(index);
+                let root = self.nodes.get(ROOT);
+                let options = Box::new(SharedNodeOptions::new(side_one_options, side_two_options));
+                let options_usage = options.heap_usage();
+                if root.options.set(options).is_ok() {
+                    *self.memory_usage.get_mut() += options_usage;
+                }
+                true
+            }
+            None => {
+                *self = SharedMctsTree::new(side_one_options, side_two_options);
+                false
+            }
+        }
+    }
+
+    // makes the node at `new_root` the root, moving it and every node that can be reached from
+    // it into a new arena. the old root and the outcomes that did not happen are dropped
+    fn reroot(&mut self, new_root: NodeIndex) {
+        let mut old_nodes = std::mem::replace(&mut self.nodes, NodeArena::new());
+        let mut branches_by_parent = HashMap::new();
+        for ((parent, s1_index, s2_index), branch) in std::mem::take(&mut self.children) {
+            branches_by_parent
+                .entry(parent)
+                .or_insert_with(Vec::new)
+                .push(((s1_index, s2_index), branch));
+        }
+
+        let mut root = old_nodes
+            .take(new_root)
+            .expect("the new root is in the tree");
+        let depth_offset = root.depth;
+        root.root = true;
+        root.depth = 0;
+        let mut memory_usage = root.memory_usage();
+        let root_index = self.nodes.push_all(vec![root]);
+        debug_assert_eq!(root_index, Some(ROOT));
+
+        // pairs of the index a node had in the old arena and the index it has in the new one
+        let mut stack = vec![(new_root, ROOT)];
+        let mut moved = HashMap::from([(new_root, ROOT)]);
+        while let Some((old_parent, parent)) = stack.pop() {
+            for ((s1_index, s2_index), branch) in
+                branches_by_parent.remove(&old_parent).unwrap_or_default()
+            {
+                let nodes: Vec<Node> = branch
+                    .indices()
+                    .map(|index| {
+                        let mut node = old_nodes.take(index).expect("a branch's nodes are set");
+                        node.depth -= depth_offset;
+                        node
+                    })
+                    .collect();
+                memory_usage += SharedBranch::memory_usage(&nodes);
+                let start = self
+                    .nodes
+                    .push_all(nodes)
+                    .expect("the new arena holds fewer nodes than the old one");
+                stack.extend(branch.indices().zip(start..));
+                moved.extend(branch.indices().zip(start..));
+                self.children.insert(
+                    (parent, s1_index, s2_index),
+                    SharedBranch { start, ..branch },
+                );
+            }
+        }
+
+        // a node transposing to one that was dropped holds its own statistics again
+        for index in 0..self.nodes.len() as NodeIndex {
+            let node = self.nodes.get_mut(index);
+            if let Some(new_index) = node
+                .transposition
+                .take()
+                .and_then(|old_index| moved.get(&old_index))
+            {
+                let _ = node.transposition.set(*new_index);
+            }
+        }
+        self.transpositions = std::mem::take(&mut self.transpositions)
+            .into_iter()
+            .filter_map(|((hash, depth), old_index)| {
+                let new_index = *moved.get(&old_index)?;
+                Some(((hash, depth - depth_offset), new_index))
+            })
+            .collect();
+        self.memory_usage = AtomicUsize::new(memory_usage);
+    }
+}
+
+/// A search of a `SharedMctsTree` running on background threads, started with
+/// `SharedMctsTree::search_in_background`
+pub struct BackgroundSearch {
+    tree: Arc<SharedMctsTree>,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<MctsResult>,
+}
+
+impl BackgroundSearch {
+    /// The statistics of the root's options so far, without a solved Nash strategy or
+    /// principal variation
+    pub fn snapshot(&self) -> MctsResult {
+        root_result(self.tree.nodes.get(ROOT), self.tree.leaf_depths.load())
+    }
+
+    /// Whether the search has stopped, on its own or through `stop`
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Stops the search once every worker finishes the iteration it is on, and returns the
+    /// tree and the search's result
+    pub fn stop(self) -> (SharedMctsTree, MctsResult) {
+        self.stop.store(true, Ordering::Release);
+        self.wait()
+    }
+
+    /// Waits for the search to stop on its own, and returns the tree and the search's result
+    pub fn wait(self) -> (SharedMctsTree, MctsResult) {
+        let result = self.handle.join().expect("the search thread panicked");
+        match Arc::try_unwrap(self.tree) {
+            Ok(tree) => (tree, result),
+            Err(_) => unreachable!("only the search thread shares the tree"),
+        }
+    }
+}
+
+pub fn perform_mcts_shared_tree(
+    state: &mut State,
+    side_one_options: Vec<(MoveChoice, MoveChoice)>,
+    side_two_options: Vec<(MoveChoice, MoveChoice)>,
+    max_time: Duration,
+    worker_count: usize,
+    config: &MctsConfig,
+) -> MctsResult {
+    let start_time = Instant::now();
+    if is_endgame(state, config) {
+        if let Some(result) = solve_endgame(
+            state,
+            &side_one_options,
+            &side_two_options,
+            max_time,
+            config,
+        ) {
+            return result;
+        }
     }
+    SharedMctsTree::new(side_one_options, side_two_options).search(
+        state,
+        max_time.saturating_sub(start_time.elapsed()),
+        worker_count,
+        config,
+    )
 }
@@ -2,7 +2,7 @@ use crate::define_enum_with_from_str;
 
 define_enum_with_from_str! {
     #[repr(i16)]
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
     PokemonName {
         NONE,
         BULBASAUR,
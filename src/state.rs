@@ -556,6 +556,9 @@ impl Default for Side {
             side_conditions: SideConditions {
                 ..Default::default()
             },
+            dynamax_used: false,
+            z_move_used: false,
+            mega_used: false,
         };
         side.slot_a.active_index = PokemonIndex::P0;
         side.slot_b.active_index = PokemonIndex::P1;
@@ -812,6 +815,7 @@ impl StateTrickRoom {
 #[derive(Debug, Clone)]
 pub struct VolatileStatusDurations {
     pub confusion: i8,
+    pub dynamax: i8,
     pub encore: i8,
     pub lockedmove: i8,
     pub protect: i8,
@@ -824,6 +828,7 @@ impl Default for VolatileStatusDurations {
     fn default() -> VolatileStatusDurations {
         VolatileStatusDurations {
             confusion: 0,
+            dynamax: 0,
             encore: 0,
             lockedmove: 0,
             protect: 0,
@@ -838,6 +843,7 @@ impl VolatileStatusDurations {
     pub fn pprint(&self) -> String {
         let durations = [
             ("confusion", self.confusion),
+            ("dynamax", self.dynamax),
             ("encore", self.encore),
             ("lockedmove", self.lockedmove),
             ("protect", self.protect),
@@ -860,14 +866,15 @@ impl VolatileStatusDurations {
 
     pub fn serialize(&self) -> String {
         format!(
-            "{};{};{};{};{};{};{}",
+            "{};{};{};{};{};{};{};{}",
             self.confusion,
             self.encore,
             self.lockedmove,
             self.protect,
             self.slowstart,
             self.taunt,
-            self.yawn
+            self.yawn,
+            self.dynamax
         )
     }
     pub fn deserialize(serialized: &str) -> VolatileStatusDurations {
@@ -880,6 +887,11 @@ impl VolatileStatusDurations {
             slowstart: split[4].parse::<i8>().unwrap(),
             taunt: split[5].parse::<i8>().unwrap(),
             yawn: split[6].parse::<i8>().unwrap(),
+            // dynamax was added after the other durations; older strings omit it
+            dynamax: match split.get(7) {
+                Some(dynamax_str) => dynamax_str.parse::<i8>().unwrap(),
+                None => 0,
+            },
         }
     }
 }
@@ -897,6 +909,7 @@ pub struct Pokemon {
     pub item: Items,
     pub nature: PokemonNature,
     pub evs: (u8, u8, u8, u8, u8, u8),
+    pub ivs: (u8, u8, u8, u8, u8, u8),
     pub attack: i16,
     pub defense: i16,
     pub special_attack: i16,
@@ -928,6 +941,7 @@ impl Default for Pokemon {
             item: Items::NONE,
             nature: PokemonNature::SERIOUS,
             evs: (11, 11, 11, 11, 11, 11),
+            ivs: (31, 31, 31, 31, 31, 31),
             attack: 100,
             defense: 100,
             special_attack: 100,
@@ -1011,13 +1025,17 @@ impl Pokemon {
             "{};{};{};{};{};{}",
             self.evs.0, self.evs.1, self.evs.2, self.evs.3, self.evs.4, self.evs.5
         );
+        let ivs_str = format!(
+            "{};{};{};{};{};{}",
+            self.ivs.0, self.ivs.1, self.ivs.2, self.ivs.3, self.ivs.4, self.ivs.5
+        );
         let mut stellar_boosted_types = String::new();
         for pkmn_type in &self.stellar_boosted_types {
             stellar_boosted_types.push_str(&pkmn_type.to_string());
             stellar_boosted_types.push_str(";");
         }
         format!(
-            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
             self.id,
             self.level,
             self.types.0.to_string(),
@@ -1049,6 +1067,7 @@ impl Pokemon {
             self.tera_type.to_string(),
             self.times_attacked,
             stellar_boosted_types,
+            ivs_str,
         )
     }
 
@@ -1067,6 +1086,22 @@ impl Pokemon {
         } else {
             (11, 11, 11, 11, 11, 11)
         };
+
+        // ivs were added after the rest of the format, so they may be missing entirely
+        let ivs = match split.get(31) {
+            Some(ivs_str) if !ivs_str.is_empty() => {
+                let mut iv_iter = ivs_str.split(";");
+                (
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                    iv_iter.next().unwrap().parse::<u8>().unwrap(),
+                )
+            }
+            _ => (31, 31, 31, 31, 31, 31),
+        };
         let mut stellar_boosted_types = HashSet::new();
         if split[30] != "" {
             for item in split[30].split(";") {
@@ -1091,6 +1126,7 @@ impl Pokemon {
             item: Items::from_str(split[10]).unwrap(),
             nature: PokemonNature::from_str(split[11]).unwrap(),
             evs,
+            ivs,
             attack: split[13].parse::<i16>().unwrap(),
             defense: split[14].parse::<i16>().unwrap(),
             special_attack: split[15].parse::<i16>().unwrap(),
@@ -1349,6 +1385,9 @@ pub struct Side {
     pub side_conditions: SideConditions,
     pub slot_a: SideSlot,
     pub slot_b: SideSlot,
+    pub dynamax_used: bool,
+    pub z_move_used: bool,
+    pub mega_used: bool,
 }
 impl Side {
     pub fn pprint(&self, slot_a_choices: Vec<String>, slot_b_choices: Vec<String>) -> String {
@@ -1410,7 +1449,7 @@ impl Side {
     }
     pub fn serialize(&self) -> String {
         format!(
-            "{}={}={}={}={}={}={}={}={}",
+            "{}={}={}={}={}={}={}={}={}={}={}={}",
             self.pokemon.pkmn[0].serialize(),
             self.pokemon.pkmn[1].serialize(),
             self.pokemon.pkmn[2].serialize(),
@@ -1420,6 +1459,9 @@ impl Side {
             self.slot_a.serialize(),
             self.slot_b.serialize(),
             self.side_conditions.serialize(),
+            self.dynamax_used,
+            self.z_move_used,
+            self.mega_used,
         )
     }
     pub fn deserialize(serialized: &str) -> Side {
@@ -1438,6 +1480,18 @@ impl Side {
             slot_a: SideSlot::deserialize(split[6]),
             slot_b: SideSlot::deserialize(split[7]),
             side_conditions: SideConditions::deserialize(split[8]),
+            dynamax_used: match split.get(9) {
+                Some(dynamax_used_str) => dynamax_used_str.parse::<bool>().unwrap(),
+                None => false,
+            },
+            z_move_used: match split.get(10) {
+                Some(z_move_used_str) => z_move_used_str.parse::<bool>().unwrap(),
+                None => false,
+            },
+            mega_used: match split.get(11) {
+                Some(mega_used_str) => mega_used_str.parse::<bool>().unwrap(),
+                None => false,
+            },
         }
     }
 }
@@ -1587,6 +1641,15 @@ pub struct State {
     pub team_preview: bool,
     pub use_last_used_move: bool,
     pub use_damage_dealt: bool,
+
+    /// Zobrist hash of every other field, kept up to date by applying and reversing instructions
+    /// while `track_hash` is set. Call `rehash` after changing fields directly
+    pub hash: u64,
+
+    /// Whether applying and reversing instructions updates `hash`. Off by default, as it hashes
+    /// every changed field twice per instruction and only the transposition table and the endgame
+    /// solver look states up by hash
+    pub track_hash: bool,
 }
 impl Default for State {
     fn default() -> State {
@@ -1607,11 +1670,14 @@ impl Default for State {
             team_preview: false,
             use_damage_dealt: false,
             use_last_used_move: false,
+            hash: 0,
+            track_hash: false,
         };
 
         // many tests rely on the speed of side 2's active pokemon being greater than side_one's
         s.sides[1].get_active(&SlotReference::SlotA).speed += 1;
         s.sides[1].get_active(&SlotReference::SlotB).speed += 1;
+        s.rehash();
         s
     }
 }
@@ -1965,6 +2031,9 @@ impl State {
             PokemonVolatileStatus::CONFUSION => {
                 slot.volatile_status_durations.confusion += amount;
             }
+            PokemonVolatileStatus::DYNAMAX => {
+                slot.volatile_status_durations.dynamax += amount;
+            }
             PokemonVolatileStatus::LOCKEDMOVE => {
                 slot.volatile_status_durations.lockedmove += amount;
             }
@@ -2228,6 +2297,7 @@ impl State {
     }
 
     pub fn apply_one_instruction(&mut self, instruction: &Instruction) {
+        let hash_before = self.tracked_instruction_hash(instruction);
         match instruction {
             Instruction::Damage(instruction) => self.damage(
                 instruction.side_ref,
@@ -2334,6 +2404,10 @@ impl State {
                 self.get_side(instruction.side_ref).pokemon[instruction.pokemon_index].speed +=
                     instruction.amount;
             }
+            Instruction::ChangeMaxHP(instruction) => {
+                self.get_side(instruction.side_ref).pokemon[instruction.pokemon_index].maxhp +=
+                    instruction.amount;
+            }
             Instruction::EnableMove(instruction) => self.enable_move(
                 instruction.side_ref,
                 &instruction.pokemon_index,
@@ -2430,6 +2504,15 @@ impl State {
                 self.get_side(instruction.side_ref).pokemon[&instruction.pokemon_index]
                     .mega_evolved ^= true
             }
+            Instruction::ToggleMegaUsed(instruction) => {
+                self.get_side(instruction.side_ref).mega_used ^= true
+            }
+            Instruction::ToggleDynamaxUsed(instruction) => {
+                self.get_side(instruction.side_ref).dynamax_used ^= true
+            }
+            Instruction::ToggleZMoveUsed(instruction) => {
+                self.get_side(instruction.side_ref).z_move_used ^= true
+            }
             Instruction::SetLastUsedMove(instruction) => self.set_last_used_move(
                 instruction.side_ref,
                 &instruction.slot_ref,
@@ -2478,6 +2561,7 @@ impl State {
                 self.team_preview = !self.team_preview;
             }
         }
+        self.hash ^= hash_before ^ self.tracked_instruction_hash(instruction);
     }
 
     pub fn reverse_instructions(&mut self, instructions: &Vec<Instruction>) {
@@ -2487,6 +2571,7 @@ impl State {
     }
 
     pub fn reverse_one_instruction(&mut self, instruction: &Instruction) {
+        let hash_before = self.tracked_instruction_hash(instruction);
         match instruction {
             Instruction::Damage(instruction) => self.heal(
                 instruction.side_ref,
@@ -2603,6 +2688,10 @@ impl State {
                 self.get_side(instruction.side_ref).pokemon[&instruction.pokemon_index].speed -=
                     instruction.amount;
             }
+            Instruction::ChangeMaxHP(instruction) => {
+                self.get_side(instruction.side_ref).pokemon[&instruction.pokemon_index].maxhp -=
+                    instruction.amount;
+            }
             Instruction::ChangeWish(instruction) => self.unset_wish(
                 instruction.side_ref,
                 &instruction.slot_ref,
@@ -2685,6 +2774,15 @@ impl State {
                 self.get_side(instruction.side_ref).pokemon[&instruction.pokemon_index]
                     .mega_evolved ^= true
             }
+            Instruction::ToggleMegaUsed(instruction) => {
+                self.get_side(instruction.side_ref).mega_used ^= true
+            }
+            Instruction::ToggleDynamaxUsed(instruction) => {
+                self.get_side(instruction.side_ref).dynamax_used ^= true
+            }
+            Instruction::ToggleZMoveUsed(instruction) => {
+                self.get_side(instruction.side_ref).z_move_used ^= true
+            }
             Instruction::SetLastUsedMove(instruction) => self.set_last_used_move(
                 instruction.side_ref,
                 &instruction.slot_ref,
@@ -2733,6 +2831,589 @@ impl State {
                 self.team_preview = !self.team_preview;
             }
         }
+        self.hash ^= hash_before ^ self.tracked_instruction_hash(instruction);
+    }
+}
+// Zobrist hashing: every (field, value) pair of a state has its own key and a state's hash is
+// the xor of the keys of its fields' current values, so applying an instruction only has to xor
+// out the keys of the fields it changes and xor in their new ones.
+// keys are derived from the field and value with splitmix64 instead of being drawn into a table
+// up front, since most fields can hold far too many values for one
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+fn zobrist_key(owner: u64, field: u64, value: u64) -> u64 {
+    splitmix64(splitmix64(owner << 8 | field) ^ value)
+}
+
+// the weather, terrain, trick room and other fields that belong to the whole battle
+const BATTLE_OWNER: u64 = 0;
+
+fn side_owner(side_ref: SideReference) -> u64 {
+    1 + side_ref as u64
+}
+
+fn slot_owner(side_ref: SideReference, slot_ref: SlotReference) -> u64 {
+    3 + 2 * side_ref as u64 + slot_ref as u64
+}
+
+fn pokemon_owner(side_ref: SideReference, pokemon_index: PokemonIndex) -> u64 {
+    7 + 6 * side_ref as u64 + pokemon_index as u64
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StateField {
+    Weather,
+    Terrain,
+    TrickRoom,
+    TeamPreview,
+    UseLastUsedMove,
+    UseDamageDealt,
+}
+
+// whether the side has used each of these.
+// side conditions are hashed as fields `0..SIDE_CONDITIONS.len()`
+#[derive(Debug, Clone, Copy)]
+enum SideField {
+    Dynamax = 32,
+    ZMove,
+    Mega,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SlotField {
+    ActiveIndex,
+    BatonPassing,
+    ShedTailing,
+    VolatileStatusDurations,
+    Wish,
+    FutureSight,
+    ForceSwitch,
+    ForceTrapped,
+    SlowUturnMove,
+    VolatileStatuses,
+    SubstituteHealth,
+    Boosts,
+    LastUsedMove,
+    DamageDealt,
+    SwitchOutMove,
+}
+
+// moves are hashed as fields `MOVES_FIELD..MOVES_FIELD + 4`
+#[derive(Debug, Clone, Copy)]
+enum PokemonField {
+    Id,
+    Level,
+    Types,
+    BaseTypes,
+    Hp,
+    MaxHp,
+    Ability,
+    BaseAbility,
+    Item,
+    Nature,
+    Evs,
+    Ivs,
+    Attack,
+    Defense,
+    SpecialAttack,
+    SpecialDefense,
+    Speed,
+    Status,
+    RestTurns,
+    SleepTurns,
+    WeightKg,
+    MegaEvolved,
+    Terastallized,
+    TeraType,
+    TimesAttacked,
+    StellarBoostedTypes,
+}
+
+const MOVES_FIELD: u64 = 32;
+
+const STATE_FIELDS: [StateField; 6] = [
+    StateField::Weather,
+    StateField::Terrain,
+    StateField::TrickRoom,
+    StateField::TeamPreview,
+    StateField::UseLastUsedMove,
+    StateField::UseDamageDealt,
+];
+
+const SIDE_FIELDS: [SideField; 3] = [SideField::Dynamax, SideField::ZMove, SideField::Mega];
+
+const SIDE_CONDITIONS: [PokemonSideCondition; 19] = [
+    PokemonSideCondition::AuroraVeil,
+    PokemonSideCondition::CraftyShield,
+    PokemonSideCondition::HealingWish,
+    PokemonSideCondition::LightScreen,
+    PokemonSideCondition::LuckyChant,
+    PokemonSideCondition::LunarDance,
+    PokemonSideCondition::MatBlock,
+    PokemonSideCondition::Mist,
+    PokemonSideCondition::Protect,
+    PokemonSideCondition::QuickGuard,
+    PokemonSideCondition::Reflect,
+    PokemonSideCondition::Safeguard,
+    PokemonSideCondition::Spikes,
+    PokemonSideCondition::Stealthrock,
+    PokemonSideCondition::StickyWeb,
+    PokemonSideCondition::Tailwind,
+    PokemonSideCondition::ToxicCount,
+    PokemonSideCondition::ToxicSpikes,
+    PokemonSideCondition::WideGuard,
+];
+
+const SLOT_FIELDS: [SlotField; 15] = [
+    SlotField::ActiveIndex,
+    SlotField::BatonPassing,
+    SlotField::ShedTailing,
+    SlotField::VolatileStatusDurations,
+    SlotField::Wish,
+    SlotField::FutureSight,
+    SlotField::ForceSwitch,
+    SlotField::ForceTrapped,
+    SlotField::SlowUturnMove,
+    SlotField::VolatileStatuses,
+    SlotField::SubstituteHealth,
+    SlotField::Boosts,
+    SlotField::LastUsedMove,
+    SlotField::DamageDealt,
+    SlotField::SwitchOutMove,
+];
+
+const POKEMON_FIELDS: [PokemonField; 26] = [
+    PokemonField::Id,
+    PokemonField::Level,
+    PokemonField::Types,
+    PokemonField::BaseTypes,
+    PokemonField::Hp,
+    PokemonField::MaxHp,
+    PokemonField::Ability,
+    PokemonField::BaseAbility,
+    PokemonField::Item,
+    PokemonField::Nature,
+    PokemonField::Evs,
+    PokemonField::Ivs,
+    PokemonField::Attack,
+    PokemonField::Defense,
+    PokemonField::SpecialAttack,
+    PokemonField::SpecialDefense,
+    PokemonField::Speed,
+    PokemonField::Status,
+    PokemonField::RestTurns,
+    PokemonField::SleepTurns,
+    PokemonField::WeightKg,
+    PokemonField::MegaEvolved,
+    PokemonField::Terastallized,
+    PokemonField::TeraType,
+    PokemonField::TimesAttacked,
+    PokemonField::StellarBoostedTypes,
+];
+
+fn types_value(types: &(PokemonType, PokemonType)) -> u64 {
+    (types.0 as u64) << 8 | types.1 as u64
+}
+
+fn stats_value(stats: &(u8, u8, u8, u8, u8, u8)) -> u64 {
+    u64::from_le_bytes([stats.0, stats.1, stats.2, stats.3, stats.4, stats.5, 0, 0])
+}
+
+impl Pokemon {
+    fn zobrist_field(&self, owner: u64, field: PokemonField) -> u64 {
+        let value = match field {
+            PokemonField::Id => self.id as u64,
+            PokemonField::Level => self.level as u64,
+            PokemonField::Types => types_value(&self.types),
+            PokemonField::BaseTypes => types_value(&self.base_types),
+            PokemonField::Hp => self.hp as u64,
+            PokemonField::MaxHp => self.maxhp as u64,
+            PokemonField::Ability => self.ability as u64,
+            PokemonField::BaseAbility => self.base_ability as u64,
+            PokemonField::Item => self.item as u64,
+            PokemonField::Nature => self.nature.clone() as u64,
+            PokemonField::Evs => stats_value(&self.evs),
+            PokemonField::Ivs => stats_value(&self.ivs),
+            PokemonField::Attack => self.attack as u64,
+            PokemonField::Defense => self.defense as u64,
+            PokemonField::SpecialAttack => self.special_attack as u64,
+            PokemonField::SpecialDefense => self.special_defense as u64,
+            PokemonField::Speed => self.speed as u64,
+            PokemonField::Status => self.status as u64,
+            PokemonField::RestTurns => self.rest_turns as u64,
+            PokemonField::SleepTurns => self.sleep_turns as u64,
+            PokemonField::WeightKg => self.weight_kg.to_bits() as u64,
+            PokemonField::MegaEvolved => self.mega_evolved as u64,
+            PokemonField::Terastallized => self.terastallized as u64,
+            PokemonField::TeraType => self.tera_type as u64,
+            PokemonField::TimesAttacked => self.times_attacked as u64,
+
+            // a set, so each member gets its own key
+            PokemonField::StellarBoostedTypes => {
+                return self.stellar_boosted_types.iter().fold(0, |hash, x| {
+                    hash ^ zobrist_key(owner, field as u64, *x as u64)
+                });
+            }
+        };
+        zobrist_key(owner, field as u64, value)
+    }
+
+    fn zobrist_move(&self, owner: u64, move_index: &PokemonMoveIndex) -> u64 {
+        let mv = &self.moves[move_index];
+        let value = (mv.id as u64) << 16 | (mv.disabled as u64) << 8 | mv.pp as u8 as u64;
+        zobrist_key(owner, MOVES_FIELD + *move_index as u64, value)
+    }
+
+    fn zobrist_hash(&self, owner: u64) -> u64 {
+        let mut hash = 0;
+        for field in POKEMON_FIELDS.iter() {
+            hash ^= self.zobrist_field(owner, *field);
+        }
+        for move_index in [
+            PokemonMoveIndex::M0,
+            PokemonMoveIndex::M1,
+            PokemonMoveIndex::M2,
+            PokemonMoveIndex::M3,
+        ]
+        .iter()
+        {
+            hash ^= self.zobrist_move(owner, move_index);
+        }
+        hash
+    }
+}
+
+impl SideSlot {
+    fn zobrist_field(&self, owner: u64, field: SlotField) -> u64 {
+        let value = match field {
+            SlotField::ActiveIndex => self.active_index as u64,
+            SlotField::BatonPassing => self.baton_passing as u64,
+            SlotField::ShedTailing => self.shed_tailing as u64,
+            SlotField::VolatileStatusDurations => {
+                let durations = &self.volatile_status_durations;
+                u64::from_le_bytes([
+                    durations.confusion as u8,
+                    durations.dynamax as u8,
+                    durations.encore as u8,
+                    durations.lockedmove as u8,
+                    durations.protect as u8,
+                    durations.slowstart as u8,
+                    durations.taunt as u8,
+                    durations.yawn as u8,
+                ])
+            }
+            SlotField::Wish => (self.wish.0 as u8 as u64) << 16 | self.wish.1 as u16 as u64,
+            SlotField::FutureSight => {
+                (self.future_sight.0 as u8 as u64) << 8 | self.future_sight.1 as u64
+            }
+            SlotField::ForceSwitch => self.force_switch as u64,
+            SlotField::ForceTrapped => self.force_trapped as u64,
+            SlotField::SlowUturnMove => self.slow_uturn_move as u64,
+            SlotField::VolatileStatuses => {
+                let bits = self.volatile_statuses.0;
+                bits as u64 ^ splitmix64((bits >> 64) as u64)
+            }
+            SlotField::SubstituteHealth => self.substitute_health as u64,
+            SlotField::Boosts => u64::from_le_bytes([
+                self.attack_boost as u8,
+                self.defense_boost as u8,
+                self.special_attack_boost as u8,
+                self.special_defense_boost as u8,
+                self.speed_boost as u8,
+                self.accuracy_boost as u8,
+                self.evasion_boost as u8,
+                0,
+            ]),
+            SlotField::LastUsedMove => match self.last_used_move {
+                LastUsedMove::Move(move_index) => move_index as u64,
+                LastUsedMove::Switch(pokemon_index) => 4 + pokemon_index as u64,
+                LastUsedMove::None => 10,
+            },
+            SlotField::DamageDealt => {
+                (self.damage_dealt.damage as u16 as u64)
+                    | (self.damage_dealt.move_category as u64) << 16
+                    | (self.damage_dealt.hit_substitute as u64) << 24
+            }
+            SlotField::SwitchOutMove => self.switch_out_move_second_saved_move.to_u8() as u64,
+        };
+        zobrist_key(owner, field as u64, value)
+    }
+
+    fn zobrist_hash(&self, owner: u64) -> u64 {
+        SLOT_FIELDS
+            .iter()
+            .fold(0, |hash, field| hash ^ self.zobrist_field(owner, *field))
+    }
+}
+
+impl Side {
+    fn zobrist_side_condition(&self, owner: u64, side_condition: PokemonSideCondition) -> u64 {
+        zobrist_key(
+            owner,
+            side_condition as u64,
+            self.get_side_condition(side_condition) as u64,
+        )
+    }
+
+    fn zobrist_field(&self, owner: u64, field: SideField) -> u64 {
+        let value = match field {
+            SideField::Dynamax => self.dynamax_used,
+            SideField::ZMove => self.z_move_used,
+            SideField::Mega => self.mega_used,
+        };
+        zobrist_key(owner, field as u64, value as u64)
+    }
+
+    fn zobrist_hash(&self, side_ref: SideReference) -> u64 {
+        let owner = side_owner(side_ref);
+        let mut hash = 0;
+        for side_condition in SIDE_CONDITIONS.iter() {
+            hash ^= self.zobrist_side_condition(owner, *side_condition);
+        }
+        for field in SIDE_FIELDS.iter() {
+            hash ^= self.zobrist_field(owner, *field);
+        }
+        hash ^= self
+            .slot_a
+            .zobrist_hash(slot_owner(side_ref, SlotReference::SlotA));
+        hash ^= self
+            .slot_b
+            .zobrist_hash(slot_owner(side_ref, SlotReference::SlotB));
+        for pokemon_index in pokemon_index_iter() {
+            hash ^=
+                self.pokemon[pokemon_index].zobrist_hash(pokemon_owner(side_ref, pokemon_index));
+        }
+        hash
+    }
+}
+
+impl State {
+    /// Computes the Zobrist hash of the state from scratch. `hash` holds the same value as long
+    /// as the state has only been changed through instructions
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for field in STATE_FIELDS.iter() {
+            hash ^= self.zobrist_field(*field);
+        }
+        hash ^ self.sides[0].zobrist_hash(SideReference::SideOne)
+            ^ self.sides[1].zobrist_hash(SideReference::SideTwo)
+    }
+
+    /// Recomputes `hash`, needed after changing the state's fields directly
+    pub fn rehash(&mut self) {
+        self.hash = self.zobrist_hash();
+    }
+
+    fn zobrist_field(&self, field: StateField) -> u64 {
+        let value = match field {
+            StateField::Weather => {
+                (self.weather.weather_type as u64) << 8 | self.weather.turns_remaining as u8 as u64
+            }
+            StateField::Terrain => {
+                (self.terrain.terrain_type as u64) << 8 | self.terrain.turns_remaining as u8 as u64
+            }
+            StateField::TrickRoom => {
+                (self.trick_room.active as u64) << 8 | self.trick_room.turns_remaining as u8 as u64
+            }
+            StateField::TeamPreview => self.team_preview as u64,
+            StateField::UseLastUsedMove => self.use_last_used_move as u64,
+            StateField::UseDamageDealt => self.use_damage_dealt as u64,
+        };
+        zobrist_key(BATTLE_OWNER, field as u64, value)
+    }
+
+    fn zobrist_side_field(&self, side_ref: SideReference, field: SideField) -> u64 {
+        self.sides[side_ref as usize].zobrist_field(side_owner(side_ref), field)
+    }
+
+    fn zobrist_slot_field(
+        &self,
+        side_ref: SideReference,
+        slot_ref: SlotReference,
+        field: SlotField,
+    ) -> u64 {
+        self.sides[side_ref as usize]
+            .get_slot_immutable(&slot_ref)
+            .zobrist_field(slot_owner(side_ref, slot_ref), field)
+    }
+
+    fn zobrist_pokemon_field(
+        &self,
+        side_ref: SideReference,
+        pokemon_index: PokemonIndex,
+        field: PokemonField,
+    ) -> u64 {
+        self.sides[side_ref as usize].pokemon[pokemon_index]
+            .zobrist_field(pokemon_owner(side_ref, pokemon_index), field)
+    }
+
+    // `instruction_hash`, or nothing when `hash` is not being tracked
+    fn tracked_instruction_hash(&self, instruction: &Instruction) -> u64 {
+        if self.track_hash {
+            self.instruction_hash(instruction)
+        } else {
+            0
+        }
+    }
+
+    // the xor of the keys of every field `instruction` changes, at their current values
+    fn instruction_hash(&self, instruction: &Instruction) -> u64 {
+        match instruction {
+            Instruction::Damage(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Hp)
+            }
+            Instruction::Heal(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Hp)
+            }
+            Instruction::Switch(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::ActiveIndex)
+            }
+            Instruction::ApplyVolatileStatus(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::VolatileStatuses)
+            }
+            Instruction::RemoveVolatileStatus(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::VolatileStatuses)
+            }
+            Instruction::ChangeStatus(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Status)
+            }
+            Instruction::Boost(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::Boosts)
+            }
+            Instruction::ChangeSideCondition(i) => self.sides[i.side_ref as usize]
+                .zobrist_side_condition(side_owner(i.side_ref), i.side_condition),
+            Instruction::ChangeVolatileStatusDuration(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::VolatileStatusDurations)
+            }
+            Instruction::ChangeWeather(_) | Instruction::DecrementWeatherTurnsRemaining => {
+                self.zobrist_field(StateField::Weather)
+            }
+            Instruction::ChangeTerrain(_) | Instruction::DecrementTerrainTurnsRemaining => {
+                self.zobrist_field(StateField::Terrain)
+            }
+            Instruction::ToggleTrickRoom(_) | Instruction::DecrementTrickRoomTurnsRemaining => {
+                self.zobrist_field(StateField::TrickRoom)
+            }
+            Instruction::ToggleTeamPreview => self.zobrist_field(StateField::TeamPreview),
+            Instruction::ChangeType(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Types)
+            }
+            Instruction::ChangeAbility(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Ability)
+            }
+            Instruction::ChangeBaseAbility(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::BaseAbility)
+            }
+            Instruction::ChangeItem(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Item)
+            }
+            Instruction::ChangeAttack(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Attack)
+            }
+            Instruction::ChangeDefense(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Defense)
+            }
+            Instruction::ChangeSpecialAttack(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::SpecialAttack)
+            }
+            Instruction::ChangeSpecialDefense(i) => self.zobrist_pokemon_field(
+                i.side_ref,
+                i.pokemon_index,
+                PokemonField::SpecialDefense,
+            ),
+            Instruction::ChangeSpeed(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Speed)
+            }
+            Instruction::ChangeMaxHP(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::MaxHp)
+            }
+            Instruction::DisableMove(i) => self.sides[i.side_ref as usize].pokemon[i.pokemon_index]
+                .zobrist_move(pokemon_owner(i.side_ref, i.pokemon_index), &i.move_index),
+            Instruction::EnableMove(i) => self.sides[i.side_ref as usize].pokemon[i.pokemon_index]
+                .zobrist_move(pokemon_owner(i.side_ref, i.pokemon_index), &i.move_index),
+            Instruction::DecrementPP(i) => self.sides[i.side_ref as usize].pokemon[i.pokemon_index]
+                .zobrist_move(pokemon_owner(i.side_ref, i.pokemon_index), &i.move_index),
+            Instruction::ChangeWish(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::Wish)
+            }
+            Instruction::DecrementWish(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::Wish)
+            }
+            Instruction::SetFutureSight(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::FutureSight)
+            }
+            Instruction::DecrementFutureSight(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::FutureSight)
+            }
+            Instruction::DamageSubstitute(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::SubstituteHealth)
+            }
+            Instruction::ChangeSubstituteHealth(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::SubstituteHealth)
+            }
+            Instruction::DecrementRestTurns(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::RestTurns)
+            }
+            Instruction::SetRestTurns(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::RestTurns)
+            }
+            Instruction::SetSleepTurns(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::SleepTurns)
+            }
+            Instruction::FormeChange(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Id)
+            }
+            Instruction::SetSwitchOutMove(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::SwitchOutMove)
+            }
+            Instruction::ToggleBatonPassing(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::BatonPassing)
+            }
+            Instruction::ToggleShedTailing(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::ShedTailing)
+            }
+            Instruction::SetLastUsedMove(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::LastUsedMove)
+            }
+            Instruction::ChangeDamageDealtDamage(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::DamageDealt)
+            }
+            Instruction::ChangeDamageDealtMoveCatagory(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::DamageDealt)
+            }
+            Instruction::ToggleDamageDealtHitSubstitute(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::DamageDealt)
+            }
+            Instruction::ToggleForceSwitch(i) => {
+                self.zobrist_slot_field(i.side_ref, i.slot_ref, SlotField::ForceSwitch)
+            }
+            Instruction::ToggleMegaEvolved(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::MegaEvolved)
+            }
+            Instruction::ToggleTerastallized(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::Terastallized)
+            }
+            Instruction::ToggleMegaUsed(i) => self.zobrist_side_field(i.side_ref, SideField::Mega),
+            Instruction::ToggleDynamaxUsed(i) => {
+                self.zobrist_side_field(i.side_ref, SideField::Dynamax)
+            }
+            Instruction::ToggleZMoveUsed(i) => {
+                self.zobrist_side_field(i.side_ref, SideField::ZMove)
+            }
+            Instruction::IncrementTimesAttacked(i) => {
+                self.zobrist_pokemon_field(i.side_ref, i.pokemon_index, PokemonField::TimesAttacked)
+            }
+            Instruction::InsertStellarBoostedType(i) => self.zobrist_pokemon_field(
+                i.side_ref,
+                i.pokemon_index,
+                PokemonField::StellarBoostedTypes,
+            ),
+        }
     }
 }
 impl State {
@@ -2897,6 +3578,8 @@ impl State {
     /// // tera_type
     /// "Normal=",
     ///
+    /// // IVs split by `;` are the final field. They may be omitted entirely for default IVs (31 in all)
+    ///
     /// // all remaining Pokémon shown in 1 line for brevity
     /// "skarmory,100,Steel,Flying,Steel,Flying,271,271,STURDY,STURDY,CUSTAPBERRY,SERIOUS,,259,316,104,177,262,None,0,0,25.5,STEALTHROCK;false;32,SPIKES;false;32,BRAVEBIRD;false;24,THIEF;false;40,false,Normal=",
     /// "tyranitar,100,Rock,Dark,Rock,Dark,404,404,SANDSTREAM,SANDSTREAM,CHOPLEBERRY,SERIOUS,,305,256,203,327,159,None,0,0,25.5,CRUNCH;false;24,SUPERPOWER;false;8,THUNDERWAVE;false;32,PURSUIT;false;32,false,Normal=",
@@ -2996,8 +3679,11 @@ impl State {
             team_preview: split[5].parse::<bool>().unwrap(),
             use_damage_dealt: false,
             use_last_used_move: false,
+            hash: 0,
+            track_hash: false,
         };
         state.set_conditional_mechanics();
+        state.rehash();
         state
     }
 }
@@ -3005,6 +3691,8 @@ impl State {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::generate_instructions::generate_instructions_from_move_pair;
+    use crate::instruction::DamageInstruction;
     use std::collections::HashMap;
 
     #[test]
@@ -3044,6 +3732,8 @@ mod tests {
                 for &mv in &moves {
                     check(MoveChoice::MoveTera(slot, side, mv));
                     check(MoveChoice::MoveMega(slot, side, mv));
+                    check(MoveChoice::MoveDynamax(slot, side, mv));
+                    check(MoveChoice::MoveZ(slot, side, mv));
                     check(MoveChoice::Move(slot, side, mv));
                 }
             }
@@ -3064,10 +3754,189 @@ mod tests {
         // confirm total count is what we expect
         // MoveTera:    2 slots * 2 sides * 4 moves = 16
         // Move:        2 slots * 2 sides * 4 moves = 16
+        // MoveMega:    2 slots * 2 sides * 4 moves = 16
+        // MoveDynamax: 2 slots * 2 sides * 4 moves = 16
+        // MoveZ:       2 slots * 2 sides * 4 moves = 16
         // Switch:      6
         // TeamPreview: 6 * 6 = 36
         // None:        1
-        // Total:       75
-        assert_eq!(seen.len(), 91);
+        // Total:       123
+        assert_eq!(seen.len(), 123);
+    }
+
+    #[test]
+    fn test_pokemon_ivs_serialization_round_trip() {
+        let pkmn = Pokemon {
+            ivs: (31, 0, 31, 31, 31, 0),
+            ..Default::default()
+        };
+        let deserialized = Pokemon::deserialize(&pkmn.serialize());
+        assert_eq!(deserialized.ivs, (31, 0, 31, 31, 31, 0));
+    }
+
+    #[test]
+    fn test_pokemon_deserialize_without_ivs_defaults_to_31() {
+        let serialized = Pokemon::default().serialize();
+        let without_ivs = serialized.rsplit_once(",").unwrap().0;
+        let deserialized = Pokemon::deserialize(without_ivs);
+        assert_eq!(deserialized.ivs, (31, 31, 31, 31, 31, 31));
+    }
+
+    #[test]
+    fn test_side_dynamax_serialization_round_trip() {
+        let mut side = Side {
+            dynamax_used: true,
+            ..Default::default()
+        };
+        side.slot_a.volatile_status_durations.dynamax = 2;
+        let deserialized = Side::deserialize(&side.serialize());
+        assert!(deserialized.dynamax_used);
+        assert_eq!(deserialized.slot_a.volatile_status_durations.dynamax, 2);
+    }
+
+    #[test]
+    fn test_side_deserialize_without_dynamax_fields() {
+        let serialized = Side::default().serialize();
+        let without_dynamax_used = serialized.rsplit_once("=").unwrap().0;
+        let deserialized = Side::deserialize(without_dynamax_used);
+        assert!(!deserialized.dynamax_used);
+
+        let durations = VolatileStatusDurations::deserialize("0;0;0;0;0;0;0");
+        assert_eq!(durations.dynamax, 0);
+    }
+
+    #[test]
+    fn test_side_z_move_used_serialization_round_trip() {
+        let side = Side {
+            z_move_used: true,
+            ..Default::default()
+        };
+        let deserialized = Side::deserialize(&side.serialize());
+        assert!(deserialized.z_move_used);
+
+        let without_z_move_used = side.serialize().rsplitn(3, '=').last().unwrap().to_string();
+        assert!(!Side::deserialize(&without_z_move_used).z_move_used);
+    }
+
+    #[test]
+    fn test_side_mega_used_serialization_round_trip() {
+        let side = Side {
+            mega_used: true,
+            ..Default::default()
+        };
+        let deserialized = Side::deserialize(&side.serialize());
+        assert!(deserialized.mega_used);
+
+        let without_mega_used = side.serialize().rsplit_once("=").unwrap().0.to_string();
+        assert!(!Side::deserialize(&without_mega_used).mega_used);
+    }
+
+    fn varied_moves_state() -> State {
+        let mut state = State::default();
+        let movesets = [
+            [
+                Choices::HYDROPUMP,
+                Choices::SWORDSDANCE,
+                Choices::WILLOWISP,
+                Choices::UTURN,
+            ],
+            [
+                Choices::ROCKSLIDE,
+                Choices::THUNDERWAVE,
+                Choices::PROTECT,
+                Choices::SUBSTITUTE,
+            ],
+        ];
+        for (side, moveset) in state.sides.iter_mut().zip(movesets.iter()) {
+            for pkmn in side.pokemon.pkmn.iter_mut() {
+                for (move_index, choice) in [
+                    PokemonMoveIndex::M0,
+                    PokemonMoveIndex::M1,
+                    PokemonMoveIndex::M2,
+                    PokemonMoveIndex::M3,
+                ]
+                .iter()
+                .zip(moveset.iter())
+                {
+                    pkmn.moves[move_index] = Move {
+                        id: *choice,
+                        disabled: false,
+                        pp: 16,
+                        choice: MOVES.get(choice).unwrap().clone(),
+                    };
+                }
+            }
+        }
+        state.track_hash = true;
+        state.rehash();
+        state
+    }
+
+    #[test]
+    fn test_hash_is_kept_up_to_date_by_instructions() {
+        let mut state = varied_moves_state();
+        let hash_before = state.hash;
+        let (s1_options, s2_options) = state.root_get_all_options();
+        for s1_move in s1_options.iter().step_by(3) {
+            for s2_move in s2_options.iter().step_by(3) {
+                let outcomes = generate_instructions_from_move_pair(
+                    &mut state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, true,
+                );
+                assert_eq!(hash_before, state.hash);
+                for outcome in outcomes {
+                    state.apply_instructions(&outcome.instruction_list);
+                    assert_eq!(state.zobrist_hash(), state.hash);
+                    state.reverse_instructions(&outcome.instruction_list);
+                    assert_eq!(hash_before, state.hash);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transposed_states_have_the_same_hash() {
+        let mut state = State {
+            track_hash: true,
+            ..Default::default()
+        };
+        let hash_before = state.hash;
+        let hit_a = Instruction::Damage(DamageInstruction {
+            side_ref: SideReference::SideTwo,
+            pokemon_index: PokemonIndex::P0,
+            damage_amount: 10,
+        });
+        let hit_b = Instruction::Damage(DamageInstruction {
+            side_ref: SideReference::SideTwo,
+            pokemon_index: PokemonIndex::P1,
+            damage_amount: 20,
+        });
+
+        state.apply_instructions(&vec![hit_a.clone(), hit_b.clone()]);
+        let hash_one_way = state.hash;
+        state.reverse_instructions(&vec![hit_a.clone(), hit_b.clone()]);
+        state.apply_instructions(&vec![hit_b, hit_a]);
+
+        assert_ne!(hash_before, hash_one_way);
+        assert_eq!(hash_one_way, state.hash);
+    }
+
+    #[test]
+    fn test_instructions_leave_an_untracked_hash_alone() {
+        let mut state = State::default();
+        let hash_before = state.hash;
+        state.apply_instructions(&vec![Instruction::Damage(DamageInstruction {
+            side_ref: SideReference::SideTwo,
+            pokemon_index: PokemonIndex::P0,
+            damage_amount: 10,
+        })]);
+
+        assert_eq!(hash_before, state.hash);
+        assert_ne!(state.zobrist_hash(), state.hash);
+    }
+
+    #[test]
+    fn test_deserialized_state_has_the_same_hash() {
+        let state = varied_moves_state();
+        assert_eq!(state.hash, State::deserialize(&state.serialize()).hash);
     }
 }
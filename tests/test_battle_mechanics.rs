@@ -11,8 +11,9 @@ use poke_engine::instruction::{
     HealInstruction, IncrementTimesAttackedInstruction, InsertStellarBoostedTypeInstruction,
     Instruction, RemoveVolatileStatusInstruction, SetLastUsedMoveInstruction,
     SetSecondMoveSwitchOutMoveInstruction, SetSleepTurnsInstruction, StateInstructions,
-    SwitchInstruction, ToggleForceSwitchInstruction, ToggleMegaEvolvedInstruction,
-    ToggleTerastallizedInstruction,
+    SwitchInstruction, ToggleDynamaxUsedInstruction, ToggleForceSwitchInstruction,
+    ToggleMegaEvolvedInstruction, ToggleMegaUsedInstruction, ToggleTerastallizedInstruction,
+    ToggleZMoveUsedInstruction,
 };
 use poke_engine::pokemon::PokemonName;
 use poke_engine::state::LastUsedMove;
@@ -94,6 +95,24 @@ fn set_moves_on_pkmn_and_call_generate_instructions(
     instructions
 }
 
+// a tackle between two default pokemon. tackle has 50 base power in gen5
+#[cfg(feature = "gen5")]
+const TACKLE_DAMAGE: i16 = 33;
+#[cfg(not(feature = "gen5"))]
+const TACKLE_DAMAGE: i16 = 25;
+
+// surf or muddy water hitting two default pokemon. both have 95 base power in gen5
+#[cfg(feature = "gen5")]
+const SPREAD_SURF_DAMAGE: i16 = 30;
+#[cfg(not(feature = "gen5"))]
+const SPREAD_SURF_DAMAGE: i16 = 28;
+
+// the same tackle used by a pokemon that does not get STAB from it
+#[cfg(feature = "gen5")]
+const NON_STAB_TACKLE_DAMAGE: i16 = 22;
+#[cfg(not(feature = "gen5"))]
+const NON_STAB_TACKLE_DAMAGE: i16 = 17;
+
 #[test]
 fn test_all_participants_using_tackle_on_a_separate_target() {
     let mut state = State::default();
@@ -139,22 +158,22 @@ fn test_all_participants_using_tackle_on_a_separate_target() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -206,17 +225,17 @@ fn test_everybody_targetting_side_one_slot_a() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -386,7 +405,7 @@ fn test_switching_in_terrain_activates_seed() {
 fn test_boost_berry() {
     let mut state = State::default();
     state.sides[1].pokemon.pkmn[0].item = Items::SALACBERRY;
-    state.sides[1].pokemon.pkmn[0].hp = 55;
+    state.sides[1].pokemon.pkmn[0].hp = 2 * TACKLE_DAMAGE + 5;
     state.sides[1].pokemon.pkmn[0].speed = 1;
     let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
         &mut state,
@@ -430,12 +449,12 @@ fn test_boost_berry() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Boost(BoostInstruction {
                 side_ref: SideReference::SideTwo,
@@ -458,7 +477,7 @@ fn test_boost_berry() {
 fn test_damage_reduction_berry() {
     let mut state = State::default();
     state.sides[1].pokemon.pkmn[0].item = Items::SALACBERRY;
-    state.sides[1].pokemon.pkmn[0].hp = 55;
+    state.sides[1].pokemon.pkmn[0].hp = 2 * TACKLE_DAMAGE + 5;
     state.sides[1].pokemon.pkmn[0].speed = 1;
     let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
         &mut state,
@@ -502,12 +521,12 @@ fn test_damage_reduction_berry() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Boost(BoostInstruction {
                 side_ref: SideReference::SideTwo,
@@ -544,6 +563,12 @@ fn test_choice_item_locking_and_boost() {
         TestMoveChoice::default(),
         TestMoveChoice::default(),
     );
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let choice_band_tackle_damage = 48;
+    #[cfg(not(feature = "gen5"))]
+    let choice_band_tackle_damage = 38;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -566,7 +591,7 @@ fn test_choice_item_locking_and_boost() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 38,
+                damage_amount: choice_band_tackle_damage,
             }),
         ],
     }];
@@ -628,7 +653,7 @@ fn test_neutralizing_gas_blocks_abilities() {
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 25,
+            damage_amount: TACKLE_DAMAGE,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -837,7 +862,7 @@ fn test_scrappy_can_hit_terastallized_ghost_type() {
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 25, // Normal damage since Scrappy allows hitting Ghost types
+            damage_amount: TACKLE_DAMAGE, // Normal damage since Scrappy allows hitting Ghost types
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -957,17 +982,17 @@ fn test_gulp_missile_surf_high_hp() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
         ],
     }];
@@ -1008,17 +1033,17 @@ fn test_gulp_missile_dive_low_hp() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
         ],
     }];
@@ -1138,6 +1163,12 @@ fn test_flamebody_guaranteed_burn() {
         TestMoveChoice::default(),
     );
 
+    // burn does 1/8 of max hp in gen5
+    #[cfg(feature = "gen5")]
+    let burn_damage = 12;
+    #[cfg(not(feature = "gen5"))]
+    let burn_damage = 6;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -1145,7 +1176,7 @@ fn test_flamebody_guaranteed_burn() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeStatus(ChangeStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -1156,7 +1187,7 @@ fn test_flamebody_guaranteed_burn() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 6,
+                damage_amount: burn_damage,
             }),
         ],
     }];
@@ -1272,6 +1303,11 @@ fn test_protean_does_not_activate_with_typechange_status() {
 fn test_gorilla_tactics_disables_other_moves() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[0].ability = Abilities::GORILLATACTICS;
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let gorilla_tactics_tackle_damage = 48;
+    #[cfg(not(feature = "gen5"))]
+    let gorilla_tactics_tackle_damage = 38;
 
     let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
         &mut state,
@@ -1310,7 +1346,7 @@ fn test_gorilla_tactics_disables_other_moves() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 38,
+                damage_amount: gorilla_tactics_tackle_damage,
             }),
         ],
     }];
@@ -1344,7 +1380,7 @@ fn test_mummy_spreads_on_contact() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeAbility(ChangeAbilityInstruction {
                 side_ref: SideReference::SideOne,
@@ -1415,7 +1451,7 @@ fn test_lingering_aroma_spreads_on_contact() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeAbility(ChangeAbilityInstruction {
                 side_ref: SideReference::SideOne,
@@ -1454,7 +1490,7 @@ fn test_wandering_spirit_spreads_on_contact() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeAbility(ChangeAbilityInstruction {
                 side_ref: SideReference::SideOne,
@@ -1494,7 +1530,7 @@ fn test_gulp_missile_gorging_form_changes_and_paralyzes() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::FormeChange(FormeChangeInstruction {
                 side_ref: SideReference::SideTwo,
@@ -1545,7 +1581,7 @@ fn test_gulp_missile_gulping_form_changes_and_lowers_defense() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::FormeChange(FormeChangeInstruction {
                 side_ref: SideReference::SideTwo,
@@ -1702,7 +1738,7 @@ fn test_stamina_boosts_defense_when_hit() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Boost(BoostInstruction {
                 side_ref: SideReference::SideTwo,
@@ -1742,7 +1778,7 @@ fn test_cotton_down_lowers_attacker_speed() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Boost(BoostInstruction {
                 side_ref: SideReference::SideOne,
@@ -1788,7 +1824,7 @@ fn test_sand_spit_sets_sandstorm() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 17,
+                damage_amount: NON_STAB_TACKLE_DAMAGE,
             }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SAND,
@@ -1796,6 +1832,7 @@ fn test_sand_spit_sets_sandstorm() {
                 previous_weather: Weather::NONE,
                 previous_weather_turns_remaining: -1,
             }),
+            #[cfg(not(feature = "gen5"))]
             Instruction::DecrementWeatherTurnsRemaining,
         ],
     }];
@@ -1836,7 +1873,7 @@ fn test_sand_spit_does_not_activate_if_sandstorm_already_active() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 17,
+                damage_amount: NON_STAB_TACKLE_DAMAGE,
             }),
             Instruction::DecrementWeatherTurnsRemaining,
         ],
@@ -1872,7 +1909,7 @@ fn test_seed_sower_sets_grassy_terrain() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeTerrain(ChangeTerrain {
                 new_terrain: Terrain::GRASSYTERRAIN,
@@ -2009,7 +2046,7 @@ fn test_toxic_debris_sets_toxic_spikes_on_physical_hit() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeSideCondition(ChangeSideConditionInstruction {
                 side_ref: SideReference::SideOne,
@@ -2081,7 +2118,7 @@ fn test_berserk_boosts_special_attack_when_crossing_half_hp() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Boost(BoostInstruction {
                 side_ref: SideReference::SideTwo,
@@ -2154,7 +2191,7 @@ fn test_rough_skin_damages_attacker_on_contact() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
@@ -2193,7 +2230,7 @@ fn test_iron_barbs_damages_attacker_on_contact() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
@@ -2232,7 +2269,7 @@ fn test_basic_spread_move_damages_both_targets() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
@@ -2357,22 +2394,22 @@ fn test_surf_spread_move() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
         ],
     }];
@@ -2400,13 +2437,19 @@ fn test_surf_spread_move_single_target() {
         TestMoveChoice::default(),
     );
 
+    // surf has 95 base power in gen5
+    #[cfg(feature = "gen5")]
+    let surf_damage = 39;
+    #[cfg(not(feature = "gen5"))]
+    let surf_damage = 37;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 37,
+            damage_amount: surf_damage,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -2446,17 +2489,17 @@ fn test_surf_spread_move_does_not_hit_telepathy_ally() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
         ],
     }];
@@ -2664,15 +2707,15 @@ fn test_slower_pokemon_has_weather_persist_in_team_preview() {
             }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SUN,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::NONE,
                 previous_weather_turns_remaining: -1,
             }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SAND,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::SUN,
-                previous_weather_turns_remaining: 5,
+                previous_weather_turns_remaining: WEATHER_ABILITY_TURNS,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
@@ -2790,9 +2833,12 @@ fn test_slower_pokemon_has_mega_evolution_weather_set_second() {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
             }),
+            Instruction::ToggleMegaUsed(ToggleMegaUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SUN,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::SAND,
                 previous_weather_turns_remaining: 5,
             }),
@@ -2847,12 +2893,16 @@ fn test_slower_pokemon_has_mega_evolution_weather_set_second() {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
             }),
+            Instruction::ToggleMegaUsed(ToggleMegaUsedInstruction {
+                side_ref: SideReference::SideTwo,
+            }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SAND,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::SUN,
-                previous_weather_turns_remaining: 5,
+                previous_weather_turns_remaining: WEATHER_ABILITY_TURNS,
             }),
+            #[cfg(not(feature = "gen5"))]
             Instruction::DecrementWeatherTurnsRemaining,
             // everyone besides ttar takes damage from sandstorm
             Instruction::Damage(DamageInstruction {
@@ -2916,7 +2966,7 @@ fn test_switching_into_drizzle_with_megaevolve_into_drought() {
             }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::RAIN,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::NONE,
                 previous_weather_turns_remaining: -1,
             }),
@@ -2971,12 +3021,16 @@ fn test_switching_into_drizzle_with_megaevolve_into_drought() {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
             }),
+            Instruction::ToggleMegaUsed(ToggleMegaUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
             Instruction::ChangeWeather(ChangeWeather {
                 new_weather: Weather::SUN,
-                new_weather_turns_remaining: 5,
+                new_weather_turns_remaining: WEATHER_ABILITY_TURNS,
                 previous_weather: Weather::RAIN,
-                previous_weather_turns_remaining: 5,
+                previous_weather_turns_remaining: WEATHER_ABILITY_TURNS,
             }),
+            #[cfg(not(feature = "gen5"))]
             Instruction::DecrementWeatherTurnsRemaining,
         ],
     }];
@@ -3042,6 +3096,12 @@ fn test_lifeorb_with_spread_move_only_damages_once() {
         TestMoveChoice::default(),
     );
 
+    // surf has 95 base power in gen5
+    #[cfg(feature = "gen5")]
+    let lifeorb_surf_damage = 38;
+    #[cfg(not(feature = "gen5"))]
+    let lifeorb_surf_damage = 37;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -3049,17 +3109,17 @@ fn test_lifeorb_with_spread_move_only_damages_once() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 37,
+                damage_amount: lifeorb_surf_damage,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 37,
+                damage_amount: lifeorb_surf_damage,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 37,
+                damage_amount: lifeorb_surf_damage,
             }),
             Instruction::Heal(HealInstruction {
                 side_ref: SideReference::SideOne,
@@ -3324,7 +3384,7 @@ fn test_single_target_move_is_redirected_if_target_faints() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -3689,13 +3749,19 @@ fn test_friendguard_reduces_damage() {
         TestMoveChoice::default(),
     );
 
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let friendguard_tackle_damage = 24;
+    #[cfg(not(feature = "gen5"))]
+    let friendguard_tackle_damage = 20;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 20,
+            damage_amount: friendguard_tackle_damage,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -3727,7 +3793,7 @@ fn test_friendguard_does_not_reduce_damage_to_self() {
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 25,
+            damage_amount: TACKLE_DAMAGE,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -3759,6 +3825,12 @@ fn test_helping_hand_being_used_on_ally() {
         TestMoveChoice::default(),
     );
 
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let helping_hand_tackle_damage = 49;
+    #[cfg(not(feature = "gen5"))]
+    let helping_hand_tackle_damage = 38;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -3771,7 +3843,7 @@ fn test_helping_hand_being_used_on_ally() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 38,
+                damage_amount: helping_hand_tackle_damage,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -3809,6 +3881,12 @@ fn test_side_two_using_helping_hand() {
         },
     );
 
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let helping_hand_tackle_damage = 49;
+    #[cfg(not(feature = "gen5"))]
+    let helping_hand_tackle_damage = 38;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -3821,7 +3899,7 @@ fn test_side_two_using_helping_hand() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 38,
+                damage_amount: helping_hand_tackle_damage,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideTwo,
@@ -4190,7 +4268,7 @@ fn test_megasol_ignores_def_boost_from_ice_in_snow() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25, // 17 normally
+                damage_amount: TACKLE_DAMAGE, // 17 normally
             }),
             Instruction::DecrementWeatherTurnsRemaining,
         ],
@@ -4409,7 +4487,7 @@ fn test_ragepowder_causes_move_to_target_this_pkmn() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0, // Damage to the Pokemon using Rage Powder
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -4459,7 +4537,7 @@ fn test_followme_causes_move_to_target_this_pkmn() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0, // Damage to the Pokemon using Rage Powder
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -4510,7 +4588,7 @@ fn test_ragepowder_does_not_redirect_grass_pokemon_moves() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1, // Rage powder did not redirect to P0
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -4601,7 +4679,7 @@ fn test_ragepowder_does_not_redirect_safetygoggles() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1, // Rage powder did not redirect to P0
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -4652,7 +4730,7 @@ fn test_ragepowder_does_not_redirect_overcoat() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1, // Rage powder did not redirect to P0
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideOne,
@@ -4739,6 +4817,12 @@ fn test_knockoff() {
         TestMoveChoice::default(),
     );
 
+    // knockoff has 20 base power and no bonus damage in gen5
+    #[cfg(feature = "gen5")]
+    let knockoff_damage = 9;
+    #[cfg(not(feature = "gen5"))]
+    let knockoff_damage = 40;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -4746,7 +4830,7 @@ fn test_knockoff() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 40,
+                damage_amount: knockoff_damage,
             }),
             Instruction::ChangeItem(ChangeItemInstruction {
                 side_ref: SideReference::SideTwo,
@@ -4797,12 +4881,12 @@ fn test_protect_setting_volatile() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                 side_ref: SideReference::SideTwo,
@@ -4855,17 +4939,17 @@ fn test_consecutive_protect_chance_to_fail() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::ChangeVolatileStatusDuration(
                     ChangeVolatileStatusDurationInstruction {
@@ -4889,12 +4973,12 @@ fn test_consecutive_protect_chance_to_fail() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                     side_ref: SideReference::SideTwo,
@@ -4950,17 +5034,17 @@ fn test_consecutive_spikyshield_chance_to_fail() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::ChangeVolatileStatusDuration(
                     ChangeVolatileStatusDurationInstruction {
@@ -4984,12 +5068,12 @@ fn test_consecutive_spikyshield_chance_to_fail() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
                     side_ref: SideReference::SideTwo,
@@ -5146,7 +5230,7 @@ fn test_quickguard_does_not_block_regular_priority_move() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::ChangeSideCondition(ChangeSideConditionInstruction {
                 side_ref: SideReference::SideTwo,
@@ -5338,7 +5422,7 @@ fn test_wideguard_protection() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 28,
+                damage_amount: SPREAD_SURF_DAMAGE,
             }),
             Instruction::ChangeSideCondition(ChangeSideConditionInstruction {
                 side_ref: SideReference::SideTwo,
@@ -5684,7 +5768,7 @@ fn test_suckerpunch_works() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -5723,7 +5807,7 @@ fn test_suckerpunch_fails_if_target_not_using_attacking_move() {
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideOne,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 25,
+            damage_amount: TACKLE_DAMAGE,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -6249,6 +6333,12 @@ fn test_mid_turn_priority_change() {
         },
     );
 
+    // grassy glide has 70 base power in gen8
+    #[cfg(feature = "gen8")]
+    let grassy_glide_damage = 29;
+    #[cfg(not(feature = "gen8"))]
+    let grassy_glide_damage = 24;
+
     let expected_instructions = vec![StateInstructions {
         end_of_turn_triggered: true,
         percentage: 100.0,
@@ -6268,12 +6358,12 @@ fn test_mid_turn_priority_change() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo, // grassy glide hits side_two last
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 24,
+                damage_amount: grassy_glide_damage,
             }),
             Instruction::DecrementTerrainTurnsRemaining,
         ],
@@ -6445,6 +6535,11 @@ fn test_junglehealing_heals_and_removes_status() {
 #[test]
 fn test_teraform_zero_removes_weather() {
     let mut state = State::default();
+    // tackle has 50 base power in gen5
+    #[cfg(feature = "gen5")]
+    let stellar_tackle_damage = 56;
+    #[cfg(not(feature = "gen5"))]
+    let stellar_tackle_damage = 45;
     state.sides[0].pokemon.pkmn[0].tera_type = PokemonType::STELLAR;
     state.sides[0].pokemon.pkmn[0].id = PokemonName::TERAPAGOSTERASTAL;
     state.sides[0].pokemon.pkmn[0].ability = Abilities::TERASHELL;
@@ -6527,7 +6622,7 @@ fn test_teraform_zero_removes_weather() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 45,
+                damage_amount: stellar_tackle_damage,
             }),
         ],
     }];
@@ -6731,7 +6826,7 @@ fn test_terashell_halves_normal_effectiveness_damage() {
         instruction_list: vec![Instruction::Damage(DamageInstruction {
             side_ref: SideReference::SideTwo,
             pokemon_index: PokemonIndex::P0,
-            damage_amount: 12,
+            damage_amount: TACKLE_DAMAGE / 2,
         })],
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
@@ -7298,12 +7393,12 @@ fn test_parental_bond_multi_hit() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 6,
+                damage_amount: TACKLE_DAMAGE / 4,
             }),
         ],
     }];
@@ -7337,7 +7432,7 @@ fn test_accuracy_lowered_affects_move_hit_chance() {
             instruction_list: vec![Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             })],
         },
         StateInstructions {
@@ -7456,17 +7551,17 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7477,12 +7572,12 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Boost(BoostInstruction {
                     side_ref: SideReference::SideOne,
@@ -7493,7 +7588,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7504,7 +7599,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Boost(BoostInstruction {
                     side_ref: SideReference::SideOne,
@@ -7515,12 +7610,12 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7531,12 +7626,12 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7547,12 +7642,12 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7563,7 +7658,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Boost(BoostInstruction {
                     side_ref: SideReference::SideOne,
@@ -7574,7 +7669,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Boost(BoostInstruction {
                     side_ref: SideReference::SideOne,
@@ -7585,7 +7680,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideTwo,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 25,
+                    damage_amount: TACKLE_DAMAGE,
                 }),
             ],
         },
@@ -7596,7 +7691,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P0,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
                 Instruction::Boost(BoostInstruction {
                     side_ref: SideReference::SideOne,
@@ -7607,7 +7702,7 @@ fn test_muddywater_accuracy_lowers_opponents_chance_to_hit() {
                 Instruction::Damage(DamageInstruction {
                     side_ref: SideReference::SideOne,
                     pokemon_index: PokemonIndex::P1,
-                    damage_amount: 28,
+                    damage_amount: SPREAD_SURF_DAMAGE,
                 }),
             ],
         },
@@ -7635,6 +7730,12 @@ fn test_move_that_can_miss_with_perfect_accuracy() {
         TestMoveChoice::default(),
     );
 
+    // overheat has 140 base power in gen5
+    #[cfg(feature = "gen5")]
+    let overheat_damage = 15;
+    #[cfg(not(feature = "gen5"))]
+    let overheat_damage = 14;
+
     let expected_instructions = vec![
         StateInstructions {
             end_of_turn_triggered: true,
@@ -7642,7 +7743,7 @@ fn test_move_that_can_miss_with_perfect_accuracy() {
             instruction_list: vec![Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 14,
+                damage_amount: overheat_damage,
             })],
         },
         StateInstructions {
@@ -8184,12 +8285,12 @@ fn test_actual_speed_tie() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideOne,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -8652,7 +8753,7 @@ fn test_being_hit_with_ragefist_increases_ragefist_damage() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P0,
-                damage_amount: 17,
+                damage_amount: NON_STAB_TACKLE_DAMAGE,
             }),
             Instruction::IncrementTimesAttacked(IncrementTimesAttackedInstruction {
                 side_ref: SideReference::SideTwo,
@@ -9115,7 +9216,7 @@ fn test_fainted_followme_does_not_redirect() {
             Instruction::Damage(DamageInstruction {
                 side_ref: SideReference::SideTwo,
                 pokemon_index: PokemonIndex::P1,
-                damage_amount: 25,
+                damage_amount: TACKLE_DAMAGE,
             }),
         ],
     }];
@@ -9800,3 +9901,789 @@ fn test_abilityshield_prevents_neutralizinggas() {
     }];
     assert_eq!(expected_instructions, vec_of_instructions);
 }
+
+#[test]
+#[cfg(feature = "gen5")]
+fn test_gen5_sleep_turns_reset_when_switching_out() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].status = PokemonStatus::SLEEP;
+    state.sides[0].pokemon.pkmn[0].sleep_turns = 2;
+
+    let vec_of_instructions = generate_instructions_with_state_assertion(
+        &mut state,
+        &MoveChoice::Switch(PokemonIndex::P2),
+        &MoveChoice::None,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        false,
+    );
+
+    assert_eq!(1, vec_of_instructions.len());
+    assert!(vec_of_instructions[0]
+        .instruction_list
+        .contains(&Instruction::SetSleepTurns(SetSleepTurnsInstruction {
+            side_ref: SideReference::SideOne,
+            pokemon_index: PokemonIndex::P0,
+            new_turns: 0,
+            previous_turns: 2,
+        })));
+}
+
+#[test]
+#[cfg(feature = "gen5")]
+fn test_gen5_paralysis_quarters_speed() {
+    use poke_engine::engine::generate_instructions::get_effective_speed;
+
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].speed = 100;
+    state.sides[0].pokemon.pkmn[0].status = PokemonStatus::PARALYZE;
+
+    assert_eq!(
+        25,
+        get_effective_speed(&state, SideReference::SideOne, &SlotReference::SlotA)
+    );
+}
+
+#[test]
+#[cfg(feature = "gen5")]
+fn test_gen5_ability_weather_is_indefinite() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[2].ability = Abilities::DROUGHT;
+
+    let vec_of_instructions = generate_instructions_with_state_assertion(
+        &mut state,
+        &MoveChoice::Switch(PokemonIndex::P2),
+        &MoveChoice::None,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        false,
+    );
+
+    assert!(vec_of_instructions[0]
+        .instruction_list
+        .contains(&Instruction::ChangeWeather(ChangeWeather {
+            new_weather: Weather::SUN,
+            new_weather_turns_remaining: -1,
+            previous_weather: Weather::NONE,
+            previous_weather_turns_remaining: -1,
+        })));
+}
+
+#[test]
+fn test_dynamax_doubles_hp_and_uses_max_move() {
+    let mut state = State::default();
+    // max strike's base power follows tackle's, which is 50 in gen5
+    #[cfg(feature = "gen5")]
+    let max_strike_damage = 63;
+    #[cfg(not(feature = "gen5"))]
+    let max_strike_damage = 56;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::TACKLE,
+            move_choice: MoveChoice::MoveDynamax(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ToggleDynamaxUsed(ToggleDynamaxUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
+            Instruction::ApplyVolatileStatus(ApplyVolatileStatusInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+            }),
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: 3,
+            }),
+            Instruction::ChangeMaxHP(ChangeStatInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                amount: 100,
+            }),
+            Instruction::Heal(HealInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                heal_amount: 100,
+            }),
+            Instruction::Damage(DamageInstruction {
+                side_ref: SideReference::SideTwo,
+                pokemon_index: PokemonIndex::P0,
+                damage_amount: max_strike_damage,
+            }),
+            // Max Strike lowers the speed of both opponents
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                stat: PokemonBoostableStat::Speed,
+                amount: -1,
+            }),
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotB,
+                stat: PokemonBoostableStat::Speed,
+                amount: -1,
+            }),
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: -1,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_dynamax_ends_after_its_last_turn() {
+    let mut state = State::default();
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[0].slot_a.volatile_status_durations.dynamax = 1;
+    state.sides[0].pokemon.pkmn[0].maxhp = 200;
+    state.sides[0].pokemon.pkmn[0].hp = 151;
+
+    let vec_of_instructions = generate_instructions_with_state_assertion(
+        &mut state,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        false,
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: -1,
+            }),
+            Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+            }),
+            Instruction::ChangeMaxHP(ChangeStatInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                amount: -100,
+            }),
+            Instruction::Heal(HealInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                heal_amount: -75,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_dynamax_ends_when_switching_out() {
+    let mut state = State::default();
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[0].slot_a.volatile_status_durations.dynamax = 2;
+    state.sides[0].pokemon.pkmn[0].maxhp = 200;
+    state.sides[0].pokemon.pkmn[0].hp = 200;
+
+    let vec_of_instructions = generate_instructions_with_state_assertion(
+        &mut state,
+        &MoveChoice::Switch(PokemonIndex::P2),
+        &MoveChoice::None,
+        &MoveChoice::None,
+        &MoveChoice::None,
+        false,
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: -2,
+            }),
+            Instruction::ChangeMaxHP(ChangeStatInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                amount: -100,
+            }),
+            Instruction::Heal(HealInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                heal_amount: -100,
+            }),
+            Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+            }),
+            Instruction::Switch(SwitchInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                previous_index: PokemonIndex::P0,
+                next_index: PokemonIndex::P2,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_max_guard_blocks_damaging_move() {
+    let mut state = State::default();
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[0].slot_a.volatile_status_durations.dynamax = 3;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::PROTECT,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::TACKLE,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    assert_eq!(1, vec_of_instructions.len());
+    assert!(vec_of_instructions[0]
+        .instruction_list
+        .contains(&Instruction::ApplyVolatileStatus(
+            ApplyVolatileStatusInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::MAXGUARD,
+            }
+        )));
+    assert!(!vec_of_instructions[0]
+        .instruction_list
+        .iter()
+        .any(|i| matches!(i, Instruction::Damage(_))));
+}
+
+#[test]
+fn test_low_kick_fails_against_dynamaxed_target() {
+    let mut state = State::default();
+    state.sides[1]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[1].slot_a.volatile_status_durations.dynamax = 3;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::LOWKICK,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    for state_instructions in vec_of_instructions {
+        assert!(!state_instructions
+            .instruction_list
+            .iter()
+            .any(|i| matches!(i, Instruction::Damage(_))));
+    }
+}
+
+#[test]
+fn test_dynamaxed_pokemon_cannot_flinch() {
+    let mut state = State::default();
+    state.sides[1]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[1].slot_a.volatile_status_durations.dynamax = 3;
+    state.sides[1].pokemon.pkmn[0].maxhp = 200;
+    state.sides[1].pokemon.pkmn[0].hp = 200;
+    state.sides[0].slot_a.last_used_move = LastUsedMove::Switch(PokemonIndex::P0);
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::FAKEOUT,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    for state_instructions in vec_of_instructions {
+        assert!(!state_instructions
+            .instruction_list
+            .contains(&Instruction::ApplyVolatileStatus(
+                ApplyVolatileStatusInstruction {
+                    side_ref: SideReference::SideTwo,
+                    slot_ref: SlotReference::SlotA,
+                    volatile_status: PokemonVolatileStatus::FLINCH,
+                }
+            )));
+    }
+}
+
+#[test]
+fn test_max_airstream_boosts_speed_of_both_allies() {
+    let mut state = State::default();
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[0].slot_a.volatile_status_durations.dynamax = 3;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::AIRSLASH,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    assert_eq!(1, vec_of_instructions.len());
+    for slot_ref in [SlotReference::SlotA, SlotReference::SlotB] {
+        assert!(vec_of_instructions[0]
+            .instruction_list
+            .contains(&Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref,
+                stat: PokemonBoostableStat::Speed,
+                amount: 1,
+            })));
+    }
+}
+
+#[test]
+fn test_z_move_uses_converted_base_power() {
+    let mut state = State::default();
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::THUNDERBOLT,
+            move_choice: MoveChoice::MoveZ(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    // Gigavolt Havoc from Thunderbolt is 175 base power and has no chance to paralyze
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ToggleZMoveUsed(ToggleZMoveUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
+            Instruction::Damage(DamageInstruction {
+                side_ref: SideReference::SideTwo,
+                pokemon_index: PokemonIndex::P0,
+                damage_amount: 73,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_max_move_hits_through_protect_for_a_quarter_of_its_damage() {
+    let mut state = State::default();
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+    state.sides[0].slot_a.volatile_status_durations.dynamax = 3;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::TACKLE,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::PROTECT,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+    // tackle has 50 base power in gen5, which makes a stronger max strike
+    #[cfg(feature = "gen5")]
+    let max_strike_damage = 17;
+    #[cfg(not(feature = "gen5"))]
+    let max_strike_damage = 14;
+
+    // tackle becomes max strike, which still lowers the speed of both opponents
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ApplyVolatileStatus(ApplyVolatileStatusInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+            }),
+            Instruction::Damage(DamageInstruction {
+                side_ref: SideReference::SideTwo,
+                pokemon_index: PokemonIndex::P0,
+                damage_amount: max_strike_damage,
+            }),
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                stat: PokemonBoostableStat::Speed,
+                amount: -1,
+            }),
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotB,
+                stat: PokemonBoostableStat::Speed,
+                amount: -1,
+            }),
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::DYNAMAX,
+                amount: -1,
+            }),
+            Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+            }),
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+                amount: 1,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_z_move_hits_through_protect_for_a_quarter_of_its_damage() {
+    let mut state = State::default();
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::THUNDERBOLT,
+            move_choice: MoveChoice::MoveZ(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::PROTECT,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ApplyVolatileStatus(ApplyVolatileStatusInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+            }),
+            Instruction::ToggleZMoveUsed(ToggleZMoveUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
+            Instruction::Damage(DamageInstruction {
+                side_ref: SideReference::SideTwo,
+                pokemon_index: PokemonIndex::P0,
+                damage_amount: 19,
+            }),
+            Instruction::RemoveVolatileStatus(RemoveVolatileStatusInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+            }),
+            Instruction::ChangeVolatileStatusDuration(ChangeVolatileStatusDurationInstruction {
+                side_ref: SideReference::SideTwo,
+                slot_ref: SlotReference::SlotA,
+                volatile_status: PokemonVolatileStatus::PROTECT,
+                amount: 1,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_z_splash_sharply_raises_attack() {
+    let mut state = State::default();
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::SPLASH,
+            move_choice: MoveChoice::MoveZ(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ToggleZMoveUsed(ToggleZMoveUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                stat: PokemonBoostableStat::Attack,
+                amount: 3,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_z_belly_drum_heals_before_cutting_hp() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].hp = 60;
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::BELLYDRUM,
+            move_choice: MoveChoice::MoveZ(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    let expected_instructions = vec![StateInstructions {
+        end_of_turn_triggered: true,
+        percentage: 100.0,
+        instruction_list: vec![
+            Instruction::ToggleZMoveUsed(ToggleZMoveUsedInstruction {
+                side_ref: SideReference::SideOne,
+            }),
+            Instruction::Heal(HealInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                heal_amount: 40,
+            }),
+            Instruction::Damage(DamageInstruction {
+                side_ref: SideReference::SideOne,
+                pokemon_index: PokemonIndex::P0,
+                damage_amount: 50,
+            }),
+            Instruction::Boost(BoostInstruction {
+                side_ref: SideReference::SideOne,
+                slot_ref: SlotReference::SlotA,
+                stat: PokemonBoostableStat::Attack,
+                amount: 6,
+            }),
+        ],
+    }];
+    assert_eq!(expected_instructions, vec_of_instructions);
+}
+
+#[test]
+fn test_z_power_is_not_spent_when_the_user_flinches() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].speed = 50;
+    state.sides[1].slot_a.last_used_move = LastUsedMove::Switch(PokemonIndex::P0);
+    let vec_of_instructions = set_moves_on_pkmn_and_call_generate_instructions(
+        &mut state,
+        TestMoveChoice {
+            choice: Choices::THUNDERBOLT,
+            move_choice: MoveChoice::MoveZ(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+        TestMoveChoice {
+            choice: Choices::FAKEOUT,
+            move_choice: MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+        },
+        TestMoveChoice {
+            choice: Choices::NONE,
+            move_choice: MoveChoice::None,
+        },
+    );
+
+    assert!(!vec_of_instructions[0]
+        .instruction_list
+        .iter()
+        .any(|i| matches!(i, Instruction::ToggleZMoveUsed(_))));
+}
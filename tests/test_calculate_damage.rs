@@ -22,7 +22,13 @@ fn test_basic_damage_calculation() {
         &Choice::default(),
     );
 
-    assert_eq!(damage_rolls, Some(vec![28, 42]));
+    // tackle has 50 base power and crits do double damage in gen5
+    #[cfg(feature = "gen5")]
+    let expected_rolls = vec![36, 72];
+    #[cfg(not(feature = "gen5"))]
+    let expected_rolls = vec![28, 42];
+
+    assert_eq!(damage_rolls, Some(expected_rolls));
 }
 
 #[test]
@@ -80,7 +86,13 @@ fn test_spread_move_damage_reduction() {
         &Choice::default(),
     );
 
-    assert_eq!(damage_rolls, Some(vec![21, 31]));
+    // crits do double damage in gen5
+    #[cfg(feature = "gen5")]
+    let expected_rolls = vec![21, 42];
+    #[cfg(not(feature = "gen5"))]
+    let expected_rolls = vec![21, 31];
+
+    assert_eq!(damage_rolls, Some(expected_rolls));
 }
 
 #[test]
@@ -100,7 +112,13 @@ fn test_spread_move_no_damage_reduction_if_single_target() {
         &Choice::default(),
     );
 
-    assert_eq!(damage_rolls, Some(vec![28, 42]));
+    // crits do double damage in gen5
+    #[cfg(feature = "gen5")]
+    let expected_rolls = vec![28, 56];
+    #[cfg(not(feature = "gen5"))]
+    let expected_rolls = vec![28, 42];
+
+    assert_eq!(damage_rolls, Some(expected_rolls));
 }
 
 #[test]
@@ -135,5 +153,11 @@ fn test_ivycudgel_changing_type() {
         &Choice::default(),
     );
 
-    assert_eq!(damage_rolls, Some(vec![68, 102]));
+    // crits do double damage in gen5
+    #[cfg(feature = "gen5")]
+    let expected_rolls = vec![68, 137];
+    #[cfg(not(feature = "gen5"))]
+    let expected_rolls = vec![68, 102];
+
+    assert_eq!(damage_rolls, Some(expected_rolls));
 }
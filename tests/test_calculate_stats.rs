@@ -34,3 +34,39 @@ fn test_calculate_stats_with_boosting_nature() {
     assert_eq!(special_defense, 151);
     assert_eq!(speed, 111);
 }
+
+#[test]
+fn test_calculate_stats_with_zero_ivs() {
+    let pokemon = Pokemon {
+        nature: PokemonNature::BRAVE, // boosts atk and lowers spe
+        evs: (11, 0, 11, 11, 11, 0),
+        ivs: (31, 0, 31, 31, 31, 0),
+        id: PokemonName::VENUSAURMEGA,
+        ..Default::default()
+    };
+    let (hp, attack, defense, special_attack, special_defense, speed) =
+        pokemon.calculate_stats_from_base_stats();
+
+    assert_eq!(hp, 166);
+    assert_eq!(attack, 115);
+    assert_eq!(defense, 154);
+    assert_eq!(special_attack, 153);
+    assert_eq!(special_defense, 151);
+    assert_eq!(speed, 76); // minimum speed for trick room
+}
+
+#[test]
+fn test_calculate_stats_at_level_100() {
+    let pokemon = Pokemon {
+        level: 100,
+        nature: PokemonNature::SERIOUS,
+        evs: (11, 11, 11, 11, 11, 11),
+        id: PokemonName::VENUSAURMEGA,
+        ..Default::default()
+    };
+    let (hp, attack, _, _, _, speed) = pokemon.calculate_stats_from_base_stats();
+
+    assert_eq!(hp, 323);
+    assert_eq!(attack, 258);
+    assert_eq!(speed, 218);
+}
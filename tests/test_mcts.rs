@@ -0,0 +1,1588 @@
+use poke_engine::choices::{Choices, MOVES};
+use poke_engine::engine::abilities::Abilities;
+use poke_engine::engine::generate_instructions::generate_instructions_from_move_pair;
+use poke_engine::engine::items::Items;
+use poke_engine::engine::state::MoveChoice;
+use poke_engine::instruction::{DamageInstruction, Instruction, StateInstructions};
+use poke_engine::mcts::{
+    perform_mcts, search_duration, MctsConfig, MctsResult, MctsSideResult, MctsTree, RolloutPolicy,
+    SelectionPolicy,
+};
+use poke_engine::mcts_export::ExportedNode;
+use poke_engine::mcts_information_set::{perform_mcts_information_set, OpponentSet};
+use poke_engine::mcts_root_parallel::{perform_mcts_root_parallel, root_parallel_configs};
+use poke_engine::mcts_team_preview::{perform_mcts_team_preview, team_preview_matchup};
+use poke_engine::mcts_threaded::{perform_mcts_shared_tree, SharedMctsTree};
+use poke_engine::state::{
+    Move, PokemonIndex, PokemonMoveIndex, PokemonNature, PokemonType, SideReference, SlotReference,
+    State,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_perform_mcts_stops_at_max_iterations() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(2_500),
+        ..Default::default()
+    };
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        Duration::from_secs(60),
+        &config,
+    );
+
+    assert_eq!(2_500, result.iteration_count);
+}
+
+#[test]
+fn test_perform_mcts_shared_tree_stops_at_max_iterations() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(2_500),
+        ..Default::default()
+    };
+
+    let result = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        Duration::from_secs(60),
+        2,
+        &config,
+    );
+
+    assert_eq!(2_500, result.iteration_count);
+}
+
+#[test]
+fn test_perform_mcts_leaves_state_unchanged() {
+    let mut state = State::default();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(1_000),
+        max_depth: 2,
+        exploration_constant: 1.0,
+        ..Default::default()
+    };
+
+    perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        Duration::from_secs(60),
+        &config,
+    );
+
+    assert_eq!(serialized_before, state.serialize());
+}
+
+// one move per active pkmn and no switches, so every pair of root options gets explored
+fn small_state() -> State {
+    let mut state = State::default();
+    for side in state.sides.iter_mut() {
+        for pkmn in side.pokemon.pkmn[2..].iter_mut() {
+            pkmn.hp = 0;
+        }
+        for pkmn in side.pokemon.pkmn[..2].iter_mut() {
+            pkmn.moves.m1.disabled = true;
+            pkmn.moves.m2.disabled = true;
+            pkmn.moves.m3.disabled = true;
+        }
+    }
+    state
+}
+
+fn most_visited(result: &[MctsSideResult]) -> (MoveChoice, MoveChoice) {
+    result.iter().max_by_key(|x| x.visits).unwrap().move_choice
+}
+
+#[test]
+fn test_perform_mcts_and_shared_tree_search_to_the_same_turn_horizon() {
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        max_depth: 2,
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let single = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &config,
+    );
+    let shared = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        2,
+        &config,
+    );
+
+    for result in [single, shared].iter() {
+        assert_eq!(2, result.max_leaf_depth);
+        assert!(result.average_leaf_depth > 0.0);
+        assert!(result.average_leaf_depth <= 2.0);
+    }
+}
+
+#[test]
+fn test_default_turn_horizon_is_shared_by_every_search() {
+    let config = MctsConfig {
+        max_iterations: Some(10_000),
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let single = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &config,
+    );
+    let shared = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        2,
+        &config,
+    );
+
+    assert!(single.max_leaf_depth <= config.max_depth);
+    assert!(shared.max_leaf_depth <= config.max_depth);
+}
+
+#[test]
+fn test_mcts_tree_advance_keeps_the_explored_subtree() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        ..Default::default()
+    };
+    let mut tree = MctsTree::new(s1_options, s2_options);
+    let result = tree.search(&mut state, Duration::from_secs(60), &config);
+
+    let s1_move = most_visited(&result.s1);
+    let s2_move = most_visited(&result.s2);
+    let instructions = generate_instructions_from_move_pair(
+        &mut state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, true,
+    )
+    .remove(0);
+    state.apply_instructions(&instructions.instruction_list);
+    let serialized_before = state.serialize();
+
+    assert!(tree.advance(&state, &s1_move, &s2_move, &instructions));
+    let no_iterations = MctsConfig {
+        max_iterations: Some(0),
+        ..Default::default()
+    };
+    let kept_visits = tree
+        .search(&mut state, Duration::from_secs(60), &no_iterations)
+        .iteration_count;
+    assert!(kept_visits > 0);
+
+    // `max_iterations` counts the iterations of this search, not the visits kept by `advance`
+    let config = MctsConfig {
+        max_iterations: Some(6_000),
+        ..Default::default()
+    };
+    let result = tree.search(&mut state, Duration::from_secs(60), &config);
+
+    assert_eq!(kept_visits + 6_000, result.iteration_count);
+    assert_eq!(serialized_before, state.serialize());
+}
+
+#[test]
+fn test_mcts_tree_advance_starts_fresh_for_an_unexplored_outcome() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(1_000),
+        ..Default::default()
+    };
+    let mut tree = MctsTree::new(s1_options.clone(), s2_options.clone());
+    tree.search(&mut state, Duration::from_secs(60), &config);
+
+    // no outcome in the tree deals this damage
+    let instructions = StateInstructions {
+        instruction_list: vec![Instruction::Damage(DamageInstruction {
+            side_ref: SideReference::SideOne,
+            pokemon_index: PokemonIndex::P0,
+            damage_amount: 1,
+        })],
+        ..Default::default()
+    };
+    assert!(!tree.advance(&state, &s1_options[0], &s2_options[0], &instructions));
+
+    let result = tree.search(&mut state, Duration::from_secs(60), &config);
+    assert_eq!(1_000, result.iteration_count);
+}
+
+#[test]
+fn test_shared_mcts_tree_advance_keeps_the_explored_subtree() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        ..Default::default()
+    };
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+    tree.search(&mut state, Duration::from_secs(60), 2, &config);
+
+    // each side's most visited option may never have been played together when the workers
+    // interleave, so advance along the most visited outcome that was actually explored
+    let explored = tree
+        .export(1, 1)
+        .children
+        .into_iter()
+        .max_by_key(|child| child.visits)
+        .unwrap();
+    let (s1_move, s2_move) = explored.move_pair.unwrap();
+    let instructions = explored.instructions;
+    state.apply_instructions(&instructions.instruction_list);
+    let serialized_before = state.serialize();
+
+    assert!(tree.advance(&state, &s1_move, &s2_move, &instructions));
+    let no_iterations = MctsConfig {
+        max_iterations: Some(0),
+        ..Default::default()
+    };
+    let kept_visits = tree
+        .search(&mut state, Duration::from_secs(60), 2, &no_iterations)
+        .iteration_count;
+    assert!(kept_visits > 0);
+
+    // `max_iterations` counts the iterations of this search, not the visits kept by `advance`
+    let config = MctsConfig {
+        max_iterations: Some(6_000),
+        ..Default::default()
+    };
+    let result = tree.search(&mut state, Duration::from_secs(60), 2, &config);
+
+    assert_eq!(kept_visits + 6_000, result.iteration_count);
+    assert_eq!(serialized_before, state.serialize());
+}
+
+// each side has a single option, so the most visited option is decided immediately
+fn single_option_state() -> State {
+    let mut state = State::default();
+    for side in state.sides.iter_mut() {
+        for pkmn in side.pokemon.pkmn[1..].iter_mut() {
+            pkmn.hp = 0;
+        }
+        side.pokemon.pkmn[0].moves.m1.disabled = true;
+        side.pokemon.pkmn[0].moves.m2.disabled = true;
+        side.pokemon.pkmn[0].moves.m3.disabled = true;
+
+        // no tera/mega/dynamax/z-move options regardless of which features are enabled
+        side.pokemon.pkmn[0].terastallized = true;
+        side.mega_used = true;
+        side.dynamax_used = true;
+        side.z_move_used = true;
+    }
+    state
+}
+
+#[test]
+fn test_mcts_tree_stops_at_max_nodes() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_nodes: Some(500),
+        ..Default::default()
+    };
+    let mut tree = MctsTree::new(s1_options, s2_options);
+
+    // no time limit, so only the node budget can stop the search
+    tree.search(&mut state, search_duration(0), &config);
+
+    assert!(tree.node_count() >= 500);
+    assert!(tree.node_count() < 1_000);
+}
+
+#[test]
+fn test_shared_mcts_tree_stops_at_max_nodes() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_nodes: Some(500),
+        ..Default::default()
+    };
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+
+    tree.search(&mut state, search_duration(0), 2, &config);
+
+    assert!(tree.node_count() >= 500);
+    assert!(tree.node_count() < 1_000);
+}
+
+#[test]
+fn test_mcts_tree_early_stop_when_the_best_option_is_decided() {
+    let mut state = single_option_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    // the iteration budget is only a backstop so a regression fails instead of hanging
+    let config = MctsConfig {
+        early_stop: true,
+        max_iterations: Some(100_000),
+        ..Default::default()
+    };
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+
+    assert!(result.iteration_count <= 1_000);
+}
+
+#[test]
+fn test_shared_mcts_tree_early_stop_when_the_best_option_is_decided() {
+    let mut state = single_option_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    // the iteration budget is only a backstop so a regression fails instead of hanging
+    let config = MctsConfig {
+        early_stop: true,
+        max_iterations: Some(100_000),
+        ..Default::default()
+    };
+
+    let result = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        2,
+        &config,
+    );
+
+    assert!(result.iteration_count <= 2_000);
+}
+
+// moves that can miss, crit or trigger secondaries, so outcomes are sampled at random
+fn random_outcome_state() -> State {
+    let mut state = State::default();
+    for side in state.sides.iter_mut() {
+        for pkmn in side.pokemon.pkmn[..2].iter_mut() {
+            pkmn.moves.m0 = Move {
+                id: Choices::HYDROPUMP,
+                disabled: false,
+                pp: 8,
+                choice: MOVES.get(&Choices::HYDROPUMP).unwrap().clone(),
+            };
+            pkmn.moves.m1 = Move {
+                id: Choices::ROCKSLIDE,
+                disabled: false,
+                pp: 16,
+                choice: MOVES.get(&Choices::ROCKSLIDE).unwrap().clone(),
+            };
+        }
+    }
+    state
+}
+
+fn search_summary(result: &MctsResult) -> Vec<(u64, f32)> {
+    result
+        .s1
+        .iter()
+        .chain(result.s2.iter())
+        .map(|x| (x.visits, x.total_score))
+        .collect()
+}
+
+#[test]
+fn test_seeded_perform_mcts_is_reproducible() {
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let run = || {
+        let mut state = random_outcome_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        perform_mcts(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            &config,
+        )
+    };
+
+    assert_eq!(search_summary(&run()), search_summary(&run()));
+}
+
+#[test]
+fn test_seeded_perform_mcts_shared_tree_with_one_worker_is_reproducible() {
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let run = || {
+        let mut state = random_outcome_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            1,
+            &config,
+        )
+    };
+
+    assert_eq!(search_summary(&run()), search_summary(&run()));
+}
+
+fn assert_is_a_distribution(result: &[MctsSideResult]) {
+    let total: f32 = result.iter().map(|x| x.nash_probability.unwrap()).sum();
+    assert!((total - 1.0).abs() < 1e-3, "{}", total);
+    for x in result.iter().filter(|x| x.visits == 0) {
+        assert_eq!(Some(0.0), x.nash_probability);
+    }
+}
+
+#[test]
+fn test_perform_mcts_solves_the_root_for_a_nash_strategy() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        nash_iterations: 500,
+        ..Default::default()
+    };
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+
+    assert_is_a_distribution(&result.s1);
+    assert_is_a_distribution(&result.s2);
+}
+
+#[test]
+fn test_perform_mcts_shared_tree_solves_the_root_for_a_nash_strategy() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        nash_iterations: 500,
+        ..Default::default()
+    };
+
+    let result = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        2,
+        &config,
+    );
+
+    assert_is_a_distribution(&result.s1);
+    assert_is_a_distribution(&result.s2);
+}
+
+#[test]
+fn test_perform_mcts_root_parallel_merges_every_tree() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        ..Default::default()
+    };
+
+    let result = perform_mcts_root_parallel(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &root_parallel_configs(&config, 3),
+    );
+
+    assert_eq!(6_000, result.iteration_count);
+    assert_eq!(6_000, total_visits(&result.s1));
+    assert_eq!(6_000, total_visits(&result.s2));
+    assert_eq!(
+        s1_options,
+        result.s1.iter().map(|x| x.move_choice).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        s2_options,
+        result.s2.iter().map(|x| x.move_choice).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_root_parallel_configs_seed_every_tree_differently() {
+    let config = MctsConfig {
+        seed: Some(42),
+        ..Default::default()
+    };
+
+    let seeds: Vec<Option<u64>> = root_parallel_configs(&config, 3)
+        .iter()
+        .map(|x| x.seed)
+        .collect();
+
+    assert_eq!(vec![Some(42), Some(43), Some(44)], seeds);
+}
+
+#[test]
+fn test_seeded_perform_mcts_root_parallel_is_reproducible() {
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let run = || {
+        let mut state = random_outcome_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        perform_mcts_root_parallel(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            &root_parallel_configs(&config, 3),
+        )
+    };
+
+    assert_eq!(search_summary(&run()), search_summary(&run()));
+}
+
+#[test]
+fn test_perform_mcts_root_parallel_solves_the_merged_root_for_a_nash_strategy() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        nash_iterations: 500,
+        ..Default::default()
+    };
+
+    let result = perform_mcts_root_parallel(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &root_parallel_configs(&config, 2),
+    );
+
+    assert_is_a_distribution(&result.s1);
+    assert_is_a_distribution(&result.s2);
+}
+
+#[test]
+fn test_perform_mcts_team_preview_reports_brings_and_leads() {
+    let mut state = State {
+        team_preview: true,
+        ..Default::default()
+    };
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        ..Default::default()
+    };
+
+    let result = perform_mcts_team_preview(&mut state, search_duration(0), 6, 1, &config);
+
+    assert_eq!(2_000, result.search.iteration_count);
+    for side in [&result.s1, &result.s2].iter() {
+        assert_eq!(6, side.teams.len());
+        let total: f32 = side.teams.iter().map(|x| x.probability).sum();
+        assert!((total - 1.0).abs() < 1e-3, "{}", total);
+        assert!((side.brings.iter().sum::<f32>() - 4.0).abs() < 1e-3);
+        assert!((side.leads.iter().sum::<f32>() - 2.0).abs() < 1e-3);
+        assert!(side
+            .teams
+            .windows(2)
+            .all(|x| x[0].probability >= x[1].probability));
+    }
+}
+
+#[test]
+fn test_team_preview_matchup_prefers_super_effective_pokemon() {
+    let mut state = State::default();
+    for pkmn in state.sides[1].pokemon.pkmn.iter_mut() {
+        pkmn.types = (PokemonType::WATER, PokemonType::TYPELESS);
+    }
+    state.sides[0].pokemon.pkmn[4].types = (PokemonType::GRASS, PokemonType::TYPELESS);
+    state.sides[0].pokemon.pkmn[4].moves.m0 = Move {
+        id: Choices::ENERGYBALL,
+        disabled: false,
+        pp: 16,
+        choice: MOVES.get(&Choices::ENERGYBALL).unwrap().clone(),
+    };
+    let team = [
+        PokemonIndex::P0,
+        PokemonIndex::P1,
+        PokemonIndex::P2,
+        PokemonIndex::P3,
+    ];
+    let team_with_grass_lead = [
+        PokemonIndex::P4,
+        PokemonIndex::P1,
+        PokemonIndex::P2,
+        PokemonIndex::P3,
+    ];
+
+    assert!(
+        team_preview_matchup(&state, SideReference::SideOne, &team_with_grass_lead)
+            > team_preview_matchup(&state, SideReference::SideOne, &team)
+    );
+}
+
+#[test]
+fn test_nash_probability_is_empty_by_default() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(1_000),
+        ..Default::default()
+    };
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+
+    assert!(result
+        .s1
+        .iter()
+        .chain(result.s2.iter())
+        .all(|x| x.nash_probability.is_none()));
+}
+
+fn total_visits(result: &[MctsSideResult]) -> u64 {
+    result.iter().map(|x| x.visits).sum()
+}
+
+// side one's actives pick between hydro pump and splash while side two's have no moves worth using
+fn splash_or_hydro_pump_state() -> State {
+    let mut state = small_state();
+    let side = &mut state.sides[0];
+    for pkmn in side.pokemon.pkmn[..2].iter_mut() {
+        pkmn.replace_move(PokemonMoveIndex::M0, Choices::HYDROPUMP);
+        pkmn.replace_move(PokemonMoveIndex::M1, Choices::SPLASH);
+        pkmn.moves.m1.disabled = false;
+        pkmn.terastallized = true;
+    }
+    side.mega_used = true;
+    side.dynamax_used = true;
+    side.z_move_used = true;
+    state
+}
+
+// side one's most visited option uses hydro pump on the opponents in both slots
+fn assert_prefers_hydro_pump(result: &[MctsSideResult]) {
+    match most_visited(result) {
+        (
+            MoveChoice::Move(_, SideReference::SideTwo, PokemonMoveIndex::M0),
+            MoveChoice::Move(_, SideReference::SideTwo, PokemonMoveIndex::M0),
+        ) => {}
+        option => panic!("expected hydro pump in both slots, got {:?}", option),
+    }
+}
+
+// side one's best scoring option uses hydro pump on the opponents in both slots. sampling
+// spreads its visits too widely for the most visited option to be reliably the best one
+fn assert_rates_hydro_pump_highest(result: &[MctsSideResult]) {
+    let average_score = |x: &MctsSideResult| x.total_score / x.visits as f32;
+    match result
+        .iter()
+        .max_by(|a, b| average_score(a).partial_cmp(&average_score(b)).unwrap())
+        .unwrap()
+        .move_choice
+    {
+        (
+            MoveChoice::Move(_, SideReference::SideTwo, PokemonMoveIndex::M0),
+            MoveChoice::Move(_, SideReference::SideTwo, PokemonMoveIndex::M0),
+        ) => {}
+        option => panic!("expected hydro pump in both slots, got {:?}", option),
+    }
+}
+
+#[test]
+fn test_perform_mcts_with_sampled_selection_policies() {
+    for selection_policy in [SelectionPolicy::EXP3, SelectionPolicy::REGRETMATCHING]
+        .iter()
+        .copied()
+    {
+        let mut state = splash_or_hydro_pump_state();
+        let serialized_before = state.serialize();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        let config = MctsConfig {
+            max_iterations: Some(5_000),
+            selection_policy,
+            seed: Some(42),
+            ..Default::default()
+        };
+
+        let result = perform_mcts(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            &config,
+        );
+
+        assert_eq!(5_000, result.iteration_count);
+        assert_eq!(5_000, total_visits(&result.s1));
+        assert_eq!(5_000, total_visits(&result.s2));
+        assert_eq!(serialized_before, state.serialize());
+        // sampling keeps trying every option while rating the strongest one highest
+        assert_eq!(result.s1.len(), visited_options(&result.s1));
+        assert_rates_hydro_pump_highest(&result.s1);
+    }
+}
+
+#[test]
+fn test_perform_mcts_shared_tree_with_sampled_selection_policies() {
+    for selection_policy in [SelectionPolicy::EXP3, SelectionPolicy::REGRETMATCHING]
+        .iter()
+        .copied()
+    {
+        let mut state = splash_or_hydro_pump_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        // a single seeded worker, so the result does not depend on how workers interleave
+        let config = MctsConfig {
+            max_iterations: Some(5_000),
+            selection_policy,
+            seed: Some(42),
+            ..Default::default()
+        };
+
+        let result = perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            1,
+            &config,
+        );
+
+        assert_eq!(5_000, result.iteration_count);
+        assert_eq!(5_000, total_visits(&result.s1));
+        assert_eq!(5_000, total_visits(&result.s2));
+        assert_eq!(result.s1.len(), visited_options(&result.s1));
+        assert_rates_hydro_pump_highest(&result.s1);
+    }
+}
+
+#[test]
+fn test_factorized_selection_in_both_searches() {
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        selection_policy: SelectionPolicy::FACTORIZEDUCB1,
+        ..Default::default()
+    };
+
+    let mut state = splash_or_hydro_pump_state();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let result = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &config,
+    );
+    assert_eq!(5_000, total_visits(&result.s1));
+    assert_eq!(5_000, total_visits(&result.s2));
+    assert_eq!(serialized_before, state.serialize());
+    assert_prefers_hydro_pump(&result.s1);
+
+    let result = perform_mcts_shared_tree(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        2,
+        &config,
+    );
+    assert_eq!(5_000, total_visits(&result.s1));
+    assert_eq!(5_000, total_visits(&result.s2));
+    assert_prefers_hydro_pump(&result.s1);
+}
+
+fn visited_options(result: &[MctsSideResult]) -> usize {
+    result.iter().filter(|x| x.visits > 0).count()
+}
+
+#[test]
+fn test_progressive_widening_only_opens_a_few_options_early_on() {
+    for selection_policy in [SelectionPolicy::UCB1, SelectionPolicy::FACTORIZEDUCB1]
+        .iter()
+        .copied()
+    {
+        let config = MctsConfig {
+            max_iterations: Some(200),
+            selection_policy,
+            progressive_widening: true,
+            ..Default::default()
+        };
+        // ceil(2 * sqrt(200)) options are open by the last iteration
+        let open_options = 29;
+
+        let mut state = random_outcome_state();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        assert!(s1_options.len() > open_options);
+        assert!(s2_options.len() > open_options);
+        let result = perform_mcts(
+            &mut state,
+            s1_options.clone(),
+            s2_options.clone(),
+            search_duration(0),
+            &config,
+        );
+        assert!(visited_options(&result.s1) <= open_options);
+        assert!(visited_options(&result.s2) <= open_options);
+        assert_eq!(200, total_visits(&result.s1));
+
+        let result = perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            2,
+            &config,
+        );
+        assert!(visited_options(&result.s1) <= open_options);
+        assert!(visited_options(&result.s2) <= open_options);
+        assert_eq!(200, total_visits(&result.s1));
+    }
+}
+
+#[test]
+fn test_rollout_policies_in_both_searches() {
+    for rollout_policy in [RolloutPolicy::RANDOM, RolloutPolicy::GREEDY]
+        .iter()
+        .copied()
+    {
+        let config = MctsConfig {
+            max_iterations: Some(1_000),
+            rollout_policy,
+            ..Default::default()
+        };
+
+        let mut state = random_outcome_state();
+        let serialized_before = state.serialize();
+        let (s1_options, s2_options) = state.root_get_all_options();
+        let result = perform_mcts(
+            &mut state,
+            s1_options.clone(),
+            s2_options.clone(),
+            search_duration(0),
+            &config,
+        );
+        assert_eq!(1_000, total_visits(&result.s1));
+        assert_eq!(serialized_before, state.serialize());
+
+        let result = perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            2,
+            &config,
+        );
+        assert_eq!(1_000, total_visits(&result.s1));
+        assert_eq!(serialized_before, state.serialize());
+    }
+}
+
+#[test]
+fn test_callback_rollout_policy_picks_every_rollout_option() {
+    // the number of times the callback picked an option for each side
+    let calls = Arc::new([AtomicU32::new(0), AtomicU32::new(0)]);
+    let callback_calls = Arc::clone(&calls);
+    let config = MctsConfig {
+        max_iterations: Some(1_000),
+        rollout_policy: RolloutPolicy::CALLBACK,
+        rollout_turns: 2,
+        rollout_callback: Some(Arc::new(move |_, side_ref, options| {
+            callback_calls[side_ref as usize].fetch_add(1, Ordering::Relaxed);
+            options.len() - 1
+        })),
+        ..Default::default()
+    };
+    let take_calls = || {
+        let side_one = calls[0].swap(0, Ordering::Relaxed);
+        let side_two = calls[1].swap(0, Ordering::Relaxed);
+        assert_eq!(side_one, side_two);
+        side_one
+    };
+
+    let mut state = random_outcome_state();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let result = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &config,
+    );
+    assert_eq!(1_000, total_visits(&result.s1));
+    assert_eq!(serialized_before, state.serialize());
+    // at most `rollout_turns` turns are played out from each leaf, fewer if the battle ends
+    let single_calls = take_calls();
+    assert!(single_calls > 1_000);
+    assert!(single_calls <= 2_000);
+
+    let result = perform_mcts_shared_tree(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        2,
+        &config,
+    );
+    assert_eq!(1_000, total_visits(&result.s1));
+    let shared_calls = take_calls();
+    assert!(shared_calls > 1_000);
+    assert!(shared_calls <= 2_000);
+
+    let config = MctsConfig {
+        rollout_turns: 0,
+        ..config
+    };
+    perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+    assert_eq!(0, take_calls());
+}
+
+// the root's explored outcomes that reach the same state at the same depth share one node's
+// statistics, and the search leaves the state and its hash as they were
+fn assert_transposed_outcomes_share_statistics(state: &mut State, root: &ExportedNode) {
+    let serialized_before = state.serialize();
+    let mut outcomes_by_state: HashMap<(u64, bool), Vec<&ExportedNode>> = HashMap::new();
+    for outcome in root.children.iter() {
+        state.apply_instructions(&outcome.instructions.instruction_list);
+        outcomes_by_state
+            .entry((state.hash, outcome.instructions.end_of_turn_triggered))
+            .or_default()
+            .push(outcome);
+        state.reverse_instructions(&outcome.instructions.instruction_list);
+    }
+    let transposed: Vec<&Vec<&ExportedNode>> = outcomes_by_state
+        .values()
+        .filter(|outcomes| outcomes.len() > 1)
+        .collect();
+
+    // both sides missing both hydro pumps reaches the same state whichever pokemon they targeted
+    assert!(!transposed.is_empty());
+    for outcomes in transposed {
+        for outcome in outcomes.iter() {
+            assert_eq!(outcomes[0].visits, outcome.visits);
+            assert_eq!(outcomes[0].average_score, outcome.average_score);
+        }
+    }
+    assert_eq!(serialized_before, state.serialize());
+    assert_eq!(state.zobrist_hash(), state.hash);
+}
+
+#[test]
+fn test_transposition_table_in_both_searches() {
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        transposition_table: true,
+        ..Default::default()
+    };
+    let mut state = random_outcome_state();
+    state.track_hash = true;
+    state.rehash();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let mut tree = MctsTree::new(s1_options.clone(), s2_options.clone());
+    tree.search(&mut state, search_duration(0), &config);
+    assert!(tree.transposition_count() > 0);
+    assert!(tree.transposition_count() < tree.node_count());
+    assert_transposed_outcomes_share_statistics(&mut state, &tree.export(1, 1));
+
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+    tree.search(&mut state, search_duration(0), 2, &config);
+    assert!(tree.transposition_count() > 0);
+    assert!(tree.transposition_count() < tree.node_count());
+    assert_transposed_outcomes_share_statistics(&mut state, &tree.export(1, 1));
+}
+
+#[test]
+fn test_transposition_table_is_empty_by_default() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(500),
+        ..Default::default()
+    };
+
+    let mut tree = MctsTree::new(s1_options.clone(), s2_options.clone());
+    tree.search(&mut state, search_duration(0), &config);
+    assert_eq!(0, tree.transposition_count());
+
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+    tree.search(&mut state, search_duration(0), 2, &config);
+    assert_eq!(0, tree.transposition_count());
+}
+
+fn opponent_sets() -> [Vec<OpponentSet>; 6] {
+    let set = |weight: f32, item: Items, moves: [Choices; 4]| OpponentSet {
+        weight,
+        item,
+        ability: Abilities::NONE,
+        nature: PokemonNature::SERIOUS,
+        evs: (11, 11, 11, 11, 11, 11),
+        tera_type: PokemonType::NORMAL,
+        moves,
+    };
+    let candidates = vec![
+        set(
+            3.0,
+            Items::LEFTOVERS,
+            [
+                Choices::HYDROPUMP,
+                Choices::PROTECT,
+                Choices::NONE,
+                Choices::NONE,
+            ],
+        ),
+        set(
+            1.0,
+            Items::CHOICESCARF,
+            [
+                Choices::HYDROPUMP,
+                Choices::ROCKSLIDE,
+                Choices::NONE,
+                Choices::NONE,
+            ],
+        ),
+    ];
+    [
+        candidates.clone(),
+        candidates,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    ]
+}
+
+#[test]
+fn test_perform_mcts_information_set() {
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let sets = opponent_sets();
+    let run = || {
+        let mut state = random_outcome_state();
+        let serialized_before = state.serialize();
+        let (s1_options, _) = state.root_get_all_options();
+        let result = perform_mcts_information_set(
+            &mut state,
+            s1_options,
+            &sets,
+            search_duration(0),
+            &config,
+        );
+        assert_eq!(serialized_before, state.serialize());
+        result
+    };
+
+    let result = run();
+    assert_eq!(2_000, result.iteration_count);
+    assert_eq!(2_000, total_visits(&result.s1));
+    assert_eq!(2_000, total_visits(&result.s2));
+    assert_eq!(search_summary(&result), search_summary(&run()));
+}
+
+fn visits_of(result: &[MctsSideResult], move_choice: (MoveChoice, MoveChoice)) -> u64 {
+    result
+        .iter()
+        .find(|x| x.move_choice == move_choice)
+        .unwrap()
+        .visits
+}
+
+#[test]
+fn test_principal_variation_in_both_searches() {
+    // seeded, with a single shared tree worker, so the most visited options of both sides are
+    // always ones the search played against each other
+    let config = MctsConfig {
+        max_iterations: Some(2_000),
+        principal_variation_plies: 3,
+        seed: Some(42),
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let results = [
+        perform_mcts(
+            &mut state,
+            s1_options.clone(),
+            s2_options.clone(),
+            search_duration(0),
+            &config,
+        ),
+        perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            search_duration(0),
+            1,
+            &config,
+        ),
+    ];
+    for result in results.iter() {
+        let line = &result.principal_variation;
+        assert!(!line.is_empty() && line.len() <= 3);
+        assert_eq!(
+            visits_of(&result.s1, most_visited(&result.s1)),
+            visits_of(&result.s1, line[0].s1_move)
+        );
+        assert_eq!(
+            visits_of(&result.s2, most_visited(&result.s2)),
+            visits_of(&result.s2, line[0].s2_move)
+        );
+        assert!(line.windows(2).all(|x| x[0].visits >= x[1].visits));
+        assert!(line.iter().all(|x| (0.0..=1.0).contains(&x.average_score)));
+    }
+}
+
+#[test]
+fn test_principal_variation_is_empty_by_default() {
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(500),
+        ..Default::default()
+    };
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+
+    assert!(result.principal_variation.is_empty());
+}
+
+fn exported_node_count(node: &ExportedNode) -> usize {
+    1 + node.children.iter().map(exported_node_count).sum::<usize>()
+}
+
+fn assert_is_exported_top_of_tree(root: &ExportedNode, state: &State) {
+    assert_eq!(1_000, root.visits);
+    assert!(root.move_pair.is_none());
+    assert!(!root.children.is_empty());
+    assert!(root.children.iter().all(|x| x.visits >= 10));
+    assert!(root
+        .children
+        .iter()
+        .all(|x| x.move_pair.is_some() && x.children.iter().all(|y| y.children.is_empty())));
+
+    let json = root.to_json(state);
+    assert!(json.starts_with("{\"s1_move\":null,\"s2_move\":null,"));
+    assert_eq!(
+        exported_node_count(root),
+        json.matches("\"visits\"").count()
+    );
+
+    let dot = root.to_dot(state);
+    assert!(dot.starts_with("digraph mcts {\n"));
+    assert_eq!(exported_node_count(root) - 1, dot.matches("->").count());
+}
+
+#[test]
+fn test_export_the_top_of_both_trees() {
+    let config = MctsConfig {
+        max_iterations: Some(1_000),
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let mut tree = MctsTree::new(s1_options.clone(), s2_options.clone());
+    tree.search(&mut state, search_duration(0), &config);
+    assert_is_exported_top_of_tree(&tree.export(2, 10), &state);
+
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+    tree.search(&mut state, search_duration(0), 2, &config);
+    assert_is_exported_top_of_tree(&tree.export(2, 10), &state);
+
+    assert_eq!(serialized_before, state.serialize());
+}
+
+#[test]
+fn test_both_searches_solve_small_endgames() {
+    let config = MctsConfig {
+        endgame_pokemon: 2,
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let results = [
+        perform_mcts(
+            &mut state,
+            s1_options.clone(),
+            s2_options.clone(),
+            Duration::from_secs(60),
+            &config,
+        ),
+        perform_mcts_shared_tree(
+            &mut state,
+            s1_options,
+            s2_options,
+            Duration::from_secs(60),
+            2,
+            &config,
+        ),
+    ];
+    for result in results.iter() {
+        assert!(result.iteration_count > 1);
+        assert_is_a_distribution(&result.s1);
+        assert_is_a_distribution(&result.s2);
+    }
+    assert_eq!(search_summary(&results[0]), search_summary(&results[1]));
+    assert_eq!(serialized_before, state.serialize());
+}
+
+#[test]
+fn test_endgame_out_of_time_falls_back_to_searching() {
+    let config = MctsConfig {
+        endgame_pokemon: 2,
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options,
+        Duration::ZERO,
+        &config,
+    );
+
+    assert_eq!(s1_options.len(), result.s1.len());
+    assert!(result.s1.iter().all(|x| x.nash_probability.is_none()));
+}
+
+#[test]
+fn test_endgame_solver_is_only_used_below_the_threshold() {
+    let config = MctsConfig {
+        max_iterations: Some(500),
+        endgame_pokemon: 1,
+        ..Default::default()
+    };
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let result = perform_mcts(
+        &mut state,
+        s1_options,
+        s2_options,
+        search_duration(0),
+        &config,
+    );
+
+    assert_eq!(500, result.iteration_count);
+    assert!(result.s1.iter().all(|x| x.nash_probability.is_none()));
+}
+
+#[test]
+fn test_background_search_can_be_stopped() {
+    let state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+
+    let search = SharedMctsTree::new(s1_options, s2_options).search_in_background(
+        state,
+        search_duration(0),
+        2,
+        &MctsConfig::default(),
+        None,
+    );
+    std::thread::sleep(Duration::from_millis(50));
+    let snapshot = search.snapshot();
+    assert!(!search.is_finished());
+    let (tree, result) = search.stop();
+
+    assert!(snapshot.iteration_count > 0);
+    assert!(result.iteration_count >= snapshot.iteration_count);
+    assert_eq!(result.iteration_count, total_visits(&result.s1));
+    assert!(tree.node_count() > 1);
+}
+
+#[test]
+fn test_background_search_reports_progress() {
+    let state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        ..Default::default()
+    };
+    let progress_calls = Arc::new(AtomicU32::new(0));
+    let callback_calls = Arc::clone(&progress_calls);
+
+    let search = SharedMctsTree::new(s1_options, s2_options).search_in_background(
+        state,
+        search_duration(0),
+        2,
+        &config,
+        Some((
+            1_000,
+            Box::new(move |_: &MctsResult| {
+                callback_calls.fetch_add(1, Ordering::Relaxed);
+            }),
+        )),
+    );
+    let (_, result) = search.wait();
+
+    assert_eq!(5_000, result.iteration_count);
+    assert_eq!(4, progress_calls.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_mcts_tree_stops_growing_at_max_memory() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        max_memory: Some(100_000),
+        ..Default::default()
+    };
+    let mut tree = MctsTree::new(s1_options, s2_options);
+
+    let result = tree.search(&mut state, search_duration(0), &config);
+
+    // the search carries on once the tree is full
+    assert_eq!(5_000, result.iteration_count);
+    assert!(tree.memory_usage() >= 100_000);
+    assert!(tree.memory_usage() < 200_000);
+}
+
+#[test]
+fn test_shared_mcts_tree_stops_growing_at_max_memory() {
+    let mut state = State::default();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        max_memory: Some(100_000),
+        ..Default::default()
+    };
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+
+    let result = tree.search(&mut state, search_duration(0), 2, &config);
+
+    assert_eq!(5_000, result.iteration_count);
+    assert!(tree.memory_usage() >= 100_000);
+    assert!(tree.memory_usage() < 200_000);
+}
+
+#[test]
+fn test_mcts_tree_keeps_only_the_advanced_subtree() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        ..Default::default()
+    };
+    let mut tree = MctsTree::new(s1_options, s2_options);
+    let result = tree.search(&mut state, search_duration(0), &config);
+    let s1_move = most_visited(&result.s1);
+    let s2_move = most_visited(&result.s2);
+    let node_count = tree.node_count();
+    let memory_usage = tree.memory_usage();
+
+    let instructions = generate_instructions_from_move_pair(
+        &mut state, &s1_move.0, &s1_move.1, &s2_move.0, &s2_move.1, true,
+    )
+    .remove(0);
+    state.apply_instructions(&instructions.instruction_list);
+
+    assert!(tree.advance(&state, &s1_move, &s2_move, &instructions));
+    assert!(tree.node_count() < node_count);
+    assert!(tree.memory_usage() < memory_usage);
+    let result = tree.search(&mut state, search_duration(0), &config);
+    // the visits kept by `advance` plus this search's 5,000 iterations
+    assert!(result.iteration_count > 5_000);
+}
+
+#[test]
+fn test_shared_mcts_tree_keeps_only_the_advanced_subtree() {
+    let mut state = small_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        ..Default::default()
+    };
+    let mut tree = SharedMctsTree::new(s1_options, s2_options);
+    tree.search(&mut state, search_duration(0), 2, &config);
+    let node_count = tree.node_count();
+    let memory_usage = tree.memory_usage();
+
+    let explored = tree
+        .export(1, 1)
+        .children
+        .into_iter()
+        .max_by_key(|child| child.visits)
+        .unwrap();
+    let (s1_move, s2_move) = explored.move_pair.unwrap();
+    state.apply_instructions(&explored.instructions.instruction_list);
+
+    assert!(tree.advance(&state, &s1_move, &s2_move, &explored.instructions));
+    assert!(tree.node_count() < node_count);
+    assert!(tree.memory_usage() < memory_usage);
+
+    // the new root and its subtree keep their statistics
+    let kept = tree.export(u8::MAX, 1);
+    assert_eq!(explored.visits, kept.visits);
+    assert_eq!(explored.average_score, kept.average_score);
+    assert!(!kept.children.is_empty());
+    let result = tree.search(&mut state, search_duration(0), 2, &config);
+    assert_eq!(explored.visits + 5_000, result.iteration_count);
+}
+
+// side two almost always switches slot A out and hydro pumps side one's slot A with slot B
+fn switch_and_attack_prior(
+    _state: &State,
+    side_ref: SideReference,
+    slot_ref: SlotReference,
+) -> Vec<(MoveChoice, f32)> {
+    if side_ref == SideReference::SideOne {
+        return Vec::new();
+    }
+    match slot_ref {
+        SlotReference::SlotA => vec![(MoveChoice::Switch(PokemonIndex::P2), 0.95)],
+        SlotReference::SlotB => vec![(
+            MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M0,
+            ),
+            0.95,
+        )],
+    }
+}
+
+#[test]
+fn test_puct_searches_the_option_the_prior_callback_expects_more() {
+    let expected = (
+        MoveChoice::Switch(PokemonIndex::P2),
+        MoveChoice::Move(
+            SlotReference::SlotA,
+            SideReference::SideOne,
+            PokemonMoveIndex::M0,
+        ),
+    );
+    let expected_visits = |result: &MctsResult| {
+        result
+            .s2
+            .iter()
+            .find(|x| x.move_choice == expected)
+            .unwrap()
+            .visits
+    };
+    let uniform_config = MctsConfig {
+        max_iterations: Some(2_000),
+        selection_policy: SelectionPolicy::PUCT,
+        ..Default::default()
+    };
+    let config = MctsConfig {
+        prior_callback: Some(Arc::new(switch_and_attack_prior)),
+        ..uniform_config.clone()
+    };
+
+    let mut state = random_outcome_state();
+    let serialized_before = state.serialize();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let search = |state: &mut State, config: &MctsConfig| {
+        perform_mcts(
+            state,
+            s1_options.clone(),
+            s2_options.clone(),
+            search_duration(0),
+            config,
+        )
+    };
+    let uniform = search(&mut state, &uniform_config);
+    let result = search(&mut state, &config);
+    assert_eq!(2_000, total_visits(&result.s1));
+    assert!(expected_visits(&result) > 10 * expected_visits(&uniform));
+    assert_eq!(serialized_before, state.serialize());
+
+    let search = |state: &mut State, config: &MctsConfig| {
+        perform_mcts_shared_tree(
+            state,
+            s1_options.clone(),
+            s2_options.clone(),
+            search_duration(0),
+            2,
+            config,
+        )
+    };
+    let uniform = search(&mut state, &uniform_config);
+    let result = search(&mut state, &config);
+    assert_eq!(2_000, total_visits(&result.s1));
+    assert!(expected_visits(&result) > 10 * expected_visits(&uniform));
+}
+
+#[test]
+fn test_puct_without_a_prior_callback_visits_every_option() {
+    let config = MctsConfig {
+        max_iterations: Some(5_000),
+        selection_policy: SelectionPolicy::PUCT,
+        ..Default::default()
+    };
+
+    let mut state = random_outcome_state();
+    let (s1_options, s2_options) = state.root_get_all_options();
+    let result = perform_mcts(
+        &mut state,
+        s1_options.clone(),
+        s2_options.clone(),
+        search_duration(0),
+        &config,
+    );
+
+    assert_eq!(s1_options.len(), visited_options(&result.s1));
+    assert_eq!(s2_options.len(), visited_options(&result.s2));
+}
@@ -18,12 +18,14 @@ fn disable_all_moves(pokemon_moves: &mut PokemonMoves) {
 #[test]
 fn test_basic_move_generation() {
     let mut state = State::default();
-    // remove tera and only have 4 alive pkmn
+    // remove tera/dynamax and only have 4 alive pkmn
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -71,11 +73,13 @@ fn test_basic_move_generation() {
 fn test_spread_move_does_not_result_in_multiple_move_choices() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -101,11 +105,13 @@ fn test_spread_move_does_not_result_in_multiple_move_choices() {
 fn test_spikes_only_has_one_target() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -131,11 +137,13 @@ fn test_spikes_only_has_one_target() {
 fn test_cannot_target_fainted_pkmn() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -175,11 +183,13 @@ fn test_cannot_target_fainted_pkmn() {
 fn test_helping_hand_can_only_target_ally() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -218,11 +228,13 @@ fn test_helping_hand_can_only_target_ally() {
 fn test_self_boosting_move_only_has_1_target() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -259,11 +271,13 @@ fn test_self_boosting_move_only_has_1_target() {
 fn test_disabled_pkmn_cannot_use_last_used_move() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -312,11 +326,13 @@ fn test_disabled_pkmn_cannot_use_last_used_move() {
 fn test_disabled_with_one_moves_gives_no_move() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -352,11 +368,13 @@ fn test_disabled_with_one_moves_gives_no_move() {
 fn test_encored_and_disabled_means_no_moves() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -414,11 +432,13 @@ fn test_encored_and_disabled_means_no_moves() {
 fn test_pollenpuff_can_target_ally_and_opponents() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -473,11 +493,13 @@ fn test_pollenpuff_can_target_ally_and_opponents() {
 fn test_beatup_can_target_ally_with_ragefist() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -538,11 +560,13 @@ fn test_beatup_can_target_ally_with_ragefist() {
 fn test_beatup_can_only_target_ally_with_stamina() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -580,11 +604,13 @@ fn test_beatup_can_only_target_ally_with_stamina() {
 fn test_decorate_can_target_only_ally() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -621,11 +647,13 @@ fn test_decorate_can_target_only_ally() {
 fn test_coaching_can_target_only_ally() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -662,11 +690,13 @@ fn test_coaching_can_target_only_ally() {
 fn test_fakeout_is_an_option_when_just_switching_in() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -713,11 +743,13 @@ fn test_fakeout_is_an_option_when_just_switching_in() {
 fn test_electroshot_charging_can_target_either_opponent() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -763,11 +795,13 @@ fn test_electroshot_charging_can_target_either_opponent() {
 fn test_electroshot_executing_from_charge_can_target_either_opponent() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -815,11 +849,13 @@ fn test_electroshot_executing_from_charge_can_target_either_opponent() {
 fn test_fakeout_is_not_an_option_when_already_on_the_field() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -849,11 +885,13 @@ fn test_fakeout_is_not_an_option_when_already_on_the_field() {
 fn test_commanding_pkmn_cannot_select_any_moves() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -889,11 +927,13 @@ fn test_commanding_pkmn_cannot_select_any_moves() {
 fn test_commanding_pkmn_cannot_switch() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 100;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -936,12 +976,14 @@ fn test_commanding_pkmn_cannot_switch() {
 fn test_fainted_commanding_pkmn_must_switch() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 100;
     state.sides[0].pokemon.pkmn[0].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -1028,11 +1070,13 @@ fn test_mega_evolution_is_an_option_when_pkmn_can_mega() {
 fn test_mega_evolution_is_not_an_option_when_feature_not_enabled() {
     let mut state = State::default();
     state.sides[0].pokemon.pkmn[5].terastallized = true;
+    state.sides[0].dynamax_used = true;
     state.sides[0].pokemon.pkmn[5].hp = 0;
     state.sides[0].pokemon.pkmn[4].hp = 0;
     state.sides[0].pokemon.pkmn[3].hp = 0;
     state.sides[0].pokemon.pkmn[2].hp = 0;
     state.sides[1].pokemon.pkmn[5].terastallized = true;
+    state.sides[1].dynamax_used = true;
     state.sides[1].pokemon.pkmn[5].hp = 0;
     state.sides[1].pokemon.pkmn[4].hp = 0;
     state.sides[1].pokemon.pkmn[3].hp = 0;
@@ -1165,6 +1209,116 @@ fn test_cannot_mega_when_teammate_is_mega() {
     );
 }
 
+#[test]
+#[cfg(feature = "mega")]
+fn test_cannot_mega_when_side_has_already_used_mega() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[5].hp = 0;
+    state.sides[0].pokemon.pkmn[4].hp = 0;
+    state.sides[0].pokemon.pkmn[3].hp = 0;
+    state.sides[0].pokemon.pkmn[2].hp = 0;
+    state.sides[1].pokemon.pkmn[5].hp = 0;
+    state.sides[1].pokemon.pkmn[4].hp = 0;
+    state.sides[1].pokemon.pkmn[3].hp = 0;
+    state.sides[1].pokemon.pkmn[2].hp = 0;
+
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[1].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[1].moves);
+
+    // the pkmn that mega evolved is no longer on the field (e.g. fainted)
+    // and is not flagged as mega_evolved, but the side has used its mega
+    state.sides[0].mega_used = true;
+    state.sides[0].pokemon.pkmn[0].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[0].item = Items::CHARIZARDITEX;
+    state.sides[0].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+    state.sides[1].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_mega_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveMega(_, _, _)) || matches!(b, MoveChoice::MoveMega(_, _, _))
+    });
+
+    assert!(
+        !has_mega_option,
+        "Expected no MoveMega option because the side already used its mega evolution"
+    );
+}
+
+#[test]
+#[cfg(feature = "mega")]
+fn test_either_of_two_mega_capable_pkmn_can_mega_evolve() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[5].hp = 0;
+    state.sides[0].pokemon.pkmn[4].hp = 0;
+    state.sides[0].pokemon.pkmn[3].hp = 0;
+    state.sides[0].pokemon.pkmn[2].hp = 0;
+    state.sides[1].pokemon.pkmn[5].hp = 0;
+    state.sides[1].pokemon.pkmn[4].hp = 0;
+    state.sides[1].pokemon.pkmn[3].hp = 0;
+    state.sides[1].pokemon.pkmn[2].hp = 0;
+
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[1].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[1].moves);
+
+    state.sides[0].pokemon.pkmn[0].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[0].item = Items::CHARIZARDITEX;
+    state.sides[0].pokemon.pkmn[1].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[1].item = Items::CHARIZARDITEY;
+    state.sides[0].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+    state.sides[0].pokemon.pkmn[1].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+    state.sides[1].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let slot_a_megas = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(a, _)| matches!(a, MoveChoice::MoveMega(_, _, _)));
+    let slot_b_megas = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(_, b)| matches!(b, MoveChoice::MoveMega(_, _, _)));
+    let both_mega = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveMega(_, _, _)) && matches!(b, MoveChoice::MoveMega(_, _, _))
+    });
+
+    assert!(slot_a_megas);
+    assert!(slot_b_megas);
+    assert!(!both_mega);
+}
+
 #[test]
 #[cfg(feature = "mega")]
 fn test_only_one_slot_can_mega_evolve_per_turn() {
@@ -1297,3 +1451,348 @@ fn test_both_slots_cannot_mega_evolve_in_same_turn() {
         "Both active pokemon should not be able to mega evolve in the same turn"
     );
 }
+
+#[test]
+#[cfg(feature = "dynamax")]
+fn test_dynamax_is_an_option_for_each_active_pokemon() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[5].hp = 0;
+    state.sides[0].pokemon.pkmn[4].hp = 0;
+    state.sides[0].pokemon.pkmn[3].hp = 0;
+    state.sides[0].pokemon.pkmn[2].hp = 0;
+    state.sides[1].pokemon.pkmn[5].hp = 0;
+    state.sides[1].pokemon.pkmn[4].hp = 0;
+    state.sides[1].pokemon.pkmn[3].hp = 0;
+    state.sides[1].pokemon.pkmn[2].hp = 0;
+
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[1].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[1].pokemon.pkmn[1].moves);
+
+    state.sides[0].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+    state.sides[0].pokemon.pkmn[1].moves.m0 = Move {
+        id: Choices::TACKLE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+    };
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_slot_a_dynamax = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(a, _)| matches!(a, MoveChoice::MoveDynamax(_, _, _)));
+    let has_slot_b_dynamax = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(_, b)| matches!(b, MoveChoice::MoveDynamax(_, _, _)));
+    let double_dynamax_exists = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveDynamax(_, _, _))
+            && matches!(b, MoveChoice::MoveDynamax(_, _, _))
+    });
+
+    assert!(has_slot_a_dynamax);
+    assert!(has_slot_b_dynamax);
+    assert!(
+        !double_dynamax_exists,
+        "Both active pokemon should not be able to dynamax in the same turn"
+    );
+}
+
+#[test]
+#[cfg(feature = "dynamax")]
+fn test_dynamax_is_not_an_option_once_used() {
+    let mut state = State::default();
+    state.sides[0].dynamax_used = true;
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_dynamax_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveDynamax(_, _, _))
+            || matches!(b, MoveChoice::MoveDynamax(_, _, _))
+    });
+
+    assert!(!has_dynamax_option);
+}
+
+#[test]
+#[cfg(feature = "dynamax")]
+fn test_dynamax_is_not_an_option_after_using_another_gimmick() {
+    for gimmick in 0..3 {
+        let mut state = State::default();
+        match gimmick {
+            0 => state.sides[0].mega_used = true,
+            1 => state.sides[0].z_move_used = true,
+            _ => state.sides[0].pokemon.pkmn[5].terastallized = true,
+        }
+
+        let mut move_options = MoveOptions::new();
+        state.get_all_options(&mut move_options);
+
+        let has_dynamax_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+            matches!(a, MoveChoice::MoveDynamax(_, _, _))
+                || matches!(b, MoveChoice::MoveDynamax(_, _, _))
+        });
+
+        assert!(!has_dynamax_option);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "mega", feature = "dynamax"))]
+fn test_one_pokemon_cannot_mega_evolve_while_the_other_dynamaxes() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[0].item = Items::CHARIZARDITEX;
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_mega_option = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(a, _)| matches!(a, MoveChoice::MoveMega(_, _, _)));
+    let mega_and_dynamax_exists = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveMega(_, _, _)) && matches!(b, MoveChoice::MoveDynamax(_, _, _))
+    });
+
+    assert!(has_mega_option);
+    assert!(!mega_and_dynamax_exists);
+}
+
+#[test]
+#[cfg(all(feature = "mega", feature = "dynamax"))]
+fn test_mega_evolution_is_not_an_option_once_dynamax_is_used() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[0].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[0].item = Items::CHARIZARDITEX;
+    state.sides[0].dynamax_used = true;
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_mega_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveMega(_, _, _)) || matches!(b, MoveChoice::MoveMega(_, _, _))
+    });
+
+    assert!(!has_mega_option);
+}
+
+#[test]
+#[cfg(not(feature = "dynamax"))]
+fn test_dynamax_is_not_an_option_when_feature_not_enabled() {
+    let state = State::default();
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_dynamax_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveDynamax(_, _, _))
+            || matches!(b, MoveChoice::MoveDynamax(_, _, _))
+    });
+
+    assert!(
+        !has_dynamax_option,
+        "Expected no MoveDynamax option when feature not enabled"
+    );
+}
+
+#[test]
+fn test_dynamaxed_pokemon_only_gets_single_target_max_move_options() {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[5].hp = 0;
+    state.sides[0].pokemon.pkmn[4].hp = 0;
+    state.sides[0].pokemon.pkmn[3].hp = 0;
+    state.sides[0].pokemon.pkmn[2].hp = 0;
+    state.sides[0].dynamax_used = true;
+    state.sides[0]
+        .slot_a
+        .volatile_statuses
+        .insert(PokemonVolatileStatus::DYNAMAX);
+
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[0].moves);
+    state.sides[0].pokemon.pkmn[0].moves.m0 = Move {
+        id: Choices::EARTHQUAKE,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::EARTHQUAKE).unwrap().clone(),
+    };
+    state.sides[0].pokemon.pkmn[0].moves.m1 = Move {
+        id: Choices::PROTECT,
+        disabled: false,
+        pp: 32,
+        choice: MOVES.get(&Choices::PROTECT).unwrap().clone(),
+    };
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let mut slot_a_options = move_options
+        .side_one_combined_options
+        .iter()
+        .map(|(a, _)| *a)
+        .collect::<Vec<MoveChoice>>();
+    slot_a_options.dedup();
+
+    assert_eq!(
+        vec![
+            MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0
+            ),
+            MoveChoice::Move(
+                SlotReference::SlotB,
+                SideReference::SideTwo,
+                PokemonMoveIndex::M0
+            ),
+            MoveChoice::Move(
+                SlotReference::SlotA,
+                SideReference::SideOne,
+                PokemonMoveIndex::M1
+            ),
+        ],
+        slot_a_options
+    );
+}
+
+fn z_crystal_state() -> State {
+    let mut state = State::default();
+    state.sides[0].pokemon.pkmn[5].hp = 0;
+    state.sides[0].pokemon.pkmn[4].hp = 0;
+    state.sides[0].pokemon.pkmn[3].hp = 0;
+    state.sides[0].pokemon.pkmn[2].hp = 0;
+
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[0].moves);
+    disable_all_moves(&mut state.sides[0].pokemon.pkmn[1].moves);
+    for pkmn_index in [0, 1] {
+        let pkmn = &mut state.sides[0].pokemon.pkmn[pkmn_index];
+        pkmn.item = Items::ELECTRIUMZ;
+        pkmn.moves.m0 = Move {
+            id: Choices::THUNDERBOLT,
+            disabled: false,
+            pp: 32,
+            choice: MOVES.get(&Choices::THUNDERBOLT).unwrap().clone(),
+        };
+        pkmn.moves.m1 = Move {
+            id: Choices::TACKLE,
+            disabled: false,
+            pp: 32,
+            choice: MOVES.get(&Choices::TACKLE).unwrap().clone(),
+        };
+    }
+    state
+}
+
+#[test]
+#[cfg(feature = "zmoves")]
+fn test_z_move_is_only_an_option_for_moves_matching_the_z_crystal() {
+    let state = z_crystal_state();
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let slot_a_z_options = move_options
+        .side_one_combined_options
+        .iter()
+        .filter_map(|(a, _)| match a {
+            MoveChoice::MoveZ(_, _, _) => Some(*a),
+            _ => None,
+        })
+        .collect::<Vec<MoveChoice>>();
+
+    assert!(slot_a_z_options.contains(&MoveChoice::MoveZ(
+        SlotReference::SlotA,
+        SideReference::SideTwo,
+        PokemonMoveIndex::M0
+    )));
+    assert!(slot_a_z_options.contains(&MoveChoice::MoveZ(
+        SlotReference::SlotB,
+        SideReference::SideTwo,
+        PokemonMoveIndex::M0
+    )));
+    assert!(!slot_a_z_options
+        .iter()
+        .any(|z| matches!(z, MoveChoice::MoveZ(_, _, PokemonMoveIndex::M1))));
+}
+
+#[test]
+#[cfg(feature = "zmoves")]
+fn test_both_active_pokemon_cannot_use_a_z_move_together() {
+    let state = z_crystal_state();
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_slot_b_z_move = move_options
+        .side_one_combined_options
+        .iter()
+        .any(|(_, b)| matches!(b, MoveChoice::MoveZ(_, _, _)));
+    let double_z_move_exists = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveZ(_, _, _)) && matches!(b, MoveChoice::MoveZ(_, _, _))
+    });
+
+    assert!(has_slot_b_z_move);
+    assert!(
+        !double_z_move_exists,
+        "Both active pokemon should not be able to use a z-move in the same turn"
+    );
+}
+
+#[test]
+#[cfg(feature = "zmoves")]
+fn test_z_move_is_not_an_option_once_used() {
+    let mut state = z_crystal_state();
+    state.sides[0].z_move_used = true;
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_z_move_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveZ(_, _, _)) || matches!(b, MoveChoice::MoveZ(_, _, _))
+    });
+
+    assert!(!has_z_move_option);
+}
+
+#[test]
+#[cfg(all(feature = "mega", feature = "zmoves"))]
+fn test_one_pokemon_can_mega_evolve_while_the_other_uses_a_z_move() {
+    let mut state = z_crystal_state();
+    state.sides[0].pokemon.pkmn[0].id = PokemonName::CHARIZARD;
+    state.sides[0].pokemon.pkmn[0].item = Items::CHARIZARDITEX;
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    assert!(move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveMega(_, _, _)) && matches!(b, MoveChoice::MoveZ(_, _, _))
+    }));
+}
+
+#[test]
+#[cfg(not(feature = "zmoves"))]
+fn test_z_move_is_not_an_option_when_feature_not_enabled() {
+    let state = z_crystal_state();
+
+    let mut move_options = MoveOptions::new();
+    state.get_all_options(&mut move_options);
+
+    let has_z_move_option = move_options.side_one_combined_options.iter().any(|(a, b)| {
+        matches!(a, MoveChoice::MoveZ(_, _, _)) || matches!(b, MoveChoice::MoveZ(_, _, _))
+    });
+
+    assert!(
+        !has_z_move_option,
+        "Expected no MoveZ option when feature not enabled"
+    );
+}